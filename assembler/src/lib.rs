@@ -3,12 +3,15 @@ use std::collections::HashMap;
 
 use common::{
     code::{Asm, Code},
+    debug::{DebugInfo, Scope},
     fileposition::FilePosition,
+    shape,
 };
 
 #[derive(Debug)]
 pub struct Assembler {
     pub runtime_error_table: HashMap<usize, FilePosition>,
+    pub debug_info: DebugInfo,
     pub input: Vec<Asm>,
     pub nva: Vec<Asm>,
     pub output: Vec<u8>,
@@ -24,6 +27,7 @@ pub fn new(input: Vec<Asm>) -> Assembler {
         forwardjumps: vec![],
         nva: vec![],
         runtime_error_table: HashMap::default(),
+        debug_info: common::debug::new(),
     }
 }
 
@@ -35,10 +39,31 @@ pub fn new_empty() -> Assembler {
         forwardjumps: vec![],
         nva: vec![],
         runtime_error_table: HashMap::default(),
+        debug_info: common::debug::new(),
     }
 }
 
+// a length prefixed operand, laid out like the one of STRING
+fn text(output: &mut Vec<u8>, text: &str) {
+    output.extend_from_slice(&text.len().to_le_bytes());
+    output.extend_from_slice(text.as_bytes());
+}
+
 impl Assembler {
+    // assembles input after what is already in output, the labels of the
+    // input before it stay known so jumps can reach back into it
+    pub fn append(&mut self, input: Vec<Asm>) {
+        self.input = input;
+        self.forwardjumps.clear();
+        self.assemble();
+    }
+
+    // forgets the labels from address on, once that output was thrown away
+    pub fn forget(&mut self, address: usize) {
+        self.labels
+            .retain(|_, destination| (*destination as usize) < address);
+    }
+
     // pub fn assemble_from_nva(&mut self, fileinput: TokenList) {
     //     let asmfile = fileinput.clone();
     //     let mut ci = 0;
@@ -330,13 +355,43 @@ impl Assembler {
     // }
 
     pub fn assemble(&mut self) {
+        // the statement the following instructions belong to
+        let mut line: Option<FilePosition> = None;
+        let mut statement_start = false;
+        // functions being assembled, with the scope that describes their locals
+        let mut functions: Vec<(u64, Option<usize>)> = vec![];
+
         for instruction in self.input.iter().cloned() {
+            let address = self.output.len();
             match instruction {
+                Asm::LINE(position) => {
+                    line = Some(position);
+                    statement_start = true;
+                }
+                Asm::SCOPE(name, locals) => {
+                    self.debug_info.scopes.push(Scope {
+                        name,
+                        start: address,
+                        end: usize::MAX,
+                        locals,
+                    });
+                    if let Some((_, scope @ None)) = functions.last_mut() {
+                        *scope = Some(self.debug_info.scopes.len() - 1);
+                    }
+                }
                 Asm::EXIT => {
                     self.output.push(Code::EXIT);
                 }
                 Asm::LABEL(label) => {
                     self.labels.insert(label, self.output.len() as u64);
+                    if let Some((end, scope)) = functions.last() {
+                        if *end == label {
+                            if let Some(index) = scope {
+                                self.debug_info.scopes[*index].end = address;
+                            }
+                            functions.pop();
+                        }
+                    }
                 }
                 Asm::RET(has_return) => {
                     if has_return {
@@ -423,6 +478,7 @@ impl Assembler {
                 Asm::FDIV => self.output.push(Code::FDIV),
                 Asm::FMUL => self.output.push(Code::FMUL),
                 Asm::FUNCTION(target) => {
+                    functions.push((target, None));
                     if let Some(destination) = self.labels.get(&target) {
                         self.output.push(Code::FUNCTION);
                         let t = (*destination as u32).to_le_bytes();
@@ -482,8 +538,27 @@ impl Assembler {
                 Asm::EQUALS => {
                     self.output.push(Code::EQUALS);
                 }
+                Asm::CMP => {
+                    self.output.push(Code::CMP);
+                }
                 Asm::FREE => self.output.push(Code::FREE),
                 Asm::CLONE => self.output.push(Code::CLONE),
+                Asm::LAYOUT(layout) => {
+                    self.output.push(Code::LAYOUT);
+                    text(&mut self.output, &shape::encode(&layout));
+                }
+                Asm::SHOWWITH(name) => {
+                    self.output.push(Code::SHOWWITH);
+                    text(&mut self.output, &name);
+                }
+                Asm::SHOW(shape) => {
+                    self.output.push(Code::SHOW);
+                    text(&mut self.output, &shape::encode(&shape));
+                }
+                Asm::TAG(name) => {
+                    self.output.push(Code::TAG);
+                    text(&mut self.output, &name);
+                }
                 Asm::STRING(string) => {
                     self.output.push(Code::STRING);
                     let size = string.len().to_le_bytes();
@@ -504,6 +579,7 @@ impl Assembler {
                 Asm::NOT => self.output.push(Code::NOT),
                 Asm::NEG => self.output.push(Code::NEG),
                 Asm::CLOSURE(target) => {
+                    functions.push((target, None));
                     if let Some(destination) = self.labels.get(&target) {
                         self.output.push(Code::CLOSURE);
                         let t = (*destination as u32).to_le_bytes();
@@ -549,6 +625,23 @@ impl Assembler {
                         .insert(self.output.len(), file_position);
                 }
             }
+
+            if self.output.len() > address {
+                if let Some(position) = &line {
+                    self.debug_info.positions.insert(address, position.clone());
+                    if statement_start {
+                        self.debug_info.statements.insert(address);
+                        statement_start = false;
+                    }
+                }
+            }
+        }
+
+        // the top level scope runs to the end of the program
+        for scope in self.debug_info.scopes.iter_mut() {
+            if scope.end == usize::MAX {
+                scope.end = self.output.len();
+            }
         }
 
         for (target, replace) in self.forwardjumps.iter() {
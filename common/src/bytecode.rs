@@ -0,0 +1,80 @@
+// NovaError is what every step of a build or run fails with, the same as in
+// the rest of the tree, so it is returned unboxed
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{debug::DebugInfo, error::NovaError, fileposition::FilePosition};
+
+// bumped whenever the opcode layout or the container itself changes
+pub const VERSION: u32 = 5;
+pub const MAGIC: &[u8; 4] = b"NOVA";
+
+// a compiled program that can be run without the source,
+// natives are linked by name when the file is loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bytecode {
+    pub program: Vec<u8>,
+    pub runtime_error_table: HashMap<usize, FilePosition>,
+    pub natives: Vec<String>,
+    // names of the global slots, so the host can find functions to call
+    pub globals: Vec<String>,
+    // function names and positions, for stack traces
+    pub debug_info: DebugInfo,
+}
+
+pub fn new(
+    program: Vec<u8>,
+    runtime_error_table: HashMap<usize, FilePosition>,
+    natives: Vec<String>,
+    globals: Vec<String>,
+    debug_info: DebugInfo,
+) -> Bytecode {
+    Bytecode {
+        program,
+        runtime_error_table,
+        natives,
+        globals,
+        debug_info,
+    }
+}
+
+impl Bytecode {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NovaError> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        match bincode::serialize(self) {
+            Ok(body) => bytes.extend(body),
+            Err(error) => {
+                return Err(NovaError::File {
+                    msg: format!("could not serialize bytecode: {error}"),
+                })
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bytecode, NovaError> {
+        if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+            return Err(NovaError::File {
+                msg: "not a nova bytecode file".to_string(),
+            });
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != VERSION {
+            return Err(NovaError::File {
+                msg: format!(
+                    "bytecode version {version} is not supported, expected version {VERSION}"
+                ),
+            });
+        }
+        match bincode::deserialize(&bytes[8..]) {
+            Ok(bytecode) => Ok(bytecode),
+            Err(error) => Err(NovaError::File {
+                msg: format!("corrupt bytecode file: {error}"),
+            }),
+        }
+    }
+}
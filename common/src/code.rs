@@ -1,4 +1,7 @@
-use crate::fileposition::FilePosition;
+use crate::{
+    fileposition::FilePosition,
+    shape::{Layout, Shape},
+};
 
 pub struct Code {}
 
@@ -126,6 +129,18 @@ impl Code {
 
     pub const EXIT: u8 = 77;
     pub const ERROR: u8 = 78;
+
+    // -1, 0 or 1 as the first value orders before, with or after the second
+    pub const CMP: u8 = 79;
+
+    // how structs and enums are shown, and the value on the stack as a string
+    pub const LAYOUT: u8 = 80;
+    pub const SHOWWITH: u8 = 81;
+    pub const SHOW: u8 = 82;
+
+    // the name a struct or enum carries in its hidden type field, shared by
+    // every value of the type so the vm can tell what a list really is
+    pub const TAG: u8 = 83;
 }
 
 pub fn byte_to_string(byte: u8) -> String {
@@ -205,6 +220,11 @@ pub fn byte_to_string(byte: u8) -> String {
         Code::DUP => "DUP",
         Code::ISSOME => "ISSOME",
         Code::UNWRAP => "UNWRAP",
+        Code::CMP => "CMP",
+        Code::LAYOUT => "LAYOUT",
+        Code::SHOWWITH => "SHOWWITH",
+        Code::SHOW => "SHOW",
+        Code::TAG => "TAG",
         _ => "Unknown", // Handle the case where the byte is not in the enum.
     }
     .to_string()
@@ -267,6 +287,7 @@ pub enum Asm {
     FGTR,
 
     EQUALS,
+    CMP,
     NOT,
     NEG,
 
@@ -295,4 +316,14 @@ pub enum Asm {
 
     EXIT,
     ERROR(FilePosition),
+
+    // showing values
+    LAYOUT(Layout),
+    SHOWWITH(String),
+    SHOW(Shape),
+    TAG(String),
+
+    // debug information, these emit no bytecode
+    LINE(FilePosition),
+    SCOPE(String, Vec<String>),
 }
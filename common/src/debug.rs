@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fileposition::FilePosition;
+
+// source level information produced by the assembler, it maps the
+// bytecode back to the file positions and names it was compiled from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebugInfo {
+    // the position of the statement each instruction belongs to
+    pub positions: HashMap<usize, FilePosition>,
+    // addresses where a statement starts
+    pub statements: HashSet<usize>,
+    pub scopes: Vec<Scope>,
+}
+
+// a function body or the top level program along with the names of its locals,
+// in the order they are laid out from the frame offset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scope {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub locals: Vec<String>,
+}
+
+pub fn new() -> DebugInfo {
+    DebugInfo::default()
+}
+
+impl DebugInfo {
+    pub fn position(&self, address: usize) -> Option<&FilePosition> {
+        self.positions.get(&address)
+    }
+
+    // the position of the closest instruction before the address
+    pub fn position_before(&self, address: usize) -> Option<&FilePosition> {
+        (0..address)
+            .rev()
+            .find_map(|address| self.positions.get(&address))
+    }
+
+    // the position the scope around the address is at, a scope that was
+    // just entered has not reached a statement yet and reports its first one
+    pub fn position_in_scope(&self, address: usize) -> Option<&FilePosition> {
+        let Some(scope) = self.scope(address) else {
+            return self.position_before(address + 1);
+        };
+        (scope.start..=address)
+            .rev()
+            .find_map(|address| self.positions.get(&address))
+            .or_else(|| (address..scope.end).find_map(|address| self.positions.get(&address)))
+    }
+
+    // adds what was assembled after the instructions described so far
+    pub fn extend(&mut self, other: DebugInfo) {
+        self.positions.extend(other.positions);
+        self.statements.extend(other.statements);
+        self.scopes.extend(other.scopes);
+    }
+
+    // forgets the instructions from address on
+    pub fn truncate(&mut self, address: usize) {
+        self.positions.retain(|start, _| *start < address);
+        self.statements.retain(|start| *start < address);
+        self.scopes.retain(|scope| scope.start < address);
+    }
+
+    pub fn is_statement(&self, address: usize) -> bool {
+        self.statements.contains(&address)
+    }
+
+    // the innermost scope that contains the address
+    pub fn scope(&self, address: usize) -> Option<&Scope> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.start <= address && address < scope.end)
+            .min_by_key(|scope| scope.end - scope.start)
+    }
+
+    // every statement address that starts on the given line of the file
+    pub fn addresses(&self, filepath: &str, line: usize) -> Vec<usize> {
+        let mut addresses = self
+            .statements
+            .iter()
+            .filter(|address| match self.positions.get(address) {
+                Some(position) => position.line == line && same_file(&position.filepath, filepath),
+                None => false,
+            })
+            .copied()
+            .collect::<Vec<usize>>();
+        addresses.sort();
+        addresses
+    }
+}
+
+// breakpoints may name a file by its full path or just the end of it
+fn same_file(filepath: &str, name: &str) -> bool {
+    if filepath == name || filepath.ends_with(&format!("/{name}")) {
+        return true;
+    }
+    match (std::fs::canonicalize(filepath), std::fs::canonicalize(name)) {
+        (Ok(filepath), Ok(name)) => filepath == name,
+        _ => false,
+    }
+}
@@ -18,6 +18,21 @@ pub struct Environment {
     pub generic_type_struct: HashMap<String, Vec<String>>,
     pub generic_type_map: HashMap<String, String>,
     pub live_generics: Vec<table::Table<String>>,
+    // the methods each trait asks for, $Self stands for the implementing type
+    pub traits: HashMap<String, Vec<(String, TType)>>,
+    // the types that implement each trait
+    pub implementations: HashMap<String, Vec<Implementation>>,
+    // the trait bounds on the generics of each function, as generic and trait
+    pub bounds: HashMap<String, Vec<(String, String)>>,
+    pub live_bounds: Vec<Vec<(String, String)>>,
+}
+
+// a type implementing a trait, along with the function id and type of
+// every method in the order the trait declares them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Implementation {
+    pub ttype: TType,
+    pub functions: Vec<(String, TType)>,
 }
 
 pub fn new_environment() -> Environment {
@@ -31,6 +46,10 @@ pub fn new_environment() -> Environment {
         generic_type_map: HashMap::default(),
         live_generics: vec![table::new()],
         enums: table::new(),
+        traits: HashMap::default(),
+        implementations: HashMap::default(),
+        bounds: HashMap::default(),
+        live_bounds: vec![],
     }
 }
 
@@ -96,6 +115,11 @@ impl Environment {
         self.values.last().unwrap().get(symbol).cloned()
     }
 
+    // looks through every scope, innermost first
+    pub fn find(&self, symbol: &str) -> Option<&Symbol> {
+        self.values.iter().rev().find_map(|scope| scope.get(symbol))
+    }
+
     pub fn get_type(&mut self, symbol: &str) -> Option<TType> {
         if let Some(s) = self.values.last().unwrap().get(symbol) {
             Some(s.ttype.clone())
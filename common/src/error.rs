@@ -1,5 +1,6 @@
 use crate::{fileposition::FilePosition, ttype::TType};
 use colored::Colorize;
+use serde::Serialize;
 use std::io::{self, BufRead};
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<std::fs::File>>>
@@ -11,36 +12,157 @@ where
 }
 
 pub fn print_line(position: &FilePosition, msg: &str) {
-    if let Ok(lines) = read_lines(&position.filepath) {
-        let line_number_width = position.line.to_string().chars().count();
-
-        for (linenumber, line_content) in lines.enumerate() {
-            let current_line = linenumber + 1;
-            if current_line == position.line {
-                if let Ok(line) = line_content {
-                    // Print line number and line content with padding
-                    print!("{:<width$} |\n", "", width = line_number_width);
-                    println!(
-                        "{:width$} | {}",
-                        current_line,
-                        line,
-                        width = line_number_width
-                    );
-
-                    // Print marker line with padding for alignment
-                    print!("{:<width$} |", "", width = line_number_width);
-                    if let Some(mut row) = position.row.checked_sub(1) {
-                        row += 1;
-                        println!("{: <row$}{} {}", "", "^".red(), msg.bright_red(), row = row);
-                    } else {
-                        println!(" {}", "^".red());
-                    }
-                }
+    print_span(position, msg, '^');
+}
+
+// a secondary location, marked with dashes so it reads as context
+pub fn print_label(position: &FilePosition, msg: &str) {
+    println!(
+        "{} {}:{}:{}",
+        "-->".bright_blue(),
+        position.filepath,
+        position.line,
+        position.row
+    );
+    print_span(position, msg, '-');
+}
+
+// prints the source lines the span covers and marks the spanned text,
+// sources that cannot be read again, like repl input, only show the message
+fn print_span(position: &FilePosition, msg: &str, marker: char) {
+    let lines = match read_lines(&position.filepath) {
+        Ok(lines) => lines.map_while(Result::ok).collect::<Vec<String>>(),
+        Err(_) => vec![],
+    };
+    match render_span(&lines, position, msg, marker) {
+        Some(rendered) => {
+            for line in rendered {
+                println!("{line}");
             }
         }
+        None => println!("{}", msg.bright_red()),
     }
 }
 
+// the lines print_span shows, long spans only show their first and last two lines.
+// None when the span starts outside of the source
+fn render_span(
+    lines: &[String],
+    position: &FilePosition,
+    msg: &str,
+    marker: char,
+) -> Option<Vec<String>> {
+    let first = lines.get(position.line.wrapping_sub(1))?;
+    let end_line = position.end_line.clamp(position.line, lines.len());
+    let line_number_width = end_line.to_string().chars().count();
+    let paint = |text: String| match marker {
+        '^' => text.red(),
+        _ => text.bright_blue(),
+    };
+    let label = |text: &str| match marker {
+        '^' => text.bright_red(),
+        _ => text.bright_blue(),
+    };
+    let gutter = |line: Option<usize>| match line {
+        Some(line) => format!("{:width$} | ", line, width = line_number_width),
+        None => format!("{:width$} | ", "", width = line_number_width),
+    };
+    let mut rendered = vec![gutter(None).trim_end().to_string()];
+
+    let start = position.row.max(1);
+    if end_line == position.line {
+        let length = position.end_row.saturating_sub(start).max(1);
+        rendered.push(format!("{}{}", gutter(Some(position.line)), first));
+        rendered.push(format!(
+            "{}{: <row$}{} {}",
+            gutter(None),
+            "",
+            paint(marker.to_string().repeat(length)),
+            label(msg),
+            row = start - 1
+        ));
+        return Some(rendered);
+    }
+
+    // from the start of the span to the end of its first line
+    let length = first.chars().count().saturating_sub(start - 1).max(1);
+    rendered.push(format!("{}{}", gutter(Some(position.line)), first));
+    rendered.push(format!(
+        "{}{: <row$}{}",
+        gutter(None),
+        "",
+        paint(marker.to_string().repeat(length)),
+        row = start - 1
+    ));
+    for line in position.line + 1..end_line {
+        if end_line - position.line > 4 && line > position.line + 1 && line < end_line - 1 {
+            if line == position.line + 2 {
+                rendered.push("...".bright_blue().to_string());
+            }
+            continue;
+        }
+        rendered.push(format!("{}{}", gutter(Some(line)), lines[line - 1]));
+    }
+    // from the first non blank character of the last line to the end of the span
+    let last = &lines[end_line - 1];
+    let indent = last.chars().take_while(|c| c.is_whitespace()).count();
+    let length = position.end_row.saturating_sub(indent + 1).max(1);
+    rendered.push(format!("{}{}", gutter(Some(end_line)), last));
+    rendered.push(format!(
+        "{}{: <row$}{} {}",
+        gutter(None),
+        "",
+        paint(marker.to_string().repeat(length)),
+        label(msg),
+        row = indent
+    ));
+    Some(rendered)
+}
+
+// how errors are presented, json prints one object per line for tools to parse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+// the structured form of an error, positions are only known for some variants
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub kind: String,
+    pub severity: String,
+    pub message: String,
+    pub note: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    pub related: Vec<Related>,
+    // the nova functions that were running, innermost first
+    pub trace: Vec<DiagnosticFrame>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticFrame {
+    pub function: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub count: usize,
+}
+
+// a secondary location that explains the error
+#[derive(Debug, Clone, Serialize)]
+pub struct Related {
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum NovaError {
     File {
@@ -83,9 +205,182 @@ pub enum NovaError {
         msg: String,
         position: FilePosition,
     },
+    // a runtime error along with the nova functions that were running
+    Traced {
+        error: Box<NovaError>,
+        trace: Vec<Frame>,
+    },
+    // the program went over one of the limits the host set
+    Limit {
+        limit: Limit,
+        msg: String,
+        position: Option<FilePosition>,
+    },
+    // code that compiles but is most likely a mistake, it does not stop the build
+    Warning {
+        msg: String,
+        note: String,
+        position: FilePosition,
+    },
+}
+
+// a function on the callstack and the position it was at, recursion
+// that repeats the same frame is counted instead of listed
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub function: String,
+    pub position: Option<FilePosition>,
+    pub count: usize,
+}
+
+// the execution limits a vm can enforce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Steps,
+    Heap,
+    Stack,
+    Depth,
 }
 
 impl NovaError {
+    // a single line description without the source context,
+    // for tools that present the error themselves
+    pub fn message(&self) -> String {
+        match self {
+            NovaError::File { msg }
+            | NovaError::Lexing { msg, .. }
+            | NovaError::Parsing { msg, .. }
+            | NovaError::Compiler { msg, .. }
+            | NovaError::Runtime { msg }
+            | NovaError::RuntimeWithPos { msg, .. }
+            | NovaError::SimpleTypeError { msg, .. }
+            | NovaError::Limit { msg, .. }
+            | NovaError::Warning { msg, .. } => msg.clone(),
+            NovaError::Traced { error, .. } => error.message(),
+            NovaError::TypeError {
+                msg,
+                expected,
+                found,
+                ..
+            } => format!("{msg}, expected type: {expected}, found type: {found}"),
+            NovaError::TypeMismatch {
+                expected, found, ..
+            } => format!(
+                "expected type: {}, found type: {}",
+                expected.to_string(),
+                found.to_string()
+            ),
+        }
+    }
+
+    pub fn position(&self) -> Option<&FilePosition> {
+        match self {
+            NovaError::Lexing { position, .. }
+            | NovaError::Parsing { position, .. }
+            | NovaError::RuntimeWithPos { position, .. }
+            | NovaError::TypeError { position, .. }
+            | NovaError::TypeMismatch { position, .. }
+            | NovaError::SimpleTypeError { position, .. }
+            | NovaError::Warning { position, .. } => Some(position),
+            NovaError::Limit { position, .. } => position.as_ref(),
+            // errors without a position of their own happened where the innermost frame is
+            NovaError::Traced { error, trace } => error
+                .position()
+                .or_else(|| trace.iter().find_map(|frame| frame.position.as_ref())),
+            NovaError::File { .. } | NovaError::Compiler { .. } | NovaError::Runtime { .. } => None,
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        let (kind, note) = match self {
+            NovaError::File { .. } => ("file", None),
+            NovaError::Lexing { note, .. } => ("lexing", Some(note.clone())),
+            NovaError::Parsing { note, .. } => ("parsing", Some(note.clone())),
+            NovaError::Compiler { note, .. } => ("compiler", Some(note.clone())),
+            NovaError::Runtime { .. } | NovaError::RuntimeWithPos { .. } => ("runtime", None),
+            NovaError::TypeError { msg, .. } => ("type_error", Some(msg.clone())),
+            NovaError::TypeMismatch { .. } => ("type_mismatch", None),
+            NovaError::SimpleTypeError { .. } => ("type_error", None),
+            NovaError::Limit { .. } => ("limit", None),
+            NovaError::Warning { note, .. } => ("warning", Some(note.clone())),
+            NovaError::Traced { error, trace } => {
+                let mut diagnostic = error.diagnostic();
+                if diagnostic.file.is_none() {
+                    if let Some(position) = self.position() {
+                        diagnostic.file = Some(position.filepath.clone());
+                        diagnostic.line = Some(position.line);
+                        diagnostic.column = Some(position.row);
+                        diagnostic.end_line = Some(position.end_line);
+                        diagnostic.end_column = Some(position.end_row);
+                    }
+                }
+                diagnostic.trace = trace
+                    .iter()
+                    .map(|frame| DiagnosticFrame {
+                        function: frame.function.clone(),
+                        file: frame.position.as_ref().map(|p| p.filepath.clone()),
+                        line: frame.position.as_ref().map(|p| p.line),
+                        column: frame.position.as_ref().map(|p| p.row),
+                        count: frame.count,
+                    })
+                    .collect();
+                return diagnostic;
+            }
+        };
+        let message = match self {
+            NovaError::TypeError {
+                expected, found, ..
+            } => format!("expected type: {expected}, found type: {found}"),
+            _ => self.message(),
+        };
+        let related = match self {
+            NovaError::Parsing {
+                extra: Some(extra), ..
+            } => extra
+                .iter()
+                .map(|(message, position)| Related {
+                    message: message.clone(),
+                    file: position.filepath.clone(),
+                    line: position.line,
+                    column: position.row,
+                    end_line: position.end_line,
+                    end_column: position.end_row,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let position = self.position();
+        Diagnostic {
+            kind: kind.to_string(),
+            severity: match self {
+                NovaError::Warning { .. } => "warning",
+                _ => "error",
+            }
+            .to_string(),
+            message,
+            // empty notes carry no information
+            note: note.filter(|note| !note.is_empty()),
+            file: position.map(|position| position.filepath.clone()),
+            line: position.map(|position| position.line),
+            column: position.map(|position| position.row),
+            end_line: position.map(|position| position.end_line),
+            end_column: position.map(|position| position.end_row),
+            related,
+            trace: vec![],
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.diagnostic()).unwrap_or_default()
+    }
+
+    pub fn report(&self, format: MessageFormat) {
+        match format {
+            MessageFormat::Human => self.show(),
+            MessageFormat::Json => println!("{}", self.to_json()),
+        }
+    }
+
     pub fn show(&self) {
         match &self {
             NovaError::File { msg } => {
@@ -122,7 +417,7 @@ impl NovaError {
                 print_line(position, &msg);
                 if let Some(extra_notes) = extra {
                     for (extra_msg, extra_position) in extra_notes {
-                        print_line(&extra_position, &extra_msg);
+                        print_label(extra_position, extra_msg);
                     }
                 }
                 println!("{}: {}", "Note".bright_yellow(), note.bright_yellow());
@@ -198,6 +493,136 @@ impl NovaError {
                 );
                 print_line(position, &msg);
             }
+            NovaError::Limit {
+                msg,
+                position: Some(position),
+                ..
+            } => {
+                println!(
+                    "{} in {}:{}:{}",
+                    "Limit Error".bright_red(),
+                    position.filepath,
+                    position.line,
+                    position.row
+                );
+                print_line(position, msg);
+            }
+            NovaError::Limit { msg, .. } => {
+                println!("Limit Error: {}", msg.bright_red());
+            }
+            NovaError::Warning {
+                msg,
+                note,
+                position,
+            } => {
+                println!(
+                    "{} in {}:{}:{}",
+                    "Warning".bright_yellow(),
+                    position.filepath,
+                    position.line,
+                    position.row
+                );
+                print_line(position, msg);
+                println!("{}: {}", "Note".bright_yellow(), note.bright_yellow());
+            }
+            NovaError::Traced { error, trace } => {
+                match (error.as_ref(), self.position()) {
+                    (NovaError::Runtime { msg }, Some(position)) => NovaError::RuntimeWithPos {
+                        msg: msg.clone(),
+                        position: position.clone(),
+                    }
+                    .show(),
+                    _ => error.show(),
+                }
+                println!("{}:", "Stack trace".bright_yellow());
+                for frame in trace.iter() {
+                    let location = match &frame.position {
+                        Some(position) => {
+                            format!("{}:{}:{}", position.filepath, position.line, position.row)
+                        }
+                        None => "unknown location".to_string(),
+                    };
+                    match frame.count {
+                        1 => println!("  at {} ({location})", frame.function),
+                        count => println!("  at {} ({location}) x {count}", frame.function),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(line: usize, row: usize, end_line: usize, end_row: usize) -> FilePosition {
+        FilePosition {
+            filepath: "spanned.nv".to_string(),
+            line,
+            row,
+            end_line,
+            end_row,
         }
     }
+
+    fn render(source: &str, position: FilePosition) -> Option<String> {
+        colored::control::set_override(false);
+        let lines = source.lines().map(str::to_string).collect::<Vec<String>>();
+        render_span(&lines, &position, "message", '^').map(|rendered| rendered.join("\n"))
+    }
+
+    #[test]
+    fn single_line() {
+        assert_eq!(
+            render("let x = 1\nlet y = x + true\n", position(2, 9, 2, 17)).unwrap(),
+            "  |\n2 | let y = x + true\n  |         ^^^^^^^^ message"
+        );
+        // an empty span still gets a marker
+        assert_eq!(
+            render("let x = 1\n", position(1, 5, 1, 5)).unwrap(),
+            "  |\n1 | let x = 1\n  |     ^ message"
+        );
+    }
+
+    #[test]
+    fn long_spans_are_elided() {
+        let source = (1..=12)
+            .map(|line| format!("    line{line}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        assert_eq!(
+            render(&source, position(3, 5, 10, 10)).unwrap(),
+            "   |
+ 3 |     line3
+   |     ^^^^^
+ 4 |     line4
+...
+ 9 |     line9
+10 |     line10
+   |     ^^^^^ message"
+        );
+        // up to four lines are shown in full
+        assert_eq!(
+            render(&source, position(3, 5, 6, 10)).unwrap(),
+            "  |
+3 |     line3
+  |     ^^^^^
+4 |     line4
+5 |     line5
+6 |     line6
+  |     ^^^^^ message"
+        );
+    }
+
+    #[test]
+    fn spans_outside_of_the_source() {
+        assert_eq!(render("let x = 1\n", position(3, 1, 3, 2)), None);
+        assert_eq!(render("", position(1, 1, 1, 2)), None);
+        // a span that ends past the source stops at its last line
+        assert_eq!(
+            render("let x = [\n    1,\n", position(1, 9, 7, 2)).unwrap(),
+            "  |\n1 | let x = [\n  |         ^\n2 |     1,\n  |     ^ message"
+        );
+    }
 }
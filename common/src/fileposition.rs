@@ -1,10 +1,32 @@
+use serde::{Deserialize, Serialize};
+
 use crate::error::NovaError;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// a span of source text, line and row are where it starts and the end
+// row is one past its last character
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FilePosition {
     pub filepath: String,
     pub line: usize,
     pub row: usize,
+    pub end_line: usize,
+    pub end_row: usize,
+}
+
+impl FilePosition {
+    // the span from the start of self to the end of other
+    pub fn to(&self, other: &FilePosition) -> FilePosition {
+        let mut span = self.clone();
+        if (other.end_line, other.end_row) > (self.end_line, self.end_row) {
+            span.end_line = other.end_line;
+            span.end_row = other.end_row;
+        }
+        span
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.end_line > self.line
+    }
 }
 
 pub fn load_file_content(filepath: &str) -> Result<String, NovaError> {
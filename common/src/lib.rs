@@ -1,9 +1,14 @@
+pub mod bytecode;
 pub mod code;
+pub mod debug;
 pub mod environment;
 pub mod error;
 pub mod fileposition;
 pub mod gen;
 pub mod nodes;
+pub mod resolver;
+pub mod shape;
+pub mod symbols;
 pub mod table;
 pub mod tokens;
 pub mod ttype;
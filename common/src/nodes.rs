@@ -56,6 +56,7 @@ pub enum Statement {
         identifier: String,
         expr: Expr,
         global: bool,
+        position: FilePosition,
     },
     Function {
         ttype: TType,
@@ -77,22 +78,26 @@ pub enum Statement {
     Return {
         ttype: TType,
         expr: Expr,
+        position: FilePosition,
     },
     Expression {
         ttype: TType,
         expr: Expr,
+        position: FilePosition,
     },
     If {
         ttype: TType,
         test: Expr,
         body: Vec<Statement>,
         alternative: Option<Vec<Statement>>,
+        position: FilePosition,
     },
     Unwrap {
         ttype: TType,
         identifier: String,
         body: Vec<Statement>,
         alternative: Option<Vec<Statement>>,
+        position: FilePosition,
     },
     IfLet {
         ttype: TType,
@@ -101,21 +106,25 @@ pub enum Statement {
         body: Vec<Statement>,
         alternative: Option<Vec<Statement>>,
         global: bool,
+        position: FilePosition,
     },
     While {
         test: Expr,
         body: Vec<Statement>,
+        position: FilePosition,
     },
     For {
         init: Expr,
         test: Expr,
         inc: Expr,
         body: Vec<Statement>,
+        position: FilePosition,
     },
     Foreach {
         identifier: String,
         expr: Expr,
         body: Vec<Statement>,
+        position: FilePosition,
     },
     ForRange {
         identifier: String,
@@ -124,6 +133,7 @@ pub enum Statement {
         inclusive: bool,
         step: Option<Expr>,
         body: Vec<Statement>,
+        position: FilePosition,
     },
     Block {
         body: Vec<Statement>,
@@ -132,11 +142,90 @@ pub enum Statement {
     Match {
         ttype: TType,
         expr: Expr,
-        arms: Vec<(usize, Option<String>, Vec<Statement>)>,
-        default: Option<Vec<Statement>>,
+        arms: Vec<Arm>,
+        position: FilePosition,
+    },
+}
+
+// an arm runs when its pattern matches and its guard, if any, is true
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Vec<Statement>,
+}
+
+// the shape a match arm tests a value against. struct patterns list every
+// field in order, the ones left out are wildcards
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding {
+        name: String,
+    },
+    Literal {
+        value: Atom,
+    },
+    Range {
+        start: Atom,
+        end: Atom,
+        inclusive: bool,
+    },
+    None,
+    Some {
+        pattern: Box<Pattern>,
+    },
+    Variant {
+        name: String,
+        tag: usize,
+        pattern: Option<Box<Pattern>>,
+    },
+    Tuple {
+        patterns: Vec<Pattern>,
+    },
+    Struct {
+        name: String,
+        fields: Vec<Pattern>,
     },
 }
 
+impl Statement {
+    pub fn get_position(&self) -> Option<FilePosition> {
+        match self {
+            Statement::Let { position, .. }
+            | Statement::Return { position, .. }
+            | Statement::Expression { position, .. }
+            | Statement::If { position, .. }
+            | Statement::Unwrap { position, .. }
+            | Statement::IfLet { position, .. }
+            | Statement::While { position, .. }
+            | Statement::For { position, .. }
+            | Statement::Foreach { position, .. }
+            | Statement::ForRange { position, .. }
+            | Statement::Match { position, .. } => Some(position.clone()),
+            _ => None,
+        }
+    }
+
+    // stretches the span of the statement to its last token
+    pub fn extend_position(&mut self, end: &FilePosition) {
+        match self {
+            Statement::Let { position, .. }
+            | Statement::Return { position, .. }
+            | Statement::Expression { position, .. }
+            | Statement::If { position, .. }
+            | Statement::Unwrap { position, .. }
+            | Statement::IfLet { position, .. }
+            | Statement::While { position, .. }
+            | Statement::For { position, .. }
+            | Statement::Foreach { position, .. }
+            | Statement::ForRange { position, .. }
+            | Statement::Match { position, .. } => *position = position.to(end),
+            _ => {}
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Atom {
     None,
@@ -172,16 +261,19 @@ pub enum Expr {
         args: Vec<Arg>,
         body: Vec<Statement>,
         captures: Vec<String>,
+        position: FilePosition,
     },
     ListConstructor {
         ttype: TType,
         elements: Vec<Expr>,
+        position: FilePosition,
     },
     ListCompConstructor {
         ttype: TType,
         loops: Vec<(String, Expr)>,
         expr: Vec<Expr>,
         guards: Vec<Expr>,
+        position: FilePosition,
     },
     Field {
         ttype: TType,
@@ -211,27 +303,32 @@ pub enum Expr {
         name: String,
         function: Box<Expr>,
         args: Vec<Expr>,
+        position: FilePosition,
     },
     Unary {
         ttype: TType,
         op: Unary,
         expr: Box<Expr>,
+        position: FilePosition,
     },
     Binop {
         ttype: TType,
         op: Operator,
         lhs: Box<Expr>,
         rhs: Box<Expr>,
+        position: FilePosition,
     },
     Literal {
         ttype: TType,
         value: Atom,
+        position: FilePosition,
     },
     StoreExpr {
         ttype: TType,
         name: String,
         expr: Box<Expr>,
         body: Vec<Statement>,
+        position: FilePosition,
     },
     None,
 }
@@ -253,4 +350,39 @@ impl Expr {
             Expr::StoreExpr { ttype, .. } => ttype.clone(),
         }
     }
+
+    // the span of source the expression was parsed from
+    pub fn get_position(&self) -> Option<FilePosition> {
+        match self {
+            Expr::Closure { position, .. }
+            | Expr::ListConstructor { position, .. }
+            | Expr::ListCompConstructor { position, .. }
+            | Expr::Field { position, .. }
+            | Expr::Indexed { position, .. }
+            | Expr::Sliced { position, .. }
+            | Expr::Call { position, .. }
+            | Expr::Unary { position, .. }
+            | Expr::Binop { position, .. }
+            | Expr::Literal { position, .. }
+            | Expr::StoreExpr { position, .. } => Some(position.clone()),
+            Expr::None => None,
+        }
+    }
+
+    pub fn set_position(&mut self, span: FilePosition) {
+        match self {
+            Expr::Closure { position, .. }
+            | Expr::ListConstructor { position, .. }
+            | Expr::ListCompConstructor { position, .. }
+            | Expr::Field { position, .. }
+            | Expr::Indexed { position, .. }
+            | Expr::Sliced { position, .. }
+            | Expr::Call { position, .. }
+            | Expr::Unary { position, .. }
+            | Expr::Binop { position, .. }
+            | Expr::Literal { position, .. }
+            | Expr::StoreExpr { position, .. } => *position = span,
+            Expr::None => {}
+        }
+    }
 }
@@ -0,0 +1,266 @@
+// NovaError is the error of every resolver and loader, the same as in the
+// rest of the tree, so it is returned unboxed
+#![allow(clippy::result_large_err)]
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{error::NovaError, fileposition::load_file_content, nodes::SymbolKind, ttype::TType};
+
+// decides where imported modules come from, hosts that embed nova can
+// serve them from memory or lock scripts into a single directory
+pub trait ModuleResolver: Debug + Send + Sync {
+    // the name of the module `path` refers to when imported from `importer`,
+    // paths are relative to the directory of the importing module
+    fn resolve(&self, importer: &str, path: &str) -> Result<String, NovaError> {
+        Ok(join(importer, path))
+    }
+
+    // the source code of a resolved module
+    fn load(&self, name: &str) -> Result<String, NovaError>;
+}
+
+// opens native plugins for `import native "name"`. the host keeps the
+// functions, the parser only gets their names and signatures
+pub trait PluginLoader: Debug + Send + Sync {
+    fn load(
+        &self,
+        importer: &str,
+        name: &str,
+    ) -> Result<Vec<(String, TType, SymbolKind)>, NovaError>;
+}
+
+// for hosts that do not load plugins
+#[derive(Debug, Clone, Default)]
+pub struct NoPlugins;
+
+impl PluginLoader for NoPlugins {
+    fn load(&self, _: &str, name: &str) -> Result<Vec<(String, TType, SymbolKind)>, NovaError> {
+        Err(NovaError::File {
+            msg: format!(" native module '{name}' cannot be loaded here"),
+        })
+    }
+}
+
+// imports straight from disk, this is what the command line uses
+#[derive(Debug, Clone, Default)]
+pub struct FileResolver;
+
+impl ModuleResolver for FileResolver {
+    fn load(&self, name: &str) -> Result<String, NovaError> {
+        load_file_content(name)
+    }
+}
+
+// modules kept in memory by name, sources embedded with include_str!
+// or include_bytes! can be added as well
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    pub modules: HashMap<String, String>,
+}
+
+pub fn memory() -> MemoryResolver {
+    MemoryResolver::default()
+}
+
+impl MemoryResolver {
+    pub fn insert(&mut self, name: &str, source: &str) {
+        self.modules.insert(normalize(name), source.to_string());
+    }
+
+    pub fn insert_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), NovaError> {
+        match std::str::from_utf8(bytes) {
+            Ok(source) => {
+                self.insert(name, source);
+                Ok(())
+            }
+            Err(_) => Err(NovaError::File {
+                msg: format!(" '{name}' is not valid utf-8"),
+            }),
+        }
+    }
+}
+
+impl ModuleResolver for MemoryResolver {
+    fn resolve(&self, importer: &str, path: &str) -> Result<String, NovaError> {
+        Ok(normalize(&join(importer, path)))
+    }
+
+    fn load(&self, name: &str) -> Result<String, NovaError> {
+        match self.modules.get(&normalize(name)) {
+            Some(source) => Ok(source.clone()),
+            None => Err(NovaError::File {
+                msg: format!(" '{name}' is not a known module"),
+            }),
+        }
+    }
+}
+
+// imports from disk, but only from inside root. module names are
+// relative to root and may not climb out of it
+#[derive(Debug, Clone)]
+pub struct DirectoryResolver {
+    pub root: PathBuf,
+}
+
+pub fn directory(root: &str) -> DirectoryResolver {
+    DirectoryResolver {
+        root: PathBuf::from(root),
+    }
+}
+
+impl DirectoryResolver {
+    // the file behind a module name with symlinks followed, None when it
+    // leads out of root. files that do not exist are left to load
+    fn inside(&self, name: &str) -> Option<PathBuf> {
+        let path = self.root.join(name);
+        let (Ok(root), Ok(path)) = (std::fs::canonicalize(&self.root), path.canonicalize()) else {
+            return Some(path);
+        };
+        path.starts_with(root).then_some(path)
+    }
+}
+
+impl ModuleResolver for DirectoryResolver {
+    fn resolve(&self, importer: &str, path: &str) -> Result<String, NovaError> {
+        let name = normalize(&join(importer, path));
+        if name.starts_with("..") || Path::new(&name).is_absolute() || self.inside(&name).is_none()
+        {
+            return Err(NovaError::File {
+                msg: format!(" '{path}' is outside of the module directory"),
+            });
+        }
+        Ok(name)
+    }
+
+    fn load(&self, name: &str) -> Result<String, NovaError> {
+        let name = self.resolve("", name)?;
+        let Some(path) = self.inside(&name) else {
+            return Err(NovaError::File {
+                msg: format!(" '{name}' is outside of the module directory"),
+            });
+        };
+        match std::fs::read_to_string(path) {
+            Ok(source) => Ok(source),
+            Err(_) => Err(NovaError::File {
+                msg: format!(" '{name}' is not a valid module"),
+            }),
+        }
+    }
+}
+
+// path next to the directory importer lives in
+fn join(importer: &str, path: &str) -> String {
+    match importer.rfind('/') {
+        Some(last_slash_index) => format!("{}{}", &importer[..last_slash_index + 1], path),
+        None => path.to_string(),
+    }
+}
+
+// collapses `.` and `..` so the same module always has the same name
+pub fn normalize(path: &str) -> String {
+    let mut parts: Vec<String> = vec![];
+    let mut absolute = false;
+    for component in Path::new(path).components() {
+        match component {
+            Component::RootDir => absolute = true,
+            Component::CurDir | Component::Prefix(_) => {}
+            Component::ParentDir => match parts.last() {
+                Some(last) if last != ".." => {
+                    parts.pop();
+                }
+                _ if absolute => {}
+                _ => parts.push("..".to_string()),
+            },
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+        }
+    }
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_normalized() {
+        assert_eq!(normalize("./a/b/../c.nv"), "a/c.nv");
+        assert_eq!(normalize("../a.nv"), "../a.nv");
+        assert_eq!(normalize("a/../../b.nv"), "../b.nv");
+        assert_eq!(normalize("/x/../../a.nv"), "/a.nv");
+        assert_eq!(join("lib/shapes.nv", "../util.nv"), "lib/../util.nv");
+        assert_eq!(join("main.nv", "util.nv"), "util.nv");
+    }
+
+    #[test]
+    fn memory_resolves_relative_to_the_importer() {
+        let mut modules = memory();
+        modules.insert("./lib/../util.nv", "module util");
+        let name = modules.resolve("lib/shapes.nv", "../util.nv").unwrap();
+        assert_eq!(name, "util.nv");
+        assert_eq!(modules.load(&name).unwrap(), "module util");
+        assert!(modules.load("lib/util.nv").is_err());
+        assert!(modules.insert_bytes("bad.nv", &[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn directory_rejects_escapes() {
+        let resolver = directory("/nowhere");
+        assert_eq!(resolver.resolve("a/b.nv", "c.nv").unwrap(), "a/c.nv");
+        assert_eq!(resolver.resolve("a/b.nv", "../c.nv").unwrap(), "c.nv");
+        for (importer, path) in [
+            ("main.nv", "../c.nv"),
+            ("a/b.nv", "../../c.nv"),
+            ("main.nv", "/etc/passwd"),
+        ] {
+            let error = resolver.resolve(importer, path).unwrap_err();
+            assert!(error
+                .message()
+                .contains("is outside of the module directory"));
+        }
+        // loading checks the name again
+        assert!(resolver
+            .load("../c.nv")
+            .unwrap_err()
+            .message()
+            .contains("is outside of the module directory"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_escape() {
+        let base = std::env::temp_dir().join(format!("nova-resolver-{}", std::process::id()));
+        std::fs::create_dir_all(base.join("root/lib")).unwrap();
+        std::fs::create_dir_all(base.join("secret")).unwrap();
+        std::fs::write(base.join("root/lib/util.nv"), "module util").unwrap();
+        std::fs::write(base.join("secret/key.nv"), "module key").unwrap();
+        for (target, link) in [
+            ("secret/key.nv", "root/key.nv"),
+            ("secret", "root/secret"),
+            ("root/lib/util.nv", "root/util.nv"),
+        ] {
+            let _ = std::fs::remove_file(base.join(link));
+            std::os::unix::fs::symlink(base.join(target), base.join(link)).unwrap();
+        }
+        let resolver = directory(&base.join("root").to_string_lossy());
+        for name in ["key.nv", "secret/key.nv"] {
+            assert!(resolver
+                .resolve("main.nv", name)
+                .unwrap_err()
+                .message()
+                .contains("is outside of the module directory"));
+            assert!(resolver.load(name).is_err());
+        }
+        // a link that stays inside root is fine
+        assert_eq!(resolver.load("util.nv").unwrap(), "module util");
+        assert_eq!(resolver.load("lib/util.nv").unwrap(), "module util");
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::ttype::TType;
+
+// the part of a static type the vm needs to show a value, structs, enums
+// and tuples look like lists at runtime so only the type tells them apart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Shape {
+    Value,
+    List(Box<Shape>),
+    Tuple(Vec<Shape>),
+    Option(Box<Shape>),
+    Named(String, Vec<Shape>),
+    Generic(String),
+}
+
+// the fields of a struct or the variants of an enum, in the order they are
+// stored. a variant without a value has no shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Layout {
+    Struct {
+        name: String,
+        generics: Vec<String>,
+        fields: Vec<(String, Shape)>,
+    },
+    Enum {
+        name: String,
+        generics: Vec<String>,
+        variants: Vec<(String, Option<Shape>)>,
+    },
+}
+
+impl Layout {
+    pub fn name(&self) -> &str {
+        match self {
+            Layout::Struct { name, .. } | Layout::Enum { name, .. } => name,
+        }
+    }
+}
+
+pub fn shape(ttype: &TType) -> Shape {
+    match ttype {
+        TType::Custom { name, type_params } => {
+            Shape::Named(name.clone(), type_params.iter().map(shape).collect())
+        }
+        TType::List { inner } => Shape::List(Box::new(shape(inner))),
+        TType::Tuple { elements } => Shape::Tuple(elements.iter().map(shape).collect()),
+        TType::Option { inner } => Shape::Option(Box::new(shape(inner))),
+        TType::Generic { name } => Shape::Generic(name.clone()),
+        _ => Shape::Value,
+    }
+}
+
+// values without a struct, enum or tuple in them print the way they always
+// did. a generic value may turn out to be a struct or enum at runtime
+pub fn needs_shape(ttype: &TType) -> bool {
+    match ttype {
+        TType::Custom { .. } | TType::Tuple { .. } | TType::Generic { .. } | TType::Any => true,
+        TType::List { inner } | TType::Option { inner } => needs_shape(inner),
+        _ => false,
+    }
+}
+
+// layouts and shapes travel through the bytecode as json
+pub fn encode<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+pub fn decode<T: DeserializeOwned>(text: &str) -> Option<T> {
+    serde_json::from_str(text).ok()
+}
+
+impl Shape {
+    // fills in the type arguments of a generic struct or enum
+    pub fn substitute(&self, generics: &[String], arguments: &[Shape]) -> Shape {
+        match self {
+            Shape::Generic(name) => generics
+                .iter()
+                .position(|generic| generic == name)
+                .and_then(|index| arguments.get(index).cloned())
+                .unwrap_or(Shape::Value),
+            Shape::List(inner) => Shape::List(Box::new(inner.substitute(generics, arguments))),
+            Shape::Option(inner) => Shape::Option(Box::new(inner.substitute(generics, arguments))),
+            Shape::Tuple(elements) => Shape::Tuple(
+                elements
+                    .iter()
+                    .map(|element| element.substitute(generics, arguments))
+                    .collect(),
+            ),
+            Shape::Named(name, parameters) => Shape::Named(
+                name.clone(),
+                parameters
+                    .iter()
+                    .map(|parameter| parameter.substitute(generics, arguments))
+                    .collect(),
+            ),
+            Shape::Value => Shape::Value,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use crate::{fileposition::FilePosition, ttype::TType};
+
+// an identifier as it is written in the source along with what it resolved to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub name: String,
+    pub ttype: TType,
+    pub position: FilePosition,
+    pub definition: Option<FilePosition>,
+}
+
+// every identifier the parser resolved, indexed by file and line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolTable {
+    pub files: HashMap<String, HashMap<usize, Vec<Reference>>>,
+}
+
+pub fn new() -> SymbolTable {
+    SymbolTable::default()
+}
+
+impl SymbolTable {
+    pub fn insert(&mut self, reference: Reference) {
+        let line = self
+            .files
+            .entry(reference.position.filepath.clone())
+            .or_default()
+            .entry(reference.position.line)
+            .or_default();
+        // a later resolution of the same identifier wins
+        line.retain(|other| other.position.row != reference.position.row);
+        line.push(reference);
+    }
+
+    pub fn extend(&mut self, other: SymbolTable) {
+        for (_, lines) in other.files {
+            for (_, references) in lines {
+                for reference in references {
+                    self.insert(reference);
+                }
+            }
+        }
+    }
+
+    fn line(&self, filepath: &str, line: usize) -> &[Reference] {
+        self.files
+            .get(filepath)
+            .and_then(|lines| lines.get(&line))
+            .map_or(&[], |references| references.as_slice())
+    }
+
+    // the reference under the column, columns start at 1 like file positions
+    pub fn at(&self, filepath: &str, line: usize, column: usize) -> Option<&Reference> {
+        self.line(filepath, line).iter().find(|reference| {
+            let start = reference.position.row;
+            start <= column && column < start + reference.name.chars().count()
+        })
+    }
+
+    // the reference that ends right before the column
+    pub fn ending_at(&self, filepath: &str, line: usize, column: usize) -> Option<&Reference> {
+        self.line(filepath, line)
+            .iter()
+            .find(|reference| reference.position.row + reference.name.chars().count() == column)
+    }
+}
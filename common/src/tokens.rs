@@ -42,6 +42,30 @@ pub enum Operator {
     InclusiveRange,
     ExclusiveRange,
 }
+
+impl Operator {
+    // how many characters the operator takes up in the source
+    pub fn width(&self) -> usize {
+        match self {
+            Operator::ExclusiveRange => 3,
+            Operator::AdditionAssignment
+            | Operator::SubtractionAssignment
+            | Operator::And
+            | Operator::Or
+            | Operator::GtrOrEqu
+            | Operator::LssOrEqu
+            | Operator::DoubleColon
+            | Operator::RightArrow
+            | Operator::LeftArrow
+            | Operator::Equality
+            | Operator::NotEqual
+            | Operator::RightTilde
+            | Operator::LeftTilde
+            | Operator::InclusiveRange => 2,
+            _ => 1,
+        }
+    }
+}
 #[derive(Debug, Clone, PartialEq)]
 pub enum KeyWord {
     In,
@@ -111,6 +135,22 @@ impl Token {
         }
     }
 
+    pub fn position_mut(&mut self) -> &mut FilePosition {
+        match self {
+            Token::Type { position, .. }
+            | Token::Identifier { position, .. }
+            | Token::Integer { position, .. }
+            | Token::Float { position, .. }
+            | Token::String { position, .. }
+            | Token::Char { position, .. }
+            | Token::Symbol { position, .. }
+            | Token::Bool { position, .. }
+            | Token::Operator { position, .. }
+            | Token::Keyword { position, .. }
+            | Token::EOF { position } => position,
+        }
+    }
+
     pub fn get_bool(self) -> Option<bool> {
         if let Token::Bool { value, .. } = self {
             Some(value)
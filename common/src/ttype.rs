@@ -44,6 +44,25 @@ impl TType {
         matches!(self, TType::Function { .. })
     }
 
+    // the namespace that functions extending this type are declared under
+    pub fn extends_prefix(&self) -> Option<String> {
+        match self {
+            TType::Custom { name, .. } => Some(name.to_string()),
+            TType::List { .. } => Some("List".to_string()),
+            TType::Option { .. } => Some("Option".to_string()),
+            TType::Function { parameters, .. } => {
+                Some(format!("Function{}", "(_)".repeat(parameters.len())))
+            }
+            TType::Tuple { elements } => Some(format!("Tuple{}", "(_)".repeat(elements.len()))),
+            TType::Bool => Some("Bool".to_string()),
+            TType::Int => Some("Int".to_string()),
+            TType::Float => Some("Float".to_string()),
+            TType::Char => Some("Char".to_string()),
+            TType::String => Some("String".to_string()),
+            _ => None,
+        }
+    }
+
     pub fn custom_to_string(&self) -> Option<String> {
         match self {
             TType::Custom { name, .. } => Some(name.to_string()),
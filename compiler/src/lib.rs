@@ -4,8 +4,9 @@ use common::code::Asm;
 use common::error::NovaError;
 use common::gen::Gen;
 use common::nodes::Statement::{Block, Expression, For, Function, If, Return, Struct, While};
-use common::nodes::{Ast, Atom, Expr};
-use common::ttype::TType;
+use common::nodes::{Ast, Atom, Expr, Pattern};
+use common::shape::{self, Layout};
+use common::ttype::{generate_unique_string, TType};
 
 #[derive(Debug, Clone)]
 pub struct Compiler {
@@ -42,6 +43,20 @@ pub fn new() -> Compiler {
     }
 }
 
+// the names of the type parameters of a struct or enum
+fn generic_names(ttype: &TType) -> Vec<String> {
+    match ttype {
+        TType::Custom { type_params, .. } => type_params
+            .iter()
+            .filter_map(|param| match param {
+                TType::Generic { name } => Some(name.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
 impl Compiler {
     pub fn clear(&mut self) {
         self.output.clear()
@@ -124,11 +139,15 @@ impl Compiler {
         //dbg!(&self.native_functions);
 
         for statements in input.program.iter() {
+            if let Some(position) = statements.get_position() {
+                self.asm.push(Asm::LINE(position));
+            }
             match statements {
                 common::nodes::Statement::Foreach {
                     identifier,
                     expr,
                     body,
+                    ..
                 } => {
                     let top = self.gen.generate();
                     let end = self.gen.generate();
@@ -229,6 +248,7 @@ impl Compiler {
                     identifier,
                     expr,
                     global,
+                    ..
                 } => {
                     self.compile_expr(expr.clone())?;
 
@@ -251,11 +271,11 @@ impl Compiler {
                     }
                 }
                 Function {
+                    ttype,
                     identifier,
                     parameters,
                     body,
                     captures: captured,
-                    ..
                 } => {
                     self.global.insert(identifier.to_string());
                     // Clone the current state to prepare for function compilation
@@ -319,6 +339,23 @@ impl Compiler {
                     let num_captures = captured.len() as u32;
                     let local_vars = function_compile.variables.len() as u32;
                     //dbg!(ttype, identifier, num_parameters, num_captures, local_vars);
+                    // traces and the debugger show the name without the parameter types,
+                    // the scope starts at the offset so a frame that was just entered is named
+                    let mut types = parameters
+                        .iter()
+                        .map(|param| param.ttype.clone())
+                        .collect::<Vec<TType>>();
+                    // functions without parameters are named as taking None
+                    if types.is_empty() {
+                        types.push(TType::None);
+                    }
+                    let name = identifier
+                        .strip_suffix(&generate_unique_string("", &types))
+                        .unwrap_or(identifier);
+                    self.asm.push(Asm::SCOPE(
+                        name.to_string(),
+                        function_compile.variables.items.clone(),
+                    ));
                     self.asm.push(Asm::OFFSET(
                         num_parameters + num_captures,
                         local_vars - (num_parameters + num_captures),
@@ -333,19 +370,39 @@ impl Compiler {
                     // storeing global function
                     let index = self.global.len() - 1;
                     self.asm.push(Asm::STOREGLOBAL(index as u32));
+
+                    // a show or toString extends function replaces how the
+                    // values of its type are printed
+                    if let (Some((owner, "show" | "toString")), [parameter], TType::String) =
+                        (name.split_once("::"), types.as_slice(), ttype)
+                    {
+                        if parameter.custom_to_string().as_deref() == Some(owner) {
+                            self.asm.push(Asm::GETGLOBAL(index as u32));
+                            self.asm.push(Asm::SHOWWITH(owner.to_string()));
+                        }
+                    }
                 }
 
                 Struct {
-                    ttype: _,
+                    ttype,
                     identifier,
                     fields,
                 } => {
+                    self.asm.push(Asm::LAYOUT(Layout::Struct {
+                        name: identifier.clone(),
+                        generics: generic_names(ttype),
+                        fields: fields
+                            .iter()
+                            .filter(|field| field.identifier != "type")
+                            .map(|field| (field.identifier.clone(), shape::shape(&field.ttype)))
+                            .collect(),
+                    }));
                     self.global.insert(identifier.to_string());
                     let structjump = self.gen.generate();
                     self.asm.push(Asm::FUNCTION(structjump));
                     self.asm
                         .push(Asm::OFFSET((fields.len() - 1) as u32, 0 as u32));
-                    self.asm.push(Asm::STRING(identifier.clone()));
+                    self.asm.push(Asm::TAG(identifier.clone()));
                     self.asm.push(Asm::LIST(fields.len() as u64));
                     self.asm.push(Asm::RET(true));
                     self.asm.push(Asm::LABEL(structjump));
@@ -353,7 +410,7 @@ impl Compiler {
                     self.asm.push(Asm::STOREGLOBAL(index as u32));
                 }
 
-                Return { ttype, expr } => {
+                Return { ttype, expr, .. } => {
                     self.compile_expr(expr.clone())?;
                     if ttype != &TType::Void {
                         self.asm.push(Asm::RET(true))
@@ -361,7 +418,7 @@ impl Compiler {
                         self.asm.push(Asm::RET(false))
                     }
                 }
-                Expression { ttype, expr } => {
+                Expression { ttype, expr, .. } => {
                     self.compile_expr(expr.clone())?;
                     if ttype != &TType::Void {
                         self.asm.push(Asm::POP);
@@ -372,6 +429,7 @@ impl Compiler {
                     test,
                     body,
                     alternative,
+                    ..
                 } => {
                     let (bodyjump, alterjump) = (self.gen.generate(), self.gen.generate());
                     self.compile_expr(test.clone())?;
@@ -396,7 +454,7 @@ impl Compiler {
                     }
                 }
 
-                While { test, body } => {
+                While { test, body, .. } => {
                     let top = self.gen.generate();
                     let end = self.gen.generate();
                     self.breaks.push(end);
@@ -419,6 +477,7 @@ impl Compiler {
                     test,
                     inc,
                     body,
+                    ..
                 } => {
                     let top = self.gen.generate();
                     let end = self.gen.generate();
@@ -467,6 +526,7 @@ impl Compiler {
                     identifier,
                     body,
                     alternative,
+                    ..
                 } => {
                     let skip = self.gen.generate();
                     let end = self.gen.generate();
@@ -510,6 +570,7 @@ impl Compiler {
                     body,
                     global,
                     alternative,
+                    ..
                 } => {
                     let skip = self.gen.generate();
                     let end = self.gen.generate();
@@ -559,8 +620,23 @@ impl Compiler {
                     self.asm.push(Asm::LABEL(end));
                 }
                 common::nodes::Statement::Enum {
-                    identifier, fields, ..
+                    ttype,
+                    identifier,
+                    fields,
                 } => {
+                    self.asm.push(Asm::LAYOUT(Layout::Enum {
+                        name: identifier.clone(),
+                        generics: generic_names(ttype),
+                        variants: fields
+                            .iter()
+                            .filter(|field| field.identifier != "type")
+                            .map(|field| {
+                                let shape = (field.ttype != TType::None)
+                                    .then(|| shape::shape(&field.ttype));
+                                (field.identifier.clone(), shape)
+                            })
+                            .collect(),
+                    }));
                     for (tag, field) in fields.iter().enumerate() {
                         if field.identifier == "type" {
                             continue;
@@ -583,7 +659,7 @@ impl Compiler {
                         }
 
                         self.asm.push(Asm::INTEGER(tag as i64));
-                        self.asm.push(Asm::STRING(identifier.clone()));
+                        self.asm.push(Asm::TAG(identifier.clone()));
 
                         self.asm.push(Asm::LIST(3 as u64));
                         self.asm.push(Asm::RET(true));
@@ -593,64 +669,31 @@ impl Compiler {
                         self.asm.push(Asm::STOREGLOBAL(index as u32));
                     }
                 }
-                common::nodes::Statement::Match {
-                    expr,
-                    arms,
-                    default,
-                    ..
-                } => {
-                    // we will do it old school, each arm will make a new if branch
-                    // and if it fails it will jump to the next arm
-                    // if there is a default it will jump to the default
-                    // if there is no default it will jump to the end
+                common::nodes::Statement::Match { expr, arms, .. } => {
+                    // each arm tests the value stored in a temp variable and
+                    // jumps to the next arm when its pattern or guard fails
                     let end = self.gen.generate();
                     self.compile_expr(expr.clone())?;
-                    // will test the expression
-                    self.asm.push(Asm::INTEGER(1 as i64));
-                    self.compile_expr(expr.clone())?;
-                    self.asm.push(Asm::LIN);
-                    // store in temp variable
                     self.variables
                         .insert(format!("__matchexpr__{}", self.gen.generate()).to_string());
-                    let temp_matchexpr = self.variables.len() - 1;
-                    self.asm.push(Asm::STORE(temp_matchexpr as u32));
+                    let temp_matchexpr = self.variables.len() as u32 - 1;
+                    self.asm.push(Asm::STORE(temp_matchexpr));
                     for arm in arms.iter() {
                         let next = self.gen.generate();
-                        self.asm.push(Asm::GET(temp_matchexpr as u32));
-                        // self.asm.push(Asm::DUP);
-                        // self.asm.push(Asm::PRINT);
-                        //dbg!(arm.0);
-                        self.asm.push(Asm::INTEGER(arm.0 as i64));
-                        self.asm.push(Asm::EQUALS);
-                        self.asm.push(Asm::JUMPIFFALSE(next));
-                        if let Some(vid) = &arm.1 {
-                            self.asm.push(Asm::INTEGER(0 as i64));
-                            self.compile_expr(expr.clone())?;
-                            self.asm.push(Asm::LIN);
-                            // store the vid in the variable
-                            if let Some(index) = self.variables.get_index(vid.to_string()) {
-                                self.asm.push(Asm::STORE(index as u32))
-                            } else {
-                                self.variables.insert(vid.to_string());
-                                let index = self.variables.len() - 1;
-                                self.asm.push(Asm::STORE(index as u32))
-                            }
+                        self.match_pattern(&arm.pattern, temp_matchexpr, &mut vec![], next)?;
+                        self.bind_pattern(&arm.pattern, temp_matchexpr, &mut vec![]);
+                        if let Some(guard) = &arm.guard {
+                            self.compile_expr(guard.clone())?;
+                            self.asm.push(Asm::JUMPIFFALSE(next));
                         }
                         let arm = Ast {
-                            program: arm.2.clone(),
+                            program: arm.body.clone(),
                         };
                         self.compile_program(arm, self.filepath.clone(), false, false, false)?;
                         self.asm.pop();
                         self.asm.push(Asm::JMP(end));
                         self.asm.push(Asm::LABEL(next));
                     }
-                    if let Some(default) = default {
-                        let default = Ast {
-                            program: default.clone(),
-                        };
-                        self.compile_program(default, self.filepath.clone(), false, false, false)?;
-                        self.asm.pop();
-                    }
                     self.asm.push(Asm::LABEL(end));
                 }
                 common::nodes::Statement::ForRange {
@@ -660,6 +703,7 @@ impl Compiler {
                     inclusive,
                     step,
                     body,
+                    ..
                 } => {
                     let top = self.gen.generate();
                     let end = self.gen.generate();
@@ -702,7 +746,7 @@ impl Compiler {
                         self.asm.push(Asm::EQUALS);
                         self.asm.push(Asm::LABEL(sc))
                     }
-                    
+
                     self.asm.push(Asm::JUMPIFFALSE(end));
 
                     let whilebody = Ast {
@@ -727,7 +771,7 @@ impl Compiler {
                     }
                     self.asm.push(Asm::BJMP(top));
                     self.asm.push(Asm::LABEL(end));
-                    
+
                     self.breaks.pop();
                     self.continues.pop();
                 }
@@ -737,6 +781,10 @@ impl Compiler {
         match (function, alloc) {
             (true, _) => {}
             (false, true) => {
+                self.asm.insert(
+                    0,
+                    Asm::SCOPE("main".to_string(), self.variables.items.clone()),
+                );
                 self.asm
                     .insert(0, Asm::ALLOCLOCALS(self.variables.len() as u32));
             }
@@ -942,7 +990,39 @@ impl Compiler {
                 op,
                 lhs,
                 rhs,
+                ..
             } => {
+                // strings, lists and tuples order by the result of cmp
+                if let common::tokens::Operator::GreaterThan
+                | common::tokens::Operator::LessThan
+                | common::tokens::Operator::GtrOrEqu
+                | common::tokens::Operator::LssOrEqu = op
+                {
+                    if !matches!(lhs.get_type(), TType::Int | TType::Float) {
+                        self.compile_expr(*lhs)?;
+                        self.compile_expr(*rhs)?;
+                        self.asm.push(Asm::CMP);
+                        match op {
+                            common::tokens::Operator::GreaterThan => {
+                                self.asm.push(Asm::INTEGER(0));
+                                self.asm.push(Asm::IGTR);
+                            }
+                            common::tokens::Operator::LessThan => {
+                                self.asm.push(Asm::INTEGER(0));
+                                self.asm.push(Asm::ILSS);
+                            }
+                            common::tokens::Operator::GtrOrEqu => {
+                                self.asm.push(Asm::INTEGER(-1));
+                                self.asm.push(Asm::IGTR);
+                            }
+                            _ => {
+                                self.asm.push(Asm::INTEGER(1));
+                                self.asm.push(Asm::ILSS);
+                            }
+                        }
+                        return Ok(());
+                    }
+                }
                 match op {
                     common::tokens::Operator::RightArrow => todo!(),
                     common::tokens::Operator::GreaterThan => {
@@ -1137,8 +1217,11 @@ impl Compiler {
                                 self.compile_expr(*rhs.clone())?;
                                 self.asm.push(Asm::CONCAT);
                             }
-                            _ => {
-                                dbg!(&lhs.get_type());
+                            ttype => {
+                                return Err(NovaError::Compiler {
+                                    msg: format!("Cannot apply += to {}", ttype.to_string()),
+                                    note: "+= needs an Int, Float, String or List".to_string(),
+                                })
                             }
                         }
                         self.getref_expr(*lhs.clone())?;
@@ -1152,7 +1235,10 @@ impl Compiler {
                         } else if lhs.get_type() == TType::Float {
                             self.asm.push(Asm::FSUB);
                         } else {
-                            dbg!(&ttype);
+                            return Err(NovaError::Compiler {
+                                msg: format!("Cannot apply -= to {}", lhs.get_type().to_string()),
+                                note: "-= needs an Int or Float".to_string(),
+                            });
                         }
                         self.getref_expr(*lhs.clone())?;
 
@@ -1175,16 +1261,13 @@ impl Compiler {
                 }
                 Ok(())
             }
-            Expr::Literal {
-                ttype: _,
-                value: atom,
-            } => self.compile_atom(atom),
+            Expr::Literal { value: atom, .. } => self.compile_atom(atom),
 
             Expr::Closure {
-                ttype: _,
                 args: parameters,
                 body: input,
                 captures: captured,
+                ..
             } => {
                 //dbg!(&captured, &self.variables);
                 // Clone the current state to prepare for function compilation
@@ -1251,6 +1334,10 @@ impl Compiler {
                 let num_parameters = parameters.len() as u32;
                 let num_captures = captured.len() as u32;
                 let local_vars = function_compile.variables.len() as u32;
+                self.asm.push(Asm::SCOPE(
+                    "closure".to_string(),
+                    function_compile.variables.items.clone(),
+                ));
                 self.asm.push(Asm::OFFSET(
                     num_parameters + num_captures,
                     local_vars - (num_parameters + num_captures),
@@ -1455,6 +1542,7 @@ impl Compiler {
                 name,
                 expr,
                 body,
+                ..
             } => {
                 self.compile_expr(*expr)?;
                 if let Some(index) = self.variables.get_index(name.to_string()) {
@@ -1481,6 +1569,130 @@ impl Compiler {
         }
     }
 
+    // pushes the part of the matched value a pattern looks at, the path
+    // indexes into the lists that structs, enums and tuples are stored as
+    fn push_matched(&mut self, temp: u32, path: &[usize]) {
+        for index in path.iter().rev() {
+            self.asm.push(Asm::INTEGER(*index as i64));
+        }
+        self.asm.push(Asm::GET(temp));
+        for _ in path {
+            self.asm.push(Asm::LIN);
+        }
+    }
+
+    // jumps to next when the matched value does not fit the pattern,
+    // an enum is stored as [value, tag, type]
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        temp: u32,
+        path: &mut Vec<usize>,
+        next: u64,
+    ) -> Result<(), NovaError> {
+        match pattern {
+            Pattern::Wildcard | Pattern::Binding { .. } => {}
+            Pattern::Literal { value } => {
+                self.push_matched(temp, path);
+                self.compile_atom(value.clone())?;
+                self.asm.push(Asm::EQUALS);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+            }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                self.push_matched(temp, path);
+                self.compile_atom(start.clone())?;
+                self.asm.push(Asm::CMP);
+                self.asm.push(Asm::INTEGER(-1));
+                self.asm.push(Asm::IGTR);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+                self.push_matched(temp, path);
+                self.compile_atom(end.clone())?;
+                self.asm.push(Asm::CMP);
+                self.asm.push(Asm::INTEGER(if *inclusive { 1 } else { 0 }));
+                self.asm.push(Asm::ILSS);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+            }
+            Pattern::None => {
+                self.push_matched(temp, path);
+                self.asm.push(Asm::NONE);
+                self.asm.push(Asm::EQUALS);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+            }
+            Pattern::Some { pattern } => {
+                self.push_matched(temp, path);
+                self.asm.push(Asm::NONE);
+                self.asm.push(Asm::EQUALS);
+                self.asm.push(Asm::NOT);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+                self.match_pattern(pattern, temp, path, next)?;
+            }
+            Pattern::Variant { tag, pattern, .. } => {
+                path.push(1);
+                self.push_matched(temp, path);
+                path.pop();
+                self.asm.push(Asm::INTEGER(*tag as i64));
+                self.asm.push(Asm::EQUALS);
+                self.asm.push(Asm::JUMPIFFALSE(next));
+                if let Some(pattern) = pattern {
+                    path.push(0);
+                    self.match_pattern(pattern, temp, path, next)?;
+                    path.pop();
+                }
+            }
+            Pattern::Tuple { patterns }
+            | Pattern::Struct {
+                fields: patterns, ..
+            } => {
+                for (index, pattern) in patterns.iter().enumerate() {
+                    path.push(index);
+                    self.match_pattern(pattern, temp, path, next)?;
+                    path.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // stores the parts of the matched value the pattern names in their variables
+    fn bind_pattern(&mut self, pattern: &Pattern, temp: u32, path: &mut Vec<usize>) {
+        match pattern {
+            Pattern::Binding { name } => {
+                self.push_matched(temp, path);
+                if let Some(index) = self.variables.get_index(name.to_string()) {
+                    self.asm.push(Asm::STORE(index as u32))
+                } else {
+                    self.variables.insert(name.to_string());
+                    let index = self.variables.len() - 1;
+                    self.asm.push(Asm::STORE(index as u32))
+                }
+            }
+            Pattern::Some { pattern } => self.bind_pattern(pattern, temp, path),
+            Pattern::Variant {
+                pattern: Some(pattern),
+                ..
+            } => {
+                path.push(0);
+                self.bind_pattern(pattern, temp, path);
+                path.pop();
+            }
+            Pattern::Tuple { patterns }
+            | Pattern::Struct {
+                fields: patterns, ..
+            } => {
+                for (index, pattern) in patterns.iter().enumerate() {
+                    path.push(index);
+                    self.bind_pattern(pattern, temp, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn for_in_loop(
         &mut self,
         identifier: String,
@@ -1632,6 +1844,18 @@ impl Compiler {
                 for expr in list.iter() {
                     self.compile_expr(expr.clone())?;
                 }
+                // structs, enums and tuples look like lists at runtime, their
+                // type tells the vm how to show them
+                if let ("print" | "println" | "eprint" | "eprintln" | "Cast::string", [value]) =
+                    (caller.as_str(), list.as_slice())
+                {
+                    if shape::needs_shape(&value.get_type()) {
+                        self.asm.push(Asm::SHOW(shape::shape(&value.get_type())));
+                        if caller == "Cast::string" {
+                            return Ok(());
+                        }
+                    }
+                }
                 match caller.as_str() {
                     // "println" => {
                     //     self.asm.push(Asm::PRINT);
@@ -57,6 +57,7 @@ impl Disassembler {
                 Asm::FLSS => println!("    flss"),
                 Asm::FGTR => println!("    fgtr"),
                 Asm::EQUALS => println!("    equ"),
+                Asm::CMP => println!("    cmp"),
                 Asm::FREE => println!("    free"),
                 Asm::CLONE => println!("    clone"),
                 Asm::IMODULO => println!("    imod"),
@@ -76,6 +77,12 @@ impl Disassembler {
                 Asm::CONCAT => println!("    concat"),
                 Asm::Char(v) => println!("    char: {v}"),
                 Asm::ERROR(_) => println!("    error"),
+                Asm::LAYOUT(layout) => println!("    layout: {}", layout.name()),
+                Asm::SHOWWITH(name) => println!("    showwith: {name}"),
+                Asm::SHOW(shape) => println!("    show: {shape:?}"),
+                Asm::TAG(name) => println!("    tag: {name}"),
+                Asm::LINE(pos) => println!("  ; {}:{}", pos.filepath, pos.line),
+                Asm::SCOPE(name, _) => println!("  ; scope {name}"),
             }
         }
         println!();
@@ -88,6 +95,28 @@ impl Disassembler {
         println!("{}", output)
     }
 
+    // a length prefixed operand like the one of STRING
+    fn text(&mut self, input: &mut std::vec::IntoIter<u8>) -> String {
+        let mut string = vec![];
+        let size = usize::from_le_bytes([
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+            self.next(input).unwrap(),
+        ]);
+        for _ in 0..size {
+            string.push(self.next(input).unwrap());
+        }
+        match String::from_utf8(string) {
+            Ok(ok) => ok,
+            Err(_) => panic!(),
+        }
+    }
+
     fn next(&mut self, input: &mut std::vec::IntoIter<u8>) -> Option<u8> {
         if let Some(index) = self.depth.last() {
             if self.ip == *index {
@@ -251,6 +280,15 @@ impl Disassembler {
                     ]);
                     self.out(&format!("Direct call {}", target))
                 }
+                Code::TAILCALL => {
+                    let target = u32::from_le_bytes([
+                        self.next(&mut input).unwrap(),
+                        self.next(&mut input).unwrap(),
+                        self.next(&mut input).unwrap(),
+                        self.next(&mut input).unwrap(),
+                    ]);
+                    self.out(&format!("Tail call {}", target))
+                }
                 Code::NEWLIST => {
                     let size = u64::from_le_bytes([
                         self.next(&mut input).unwrap(),
@@ -304,6 +342,7 @@ impl Disassembler {
                 Code::WHEN => self.out("When"),
                 Code::IF => self.out("If"),
                 Code::EQUALS => self.out("Equals"),
+                Code::CMP => self.out("Compare"),
                 Code::IMODULO => self.out("Modulo"),
                 Code::REFID => {
                     let index = u16::from_le_bytes([
@@ -344,26 +383,25 @@ impl Disassembler {
                 Code::PRINT => self.out(&format!("Print")),
 
                 Code::STRING => {
-                    let mut string = vec![];
-                    let size = usize::from_le_bytes([
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                        self.next(&mut input).unwrap(),
-                    ]);
-                    for _ in 0..size {
-                        string.push(self.next(&mut input).unwrap());
-                    }
-                    let string = match String::from_utf8(string) {
-                        Ok(ok) => ok,
-                        Err(_) => panic!(),
-                    };
+                    let string = self.text(&mut input);
                     self.out(&format!("Push String: {}", string))
                 }
+                Code::LAYOUT => {
+                    let layout = self.text(&mut input);
+                    self.out(&format!("Layout: {}", layout))
+                }
+                Code::SHOWWITH => {
+                    let name = self.text(&mut input);
+                    self.out(&format!("Show with: {}", name))
+                }
+                Code::SHOW => {
+                    let shape = self.text(&mut input);
+                    self.out(&format!("Show: {}", shape))
+                }
+                Code::TAG => {
+                    let name = self.text(&mut input);
+                    self.out(&format!("Tag: {}", name))
+                }
 
                 Code::FOR => self.out("For"),
 
@@ -49,17 +49,22 @@ impl Lexer {
             Ok(value) => value,
             Err(value) => return Err(value),
         };
-        Ok(Lexer {
+        Ok(Lexer::from_source(filepath, &source))
+    }
+
+    // lex source that does not live on disk, filepath is only used for positions
+    pub fn from_source(filepath: &str, source: &str) -> Lexer {
+        Lexer {
             line: 1,
             row: 1,
             filepath: filepath.to_string(),
-            source_file: source,
+            source_file: source.to_string(),
             token_list: Default::default(),
             buffer: Default::default(),
             state: LexerState::Token,
             string_start_position: vec![],
             char_start_position: vec![],
-        })
+        }
     }
 
     fn current_position(&self) -> FilePosition {
@@ -67,6 +72,8 @@ impl Lexer {
             line: self.line,
             row: self.row,
             filepath: self.filepath.to_string(),
+            end_line: self.line,
+            end_row: self.row,
         };
     }
 
@@ -75,6 +82,8 @@ impl Lexer {
             line: self.line,
             row: row,
             filepath: self.filepath.clone(),
+            end_line: self.line,
+            end_row: row,
         };
     }
 
@@ -669,13 +678,72 @@ impl Lexer {
         self.token_list.push(Token::EOF {
             position: self.current_position(),
         });
+        self.measure_spans();
 
         Ok(self.token_list.clone())
     }
 
+    // tokens are created knowing only where they start, the end of each
+    // span is found by measuring the token text in the source
+    fn measure_spans(&mut self) {
+        let lines = self
+            .source_file
+            .split('\n')
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        for token in self.token_list.iter_mut() {
+            let (line, row) = (token.line(), token.row());
+            let (end_line, end_row) = match token {
+                Token::String { .. } => quoted_end(&lines, line, row, '"'),
+                Token::Char { .. } => quoted_end(&lines, line, row, '\''),
+                Token::Symbol { .. } => (line, row + 1),
+                Token::Operator { operator, .. } => (line, row + operator.width()),
+                Token::EOF { .. } => (line, row),
+                Token::Float { .. } => (line, row + word_width(&lines, line, row, true)),
+                _ => (line, row + word_width(&lines, line, row, false)),
+            };
+            let position = token.position_mut();
+            position.end_line = end_line;
+            position.end_row = end_row;
+        }
+    }
+
     pub fn check(&self) {
         for token in self.token_list.iter() {
             common::error::print_line(&token.position(), &format!("{}", token.to_string()));
         }
     }
 }
+
+// the length of the identifier or number starting at the position
+fn word_width(lines: &[Vec<char>], line: usize, row: usize, float: bool) -> usize {
+    let Some(chars) = line.checked_sub(1).and_then(|line| lines.get(line)) else {
+        return 1;
+    };
+    chars
+        .iter()
+        .skip(row.saturating_sub(1))
+        .take_while(|c| c.is_alphanumeric() || **c == '_' || (float && **c == '.'))
+        .count()
+        .max(1)
+}
+
+// the end of a string or char literal, which may run over several lines
+fn quoted_end(lines: &[Vec<char>], line: usize, row: usize, quote: char) -> (usize, usize) {
+    let (mut line_index, mut index) = (line.saturating_sub(1), row.saturating_sub(1) + 1);
+    while let Some(chars) = lines.get(line_index) {
+        while let Some(c) = chars.get(index) {
+            if *c == '\\' {
+                index += 2;
+                continue;
+            }
+            if *c == quote {
+                return (line_index + 1, index + 2);
+            }
+            index += 1;
+        }
+        line_index += 1;
+        index = 0;
+    }
+    (line, row + 1)
+}
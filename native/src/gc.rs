@@ -0,0 +1,31 @@
+use vm::state::State;
+
+vm::native! {
+    pub fn collect(state: &mut State) {
+        state.force_collect_garbage();
+    }
+}
+
+vm::native! {
+    pub fn count(state: &mut State) -> i64 {
+        state.gc_count as i64
+    }
+}
+
+vm::native! {
+    pub fn freed(state: &mut State) -> i64 {
+        state.garbage_collected as i64
+    }
+}
+
+vm::native! {
+    pub fn heap_size(state: &mut State) -> i64 {
+        state.live_heap() as i64
+    }
+}
+
+vm::native! {
+    pub fn threshold(state: &mut State) -> i64 {
+        state.threshold as i64
+    }
+}
@@ -1,42 +1,46 @@
 //use common::error::{runtime_error, NovaError};
 use common::error::NovaError;
-use std::{fs, io};
-use vm::state::{self, Heap, VmData};
+use std::fs;
+use vm::{bind::Any, state::State};
 
-pub fn read_line(state: &mut state::State) -> Result<(), NovaError> {
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .map_err(|e| NovaError::Runtime {
-            msg: format!("Error reading line: {}", e),
-        })?;
-    // removing newline token
-    input.pop();
-    let index = state.allocate_string(input);
-    state.stack.push(VmData::String(index));
-    Ok(())
+vm::native! {
+    pub fn read_line(state: &mut State) -> Result<String, NovaError> {
+        state.read_line()
+    }
 }
 
-pub fn read_file(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::String(index)) = state.stack.pop() {
-        if let Heap::String(path) = state.deref(index) {
-            match fs::read_to_string(path) {
-                Ok(string) => {
-                    let index = state.allocate_string(string);
-                    state.stack.push(VmData::String(index));
-                }
-                Err(e) => {
-                    return Err(NovaError::Runtime {
-                        msg: format!("Error reading file: {}", e),
-                    })
-                }
-            }
-        }
+vm::native! {
+    // print and println for the error stream of the program
+    pub fn eprint(state: &mut State, item: Any) -> Result<(), NovaError> {
+        state.write_error(&state.format_vmdata(item.0))
+    }
+}
+
+vm::native! {
+    pub fn eprintln(state: &mut State, item: Any) -> Result<(), NovaError> {
+        state.write_error(&state.format_vmdata(item.0))?;
+        state.write_error("\n")
     }
-    Ok(())
 }
 
-fn printf_with_array(format_string: &str, args: Vec<String>) {
+vm::native! {
+    pub fn read_file(path: String) -> Result<String, NovaError> {
+        fs::read_to_string(path).map_err(|e| NovaError::Runtime {
+            msg: format!("Error reading file: {}", e),
+        })
+    }
+}
+
+vm::native! {
+    // replaces the file, or creates it
+    pub fn write_file(path: String, contents: String) -> Result<(), NovaError> {
+        fs::write(path, contents).map_err(|e| NovaError::Runtime {
+            msg: format!("Error writing file: {}", e),
+        })
+    }
+}
+
+fn printf_with_array(format_string: &str, args: Vec<String>) -> String {
     let mut arg_iter = args.iter();
     let mut formatted = String::new();
     let mut segments = format_string.split("{}").peekable();
@@ -52,48 +56,12 @@ fn printf_with_array(format_string: &str, args: Vec<String>) {
         }
     }
 
-    print!("{}", formatted);
+    formatted
 }
 
-// printf function for the VM that takes an array of strings // and the format string as arguments
-pub fn printf(state: &mut state::State) -> Result<(), NovaError> {
-    let mut strings = vec![];
-    if let (Some(VmData::List(args)), Some(VmData::String(str_index))) =
-        (state.stack.pop(), state.stack.pop())
-    {
-        if let Heap::String(format_string) = state.deref(str_index) {
-            if let Heap::List(args) = state.deref(args) {
-                // gather string arguments
-                for arg in args.iter() {
-                    if let Heap::StringAddress(string) = state.deref(*arg) {
-                        if let Heap::String(string) = state.deref(string) {
-                            strings.push(string.clone());
-                        } else {
-                            return Err(NovaError::Runtime {
-                                msg: "Invalid arguments for printf".to_string(),
-                            });
-                        }
-                    } else {
-                        return Err(NovaError::Runtime {
-                            msg: "Invalid arguments for printf".to_string(),
-                        });
-                    }
-                }
-            } else {
-                return Err(NovaError::Runtime {
-                    msg: "Invalid arguments for printf".to_string(),
-                });
-            }
-            printf_with_array(&format_string, strings);
-        } else {
-            return Err(NovaError::Runtime {
-                msg: "Invalid arguments for printf".to_string(),
-            });
-        }
-    } else {
-        return Err(NovaError::Runtime {
-            msg: "Invalid arguments for printf".to_string(),
-        });
+vm::native! {
+    // fills every {} of the format string with the next argument
+    pub fn printf(state: &mut State, format: String, args: Vec<String>) -> Result<(), NovaError> {
+        state.write(&printf_with_array(&format, args))
     }
-    Ok(())
 }
@@ -6,3 +6,4 @@ pub mod str;
 pub mod terminal;
 pub mod time;
 pub mod float;
+pub mod gc;
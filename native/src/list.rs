@@ -1,43 +1,140 @@
 use common::error::NovaError;
-use vm::state::{self, Heap, VmData};
+use vm::{
+    bind::List,
+    state::{Heap, State, VmData},
+    Vm,
+};
 
-pub fn len(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::List(index)) = state.stack.pop() {
-        if let Heap::List(array) = state.deref(index) {
-            state.stack.push(VmData::Int(array.len() as i64))
+vm::native! {
+    pub fn len(state: &mut State, list: List) -> i64 {
+        match &state.heap[list.0] {
+            Heap::List(array) => array.len() as i64,
+            _ => 0,
         }
     }
-    Ok(())
 }
 
-pub fn push(state: &mut state::State) -> Result<(), NovaError> {
-    if let (Some(data), Some(VmData::List(index))) = (state.stack.pop(), state.stack.pop()) {
-        if let Heap::List(mut array) = state.deref(index) {
-            array.push(state.allocate_vmdata_to_heap(data));
-            state.heap[index] = Heap::List(array);
-        } else {
-            panic!()
+vm::native! {
+    pub fn push(state: &mut State, list: List, item: VmData) {
+        let item = state.allocate_vmdata_to_heap(item);
+        if let Heap::List(array) = &mut state.heap[list.0] {
+            array.push(item);
         }
-    } else {
-        panic!()
     }
+}
+
+vm::native! {
+    pub fn pop(state: &mut State, list: List) -> Option<VmData> {
+        let item = match &mut state.heap[list.0] {
+            Heap::List(array) => array.pop(),
+            _ => None,
+        };
+        item.map(|item| state.to_vmdata(item))
+    }
+}
+
+// the list and the function stay on the stack while nova code runs,
+// so the collector still sees them
+fn arguments(vm: &Vm) -> Result<(usize, VmData), NovaError> {
+    let length = vm.state.stack.len();
+    match vm.state.stack.get(length.wrapping_sub(2)..) {
+        Some([VmData::List(index), function]) => Ok((*index, *function)),
+        _ => Err(NovaError::Runtime {
+            msg: "Expected a list and a function on the stack".to_string(),
+        }),
+    }
+}
+
+// the cell at position in the list, the callback may have changed the list
+// so it is read again every time
+fn cell(vm: &Vm, list: usize, position: usize) -> Result<Option<usize>, NovaError> {
+    match &vm.state.heap[list] {
+        Heap::List(array) => Ok(array.get(position).copied()),
+        _ => Err(NovaError::Runtime {
+            msg: "Expected a list on the stack".to_string(),
+        }),
+    }
+}
+
+pub fn map(vm: &mut Vm) -> Result<(), NovaError> {
+    let (list, function) = arguments(vm)?;
+    let result = vm.state.allocate_array(vec![]);
+    vm.state.stack.push(VmData::List(result));
+
+    let mut position = 0;
+    while let Some(cell) = cell(vm, list, position)? {
+        let item = vm.state.to_vmdata(cell);
+        let mapped = vm.call(function, &[item])?.unwrap_or(VmData::None);
+        let mapped = vm.state.allocate_vmdata_to_heap(mapped);
+        if let Heap::List(array) = &mut vm.state.heap[result] {
+            array.push(mapped);
+        }
+        position += 1;
+    }
+
+    vm.state.stack.truncate(vm.state.stack.len() - 3);
+    vm.state.stack.push(VmData::List(result));
+    Ok(())
+}
+
+// returns a sorted copy of the list, f(a, b) tells if a goes before b.
+// the sort is stable and a comparator that is not consistent cannot break it
+pub fn sort_by(vm: &mut Vm) -> Result<(), NovaError> {
+    let (list, function) = arguments(vm)?;
+    let mut cells = vec![];
+    let mut position = 0;
+    while let Some(cell) = cell(vm, list, position)? {
+        let item = vm.state.to_vmdata(cell);
+        cells.push(vm.state.allocate_vmdata_to_heap(item));
+        position += 1;
+    }
+    // the copy holds every cell while the comparator runs
+    let result = vm.state.allocate_array(cells.clone());
+    vm.state.stack.push(VmData::List(result));
+
+    let sorted = merge_sort(vm, function, cells)?;
+    vm.state.heap[result] = Heap::List(sorted);
+
+    vm.state.stack.truncate(vm.state.stack.len() - 3);
+    vm.state.stack.push(VmData::List(result));
     Ok(())
 }
 
-pub fn pop(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::List(index)) = state.stack.pop() {
-        if let Heap::List(mut array) = state.deref(index) {
-            if let Some(item) = array.pop() {
-                state.stack.push(state.to_vmdata(item));
-            } else {
-                state.stack.push(VmData::None);
-            }
-            state.heap[index] = Heap::List(array);
+fn merge_sort(
+    vm: &mut Vm,
+    function: VmData,
+    mut left: Vec<usize>,
+) -> Result<Vec<usize>, NovaError> {
+    if left.len() < 2 {
+        return Ok(left);
+    }
+    let right = left.split_off(left.len() / 2);
+    let left = merge_sort(vm, function, left)?;
+    let right = merge_sort(vm, function, right)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut l, mut r) = (0, 0);
+    while l < left.len() && r < right.len() {
+        // only take from the right when it strictly goes first, that keeps equal items in order
+        if before(vm, function, right[r], left[l])? {
+            merged.push(right[r]);
+            r += 1;
         } else {
-            panic!()
+            merged.push(left[l]);
+            l += 1;
         }
-    } else {
-        panic!()
     }
-    Ok(())
+    merged.extend_from_slice(&left[l..]);
+    merged.extend_from_slice(&right[r..]);
+    Ok(merged)
+}
+
+fn before(vm: &mut Vm, function: VmData, a: usize, b: usize) -> Result<bool, NovaError> {
+    let (a, b) = (vm.state.to_vmdata(a), vm.state.to_vmdata(b));
+    match vm.call(function, &[a, b])? {
+        Some(VmData::Bool(v)) => Ok(v),
+        _ => Err(NovaError::Runtime {
+            msg: "sortBy expects its function to return a Bool".to_string(),
+        }),
+    }
 }
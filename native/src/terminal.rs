@@ -1,91 +1,91 @@
-use std::{io::stdout, time::Duration};
+use std::time::Duration;
 
 use common::error::NovaError;
 use crossterm::{
     cursor::MoveTo,
     event::{self, Event, KeyCode, KeyEvent},
-    execute, terminal,
+    terminal, Command,
 };
-use vm::state::{self, VmData};
+use vm::state::State;
 
-pub fn rawmode(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::Bool(bool)) = state.stack.pop() {
-        if bool {
+vm::native! {
+    pub fn rawmode(enable: bool) {
+        if enable {
             terminal::enable_raw_mode().expect("could not enable raw mode");
         } else {
             terminal::disable_raw_mode().expect("Could not disable raw mode")
         }
     }
-    Ok(())
 }
 
-pub fn getch(state: &mut state::State) -> Result<(), NovaError> {
-    if let Event::Key(KeyEvent {
-        code: KeyCode::Char(character),
-        modifiers: event::KeyModifiers::NONE,
-        kind: _,
-        state: _,
-    }) = event::read().expect("Failed to read line")
-    {
-        state.stack.push(VmData::Char(character))
-    } else {
-        state.stack.push(VmData::None);
+// a key that was typed, None for anything that is not a plain character
+fn key(event: Event) -> Option<char> {
+    match event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers: event::KeyModifiers::NONE,
+            kind: _,
+            state: _,
+        }) => Some(character),
+        _ => None,
     }
-    Ok(())
 }
 
-pub fn rawread(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::Int(time)) = state.stack.pop() {
+vm::native! {
+    pub fn getch() -> Option<char> {
+        key(event::read().expect("Failed to read line"))
+    }
+}
+
+vm::native! {
+    // waits up to time milliseconds for a key
+    pub fn rawread(time: i64) -> Option<char> {
         if event::poll(Duration::from_millis(time as u64)).expect("Error") {
-            if let Event::Key(KeyEvent {
-                code: KeyCode::Char(character),
-                modifiers: event::KeyModifiers::NONE,
-                kind: _,
-                state: _,
-            }) = event::read().expect("Failed to read line")
-            {
-                state.stack.push(VmData::Char(character));
-            } else {
-                state.stack.push(VmData::None);
-            }
+            key(event::read().expect("Failed to read line"))
         } else {
-            state.stack.push(VmData::None);
+            None
         }
     }
-    Ok(())
 }
 
-pub fn clear_screen(_state: &mut state::State) -> Result<(), NovaError> {
-    execute!(stdout(), terminal::Clear(terminal::ClearType::All)).unwrap();
-    execute!(stdout(), MoveTo(0, 0)).unwrap();
-    Ok(())
+// escape sequences are program output, they go where print goes
+fn escape(state: &State, command: impl Command) -> Result<(), NovaError> {
+    let mut text = String::new();
+    command
+        .write_ansi(&mut text)
+        .map_err(|e| NovaError::Runtime {
+            msg: format!("Error writing output: {}", e),
+        })?;
+    state.write(&text)
 }
 
-pub fn hide_cursor(_state: &mut state::State) -> Result<(), NovaError> {
-    execute!(stdout(), crossterm::cursor::Hide).unwrap();
-    Ok(())
+vm::native! {
+    pub fn clear_screen(state: &mut State) -> Result<(), NovaError> {
+        escape(state, terminal::Clear(terminal::ClearType::All))?;
+        escape(state, MoveTo(0, 0))
+    }
 }
 
-pub fn show_cursor(_state: &mut state::State) -> Result<(), NovaError> {
-    execute!(stdout(), crossterm::cursor::Show).unwrap();
-    Ok(())
+vm::native! {
+    pub fn hide_cursor(state: &mut State) -> Result<(), NovaError> {
+        escape(state, crossterm::cursor::Hide)
+    }
 }
 
-pub fn retrieve_command_line_args(state: &mut state::State) -> Result<(), NovaError> {
-    let args: Vec<String> = std::env::args().skip(3).collect();
-    let mut myarray = vec![];
-    state.gclock = true;
-    for arg in args.iter() {
-        let string_pos = state.allocate_string(arg.clone());
-        myarray.push(state.allocate_vmdata_to_heap(VmData::String(string_pos)));
-    }
-    if args.is_empty() {
-        state.stack.push(VmData::None);
-    } else {
-        let index = state.allocate_array(myarray);
-        state.gclock = false;
-        state.stack.push(VmData::List(index));
+vm::native! {
+    pub fn show_cursor(state: &mut State) -> Result<(), NovaError> {
+        escape(state, crossterm::cursor::Show)
     }
+}
 
-    Ok(())
+vm::native! {
+    // the arguments after the script, None when there are none
+    pub fn retrieve_command_line_args() -> Option<Vec<String>> {
+        let args: Vec<String> = std::env::args().skip(3).collect();
+        if args.is_empty() {
+            None
+        } else {
+            Some(args)
+        }
+    }
 }
@@ -1,12 +1,8 @@
 use std::{thread, time};
 
-use common::error::NovaError;
-use vm::state::{self, VmData};
-
-pub fn sleep(state: &mut state::State) -> Result<(), NovaError> {
-    if let Some(VmData::Int(time)) = state.stack.pop() {
-        let delay = time::Duration::from_millis(time as u64);
+vm::native! {
+    pub fn sleep(milliseconds: i64) {
+        let delay = time::Duration::from_millis(milliseconds as u64);
         thread::sleep(delay);
     }
-    Ok(())
 }
@@ -1,28 +1,127 @@
-use common::error::NovaError;
-use novacore::NovaCore;
+use common::error::{MessageFormat, NovaError};
+use novacore::{NovaCore, Options};
 use std::process::exit;
+use vm::capability::{self, Capability};
 
 fn main() {
     if entry_command().is_none() {
         print_help();
-        // TODO: add a repl
     }
 }
 
 fn entry_command() -> Option<()> {
-    let mut args = std::env::args();
+    // -O, -O0, -O1, -O2, --message-format, --plugin, --sandbox, --allow-* and
+    // --max-* may appear anywhere after the command, any of --sandbox or
+    // --allow-* takes every capability away first
+    let mut options = Options::default();
+    let mut message_format = MessageFormat::Human;
+    let mut plugin_follows = false;
+    // the flags the lsp has no use for, it never runs the program
+    let mut run_options = vec![];
+    // reported together before any command runs
+    let mut invalid_options = vec![];
+    let mut args = std::env::args()
+        .filter(|arg| {
+            let is_run_option = arg.starts_with("--max-")
+                || arg == "--sandbox"
+                || arg.starts_with("--allow-")
+                || arg.starts_with("--plugin")
+                || plugin_follows
+                || arg.starts_with("-O");
+            if is_run_option {
+                run_options.push(arg.clone());
+            }
+            let limits = &mut options.limits;
+            if let Some(limit) = arg.strip_prefix("--max-") {
+                let (name, value) = limit.split_once('=').unwrap_or((limit, ""));
+                match (name, value.parse().ok()) {
+                    ("steps", Some(value)) => limits.steps = Some(value),
+                    ("heap", Some(value)) => limits.heap = Some(value as usize),
+                    ("stack", Some(value)) => limits.stack = Some(value as usize),
+                    ("depth", Some(value)) => limits.depth = Some(value as usize),
+                    _ => invalid_options.push(arg.clone()),
+                }
+                return false;
+            }
+            if arg == "--sandbox" {
+                options.permissions.get_or_insert_with(capability::none);
+                return false;
+            }
+            if let Some(grant) = arg.strip_prefix("--allow-") {
+                let (name, directory) = match grant.split_once('=') {
+                    Some((name, directory)) => (name, Some(directory)),
+                    None => (grant, None),
+                };
+                let granted = options.permissions.get_or_insert_with(capability::none);
+                match (Capability::from_name(name), directory) {
+                    (Some(capability), Some(directory)) => {
+                        granted.allow_path(capability, directory)
+                    }
+                    (Some(capability), None) => granted.allow(capability),
+                    (None, _) => invalid_options.push(arg.clone()),
+                }
+                return false;
+            }
+            if plugin_follows {
+                options.plugins.push(arg.clone());
+                plugin_follows = false;
+                return false;
+            }
+            if arg == "--plugin" {
+                plugin_follows = true;
+                return false;
+            }
+            if let Some(plugin) = arg.strip_prefix("--plugin=") {
+                options.plugins.push(plugin.to_string());
+                return false;
+            }
+            if let Some(level) = arg.strip_prefix("-O") {
+                match level {
+                    "" => options.optimization_level = 2,
+                    "0" => options.optimization_level = 0,
+                    "1" => options.optimization_level = 1,
+                    "2" => options.optimization_level = 2,
+                    _ => invalid_options.push(arg.clone()),
+                }
+                return false;
+            }
+            if let Some(format) = arg.strip_prefix("--message-format=") {
+                match format {
+                    "human" => message_format = MessageFormat::Human,
+                    "json" => message_format = MessageFormat::Json,
+                    _ => invalid_options.push(arg.clone()),
+                }
+                return false;
+            }
+            true
+        })
+        .collect::<Vec<String>>()
+        .into_iter();
+    if !invalid_options.is_empty() {
+        for option in invalid_options.iter() {
+            println!("invalid option '{option}'");
+        }
+        println!("run 'nova help' to see the options");
+        exit(1);
+    }
     args.next(); // Skip the file path
     let command = args.next()?;
+    if command == "lsp" && !run_options.is_empty() {
+        for option in run_options.iter() {
+            println!("option '{option}' does not apply to lsp");
+        }
+        exit(1);
+    }
 
     let handle_error = |result: Result<(), NovaError>| {
         if let Err(e) = result {
-            e.show();
+            e.report(message_format);
             exit(1);
         }
     };
 
     let execute_command = |filepath: String, action: fn(NovaCore) -> Result<(), NovaError>| {
-        let novacore = compile_file_or_exit(&filepath);
+        let novacore = compile_file_or_exit(&filepath, message_format, &options);
         handle_error(action(novacore));
     };
 
@@ -32,7 +131,7 @@ fn entry_command() -> Option<()> {
         "dis" => execute_command(args.next()?, NovaCore::dis_file),
         "time" => {
             let filepath = args.next()?;
-            let novacore = compile_file_or_exit(&filepath);
+            let novacore = compile_file_or_exit(&filepath, message_format, &options);
             let start_time = std::time::Instant::now();
             let execution_result = novacore.run();
             println!("Execution time: {}ms", start_time.elapsed().as_millis());
@@ -41,10 +140,44 @@ fn entry_command() -> Option<()> {
         "check" => {
             let filepath = args.next()?;
             let start_time = std::time::Instant::now();
-            let novacore = compile_file_or_exit(&filepath);
-            handle_error(novacore.check());
-            println!("OK | Compile time: {}ms", start_time.elapsed().as_millis());
+            let novacore = compile_file_or_exit(&filepath, message_format, &options);
+            if let Err(errors) = novacore.check() {
+                for error in errors.iter() {
+                    error.report(message_format);
+                }
+                if message_format == MessageFormat::Human {
+                    match errors.len() {
+                        1 => println!("1 error found"),
+                        count => println!("{count} errors found"),
+                    }
+                }
+                exit(1);
+            }
+            if message_format == MessageFormat::Human {
+                println!("OK | Compile time: {}ms", start_time.elapsed().as_millis());
+            }
+        }
+        "build" => {
+            let filepath = args.next()?;
+            let output = match args.next() {
+                Some(flag) if flag == "-o" => args.next()?,
+                Some(_) => return None,
+                None => std::path::Path::new(&filepath)
+                    .with_extension("nvc")
+                    .to_string_lossy()
+                    .to_string(),
+            };
+            let novacore = compile_file_or_exit(&filepath, message_format, &options);
+            handle_error(novacore.build(&output));
+        }
+        "exec" => {
+            let filepath = args.next()?;
+            let novacore = NovaCore::from_bytecode(&filepath, &options);
+            handle_error(novacore.and_then(NovaCore::exec));
         }
+        "repl" => handle_error(novacore::repl::new(&options).and_then(|mut repl| repl.run())),
+        "dap" => handle_error(novacore::dap::new(options.clone()).run()),
+        "lsp" => handle_error(novacore::lsp::new().run()),
         _ => print_help(),
     }
 
@@ -55,18 +188,34 @@ fn print_help() {
     println!("Nova 0.1.0: by pyrotek45\n");
     println!("HELP MENU");
     println!("\trun   [file]  // runs the file using the nova vm");
-    println!("\tdbg   [file]  // debug the file");
+    println!("\tdbg   [file]  // step through the file in the debugger");
     println!("\ttime  [file]  // time the file");
     println!("\tcheck [file]  // check if the file compiles");
     println!("\tdis   [file]  // disassemble the file");
+    println!("\tbuild [file]  // compile the file to bytecode, -o [file] sets the output");
+    println!("\texec  [file]  // run a compiled bytecode file");
+    println!("\trepl          // start an interactive session");
+    println!("\tdap           // serve the debug adapter protocol over stdio");
+    println!("\tlsp           // serve the language server protocol over stdio");
     println!("\thelp          // displays this menu");
+    println!("\n\t-O[level]      // optimization level 0-2, -O alone is 2");
+    println!("\t--message-format=[human|json] // print errors as text or one json object per line");
+    println!("\t--plugin [library] // load a native plugin library, may be given more than once");
+    println!("\t--sandbox       // deny every capability: fs-read, fs-write, terminal, time, random, process, plugin");
+    println!("\t--allow-[capability][=dir] // grant a capability, fs-read, fs-write and plugin can be limited to dir");
+    println!("\t--max-[steps|heap|stack|depth]=[n] // stop the program once it executes n instructions, uses n heap cells, holds n values or nests n calls");
 }
 
-fn compile_file_or_exit(file: &str) -> NovaCore {
-    match novacore::NovaCore::new(file) {
+fn compile_file_or_exit(file: &str, message_format: MessageFormat, options: &Options) -> NovaCore {
+    let novacore = novacore::NovaCore::new(file).and_then(|mut novacore| {
+        novacore.set_message_format(message_format);
+        novacore.configure(options)?;
+        Ok(novacore)
+    });
+    match novacore {
         Ok(novacore) => novacore,
         Err(error) => {
-            error.show();
+            error.report(message_format);
             exit(1);
         }
     }
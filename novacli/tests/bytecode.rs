@@ -0,0 +1,93 @@
+use std::{path::PathBuf, process::Command};
+
+use common::bytecode::{MAGIC, VERSION};
+
+const PROGRAM: &str = "module built
+
+struct P { x: Int, name: String }
+
+fn extends bump(p: P) -> P { return P { x: p.x + 1, name: p.name } }
+
+let base = 10
+let add = fn(n: Int) -> Int { return base + n }
+let total = 0
+for i <- 0; i < 5; i += 1 {
+    total += add(i)
+}
+println(total)
+println(P { x: 1, name: \"p\" }.bump().x)
+println([1, 2, 3].map(fn(x: Int) -> Int { return x * x }))
+";
+
+fn program(name: &str, source: &[u8]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("nova-bytecode-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(name);
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+fn nova(arguments: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_nova"))
+        .args(arguments)
+        .output()
+        .expect("nova should run");
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("panicked"), "{stderr}");
+    (output.status.success(), stdout)
+}
+
+// each test builds its own copy, they run side by side
+fn built(name: &str) -> Vec<u8> {
+    let source = program(&format!("{name}.nv"), PROGRAM.as_bytes());
+    let source = source.to_str().unwrap();
+    let output = source.replace(".nv", ".nvc");
+    let (success, stdout) = nova(&["build", source, "-o", &output]);
+    assert!(success, "{stdout}");
+    std::fs::read(output).unwrap()
+}
+
+#[test]
+fn exec_matches_run() {
+    let bytes = built("exec");
+    let compiled = program("round_trip.nvc", &bytes);
+    let source = program("round_trip.nv", PROGRAM.as_bytes());
+    let ran = nova(&["run", source.to_str().unwrap()]);
+    let executed = nova(&["exec", compiled.to_str().unwrap()]);
+    assert!(ran.0, "{}", ran.1);
+    assert_eq!(ran, executed);
+    assert_eq!(executed.1, "60\n2\n[1,4,9]\n");
+}
+
+#[test]
+fn other_magic_or_version_is_rejected() {
+    let bytes = built("rejected");
+    assert_eq!(&bytes[0..4], MAGIC);
+
+    let mut magic = bytes.clone();
+    magic[0..4].copy_from_slice(b"NOPE");
+    let path = program("magic.nvc", &magic);
+    let (success, stdout) = nova(&["exec", path.to_str().unwrap()]);
+    assert!(!success);
+    assert!(stdout.contains("not a nova bytecode file"), "{stdout}");
+
+    let mut version = bytes.clone();
+    version[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    let path = program("version.nvc", &version);
+    let (success, stdout) = nova(&["exec", path.to_str().unwrap()]);
+    assert!(!success);
+    assert!(
+        stdout.contains(&format!(
+            "bytecode version {} is not supported, expected version {VERSION}",
+            VERSION + 1
+        )),
+        "{stdout}"
+    );
+
+    // too short to even hold the header
+    let path = program("short.nvc", b"NOV");
+    let (success, stdout) = nova(&["exec", path.to_str().unwrap()]);
+    assert!(!success);
+    assert!(stdout.contains("not a nova bytecode file"), "{stdout}");
+}
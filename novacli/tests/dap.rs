@@ -0,0 +1,313 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const PROGRAM: &str = "module debugged
+
+fn add(a: Int, b: Int) -> Int {
+    let sum = a + b
+    return sum
+}
+
+let x = 1
+let y = add(x, 2)
+println(y)
+";
+
+// a scripted debug adapter client talking to `nova dap` over its stdio
+struct Client {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    seq: i64,
+    // events that arrived while waiting for a response
+    events: Vec<Value>,
+}
+
+impl Client {
+    fn start(options: &[&str]) -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nova"))
+            .arg("dap")
+            .args(options)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("nova dap should start");
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        Client {
+            child,
+            input,
+            output,
+            seq: 1,
+            events: vec![],
+        }
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) -> i64 {
+        let seq = self.seq;
+        self.seq += 1;
+        let body = json!({
+            "seq": seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.input.flush().unwrap();
+        seq
+    }
+
+    fn read(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            assert!(
+                self.output.read_line(&mut header).unwrap() > 0,
+                "nova dap closed stdout"
+            );
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    // sends a request and returns its response, it has to succeed
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        let seq = self.send(command, arguments);
+        loop {
+            let message = self.read();
+            if message["type"] == "event" {
+                self.events.push(message);
+                continue;
+            }
+            assert_eq!(message["request_seq"], seq);
+            assert_eq!(message["command"], command);
+            assert_eq!(message["success"], true, "{command} failed: {message}");
+            return message["body"].clone();
+        }
+    }
+
+    fn event(&mut self, event: &str) -> Value {
+        if let Some(index) = self
+            .events
+            .iter()
+            .position(|message| message["event"] == event)
+        {
+            return self.events.remove(index)["body"].clone();
+        }
+        loop {
+            let message = self.read();
+            if message["type"] == "event" && message["event"] == event {
+                return message["body"].clone();
+            }
+            self.events.push(message);
+        }
+    }
+
+    fn top_frame(&mut self) -> Value {
+        let trace = self.request("stackTrace", json!({ "threadId": 1 }));
+        trace["stackFrames"][0].clone()
+    }
+
+    fn variables(&mut self, frame: i64, scope: &str) -> Vec<(String, String)> {
+        let scopes = self.request("scopes", json!({ "frameId": frame }));
+        let reference = scopes["scopes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|found| found["name"] == scope)
+            .map(|found| found["variablesReference"].clone())
+            .unwrap();
+        let variables = self.request("variables", json!({ "variablesReference": reference }));
+        variables["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variable| {
+                (
+                    variable["name"].as_str().unwrap().to_string(),
+                    variable["value"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn program(name: &str, source: &str) -> String {
+    let directory = std::env::temp_dir().join(format!("nova-dap-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let program = directory.join(name);
+    std::fs::write(&program, source).unwrap();
+    program.to_string_lossy().to_string()
+}
+
+#[test]
+fn debug_session() {
+    let program = program("debugged.nv", PROGRAM);
+
+    let mut client = Client::start(&[]);
+    client.request("initialize", json!({ "adapterID": "nova" }));
+    client.request("launch", json!({ "program": program }));
+    client.event("initialized");
+
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": program }, "breakpoints": [{ "line": 9 }, { "line": 3 }] }),
+    );
+    assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+    assert_eq!(breakpoints["breakpoints"][1]["verified"], false);
+
+    client.request("configurationDone", json!({}));
+    assert_eq!(client.event("stopped")["reason"], "breakpoint");
+    let frame = client.top_frame();
+    assert_eq!(frame["line"], 9);
+    assert_eq!(frame["source"]["path"], program);
+    // top level lets live in the frame of main, functions are left out of the globals
+    let locals = client.variables(0, "Locals");
+    assert!(locals.contains(&("x".to_string(), "1".to_string())));
+    assert!(client.variables(0, "Globals").is_empty());
+
+    client.request("stepIn", json!({ "threadId": 1 }));
+    assert_eq!(client.event("stopped")["reason"], "step");
+    let frame = client.top_frame();
+    assert_eq!(frame["name"], "add");
+    assert_eq!(frame["line"], 4);
+    let locals = client.variables(0, "Locals");
+    assert!(locals.contains(&("a".to_string(), "1".to_string())));
+    assert!(locals.contains(&("b".to_string(), "2".to_string())));
+
+    client.request("next", json!({ "threadId": 1 }));
+    assert_eq!(client.event("stopped")["reason"], "step");
+    assert_eq!(client.top_frame()["line"], 5);
+
+    client.request("stepOut", json!({ "threadId": 1 }));
+    assert_eq!(client.event("stopped")["reason"], "step");
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(trace["totalFrames"], 1);
+
+    client.request("continue", json!({ "threadId": 1 }));
+    assert_eq!(client.event("exited")["exitCode"], 0);
+    // output is forwarded as it is written, a line can come in pieces
+    let output = client
+        .events
+        .iter()
+        .filter(|message| message["event"] == "output")
+        .map(|message| {
+            assert_eq!(message["body"]["category"], "stdout");
+            message["body"]["output"].as_str().unwrap().to_string()
+        })
+        .collect::<String>();
+    assert_eq!(output, "3\n");
+    client.event("terminated");
+
+    client.request("disconnect", json!({}));
+    assert!(client.child.wait().unwrap().success());
+    let _ = std::fs::remove_file(&program);
+}
+
+#[test]
+fn launch_options_apply() {
+    let program = program(
+        "sandboxed.nv",
+        "module sandboxed\n\nprintln(random(1, 2))\n",
+    );
+    let mut client = Client::start(&["--sandbox"]);
+    client.request("initialize", json!({ "adapterID": "nova" }));
+    client.request("launch", json!({ "program": program }));
+    client.event("initialized");
+    client.request("configurationDone", json!({}));
+    assert_eq!(client.event("exited")["exitCode"], 1);
+    let output = client.event("output");
+    assert_eq!(output["category"], "stderr");
+    assert!(
+        output["output"]
+            .as_str()
+            .unwrap()
+            .contains("'random' needs the random capability"),
+        "{output}"
+    );
+    client.request("disconnect", json!({}));
+    assert!(client.child.wait().unwrap().success());
+    let _ = std::fs::remove_file(&program);
+}
+
+#[test]
+fn limits_apply_while_debugging() {
+    let program = program(
+        "limited.nv",
+        "module limited\n\nlet x = 1\nwhile true {\n    x = x + 1\n}\n",
+    );
+    let mut client = Client::start(&["--max-steps=1000"]);
+    client.request("initialize", json!({ "adapterID": "nova" }));
+    client.request("launch", json!({ "program": program }));
+    client.event("initialized");
+    client.request("configurationDone", json!({}));
+    assert_eq!(client.event("exited")["exitCode"], 1);
+    let output = client.event("output");
+    assert!(
+        output["output"]
+            .as_str()
+            .unwrap()
+            .contains("execution stopped after 1000 steps"),
+        "{output}"
+    );
+    client.request("disconnect", json!({}));
+    assert!(client.child.wait().unwrap().success());
+    let _ = std::fs::remove_file(&program);
+}
+
+#[test]
+fn struct_locals_show_like_print() {
+    let program = program(
+        "structs.nv",
+        "module structs
+
+struct P { name: String, age: Int }
+
+fn older(p: P) -> Int {
+    let next = P { name: p.name, age: p.age + 1 }
+    return next.age
+}
+println(older(P { name: \"bob\", age: 3 }))
+",
+    );
+    let mut client = Client::start(&[]);
+    client.request("initialize", json!({ "adapterID": "nova" }));
+    client.request("launch", json!({ "program": program }));
+    client.event("initialized");
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": program }, "breakpoints": [{ "line": 7 }] }),
+    );
+    client.request("configurationDone", json!({}));
+    assert_eq!(client.event("stopped")["reason"], "breakpoint");
+    let locals = client.variables(0, "Locals");
+    assert!(
+        locals.contains(&("p".to_string(), "P { name: \"bob\", age: 3 }".to_string())),
+        "{locals:?}"
+    );
+    assert!(
+        locals.contains(&(
+            "next".to_string(),
+            "P { name: \"bob\", age: 4 }".to_string()
+        )),
+        "{locals:?}"
+    );
+    client.request("continue", json!({ "threadId": 1 }));
+    assert_eq!(client.event("exited")["exitCode"], 0);
+    client.request("disconnect", json!({}));
+    assert!(client.child.wait().unwrap().success());
+    let _ = std::fs::remove_file(&program);
+}
@@ -0,0 +1,91 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde_json::{json, Value};
+
+fn program(name: &str, source: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("nova-json-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(name);
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+// the single diagnostic nova prints for the program
+fn diagnostic(path: &Path) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_nova"))
+        .args(["run", &path.to_string_lossy(), "--message-format=json"])
+        .output()
+        .expect("nova should run");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines = stdout.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 1, "{stdout}");
+    serde_json::from_str(lines[0]).expect("each line should be json")
+}
+
+#[test]
+fn parse_error() {
+    let path = program("parse.nv", "module parse\n\nlet x = 1\nprintln(y)\n");
+    let file = path.to_string_lossy();
+    let found = diagnostic(&path);
+    assert_eq!(found["kind"], "parsing");
+    assert_eq!(found["severity"], "error");
+    assert_eq!(found["message"], "E2 Not a valid symbol: y");
+    assert_eq!(found["note"], "Unknown identifier\nDid you mean? x");
+    assert_eq!(found["file"], file.as_ref());
+    assert_eq!(
+        [
+            &found["line"],
+            &found["column"],
+            &found["end_line"],
+            &found["end_column"]
+        ],
+        [4, 9, 4, 10]
+    );
+    assert_eq!(found["related"], json!([]));
+    assert_eq!(found["trace"], json!([]));
+}
+
+#[test]
+fn runtime_error_with_trace() {
+    let path = program(
+        "runtime.nv",
+        "module runtime
+
+fn first(x: ?Int) -> Int {
+    return x.unwrap()
+}
+fn outer() -> Int {
+    return first(?Int)
+}
+println(outer())
+",
+    );
+    let file = path.to_string_lossy();
+    let found = diagnostic(&path);
+    assert_eq!(found["kind"], "runtime");
+    assert_eq!(found["severity"], "error");
+    assert_eq!(found["message"], "tried to unwrap a None value");
+    assert_eq!(found["note"], Value::Null);
+    assert_eq!(found["file"], file.as_ref());
+    assert_eq!(
+        [
+            &found["line"],
+            &found["column"],
+            &found["end_line"],
+            &found["end_column"]
+        ],
+        [4, 5, 4, 22]
+    );
+    assert_eq!(
+        found["trace"],
+        json!([
+            { "function": "first", "file": file, "line": 4, "column": 5, "count": 1 },
+            { "function": "outer", "file": file, "line": 7, "column": 5, "count": 1 },
+            { "function": "main", "file": file, "line": 9, "column": 1, "count": 1 },
+        ])
+    );
+}
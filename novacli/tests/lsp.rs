@@ -0,0 +1,251 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const PROGRAM: &str = "module edited
+
+struct P { x: Int, name: String }
+
+fn extends bump(p: P) -> P { return P { x: p.x + 1, name: p.name } }
+
+let p = P { x: 1, name: \"p\" }
+let n: Int = \"x\"
+println(p.bump().x)
+";
+
+// a scripted language client talking to `nova lsp` over its stdio
+struct Client {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    id: i64,
+    // notifications that arrived while waiting for a response
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_nova"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("nova lsp should start");
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        Client {
+            child,
+            input,
+            output,
+            id: 1,
+            notifications: vec![],
+        }
+    }
+
+    fn write(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn read(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            assert!(
+                self.output.read_line(&mut header).unwrap() > 0,
+                "nova lsp closed stdout"
+            );
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.write(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    // sends a request and returns its result, it has to succeed
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.id;
+        self.id += 1;
+        self.write(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.read();
+            if message["id"].is_null() {
+                self.notifications.push(message);
+                continue;
+            }
+            assert_eq!(message["id"], id);
+            assert!(message["error"].is_null(), "{method} failed: {message}");
+            return message["result"].clone();
+        }
+    }
+
+    fn notification(&mut self, method: &str) -> Value {
+        if let Some(index) = self
+            .notifications
+            .iter()
+            .position(|message| message["method"] == method)
+        {
+            return self.notifications.remove(index)["params"].clone();
+        }
+        loop {
+            let message = self.read();
+            if message["method"] == method {
+                return message["params"].clone();
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    // line and character count from zero, like the protocol
+    fn at(&mut self, method: &str, uri: &str, line: usize, character: usize) -> Value {
+        self.request(
+            method,
+            json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+            }),
+        )
+    }
+}
+
+#[test]
+fn editing_session() {
+    let path = std::env::temp_dir()
+        .join(format!("nova-lsp-{}", std::process::id()))
+        .join("edited.nv");
+    let uri = format!("file://{}", path.to_string_lossy());
+
+    let mut client = Client::start();
+    let capabilities = client.request("initialize", json!({ "capabilities": {} }));
+    assert_eq!(capabilities["capabilities"]["hoverProvider"], true);
+    assert_eq!(capabilities["capabilities"]["definitionProvider"], true);
+    assert_eq!(
+        capabilities["capabilities"]["completionProvider"]["triggerCharacters"],
+        json!(["."])
+    );
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": uri, "languageId": "nova", "version": 1, "text": PROGRAM },
+        }),
+    );
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(published["uri"], uri);
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert!(
+        diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Cannot assign String to Int"),
+        "{diagnostics:?}"
+    );
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 7);
+
+    // `p` in the last line
+    let hover = client.at("textDocument/hover", &uri, 8, 8);
+    assert_eq!(hover["contents"]["value"], "```nova\np: P\n```");
+    assert_eq!(
+        hover["range"]["start"],
+        json!({ "line": 8, "character": 8 })
+    );
+
+    let definition = client.at("textDocument/definition", &uri, 8, 8);
+    assert_eq!(definition["uri"], uri);
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 6, "character": 4 })
+    );
+
+    // right after the dot of `p.bump`
+    let completion = client.at("textDocument/completion", &uri, 8, 10);
+    let mut labels = completion
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    labels.sort();
+    assert_eq!(labels, ["bump", "name", "x"]);
+
+    // fixing the error clears the diagnostics
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "text": PROGRAM.replace("\"x\"", "2") }],
+        }),
+    );
+    let published = client.notification("textDocument/publishDiagnostics");
+    assert_eq!(published["diagnostics"], json!([]));
+
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
+
+#[test]
+fn escaped_paths_and_native_imports() {
+    let directory = std::env::temp_dir().join(format!("nova-lsp-{}", std::process::id()));
+    // the directory `a b#%é` escaped the way editors send it
+    let uri = format!(
+        "file://{}/a%20b%23%25%C3%A9/native.nv",
+        directory.to_string_lossy()
+    );
+    let text = "module native
+
+import native \"stats\"
+
+let x = 1
+println(x)
+";
+
+    let mut client = Client::start();
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("initialized", json!({}));
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": uri, "languageId": "nova", "version": 1, "text": text },
+        }),
+    );
+    // plugins are never opened while editing
+    let published = client.notification("textDocument/publishDiagnostics");
+    let diagnostics = published["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert!(
+        diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("cannot import native module 'stats'"),
+        "{diagnostics:?}"
+    );
+
+    // the definition of `x` comes back under the uri it was opened with
+    let definition = client.at("textDocument/definition", &uri, 5, 8);
+    assert_eq!(definition["uri"], uri);
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 4, "character": 4 })
+    );
+
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
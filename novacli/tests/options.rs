@@ -0,0 +1,39 @@
+use std::process::Command;
+
+#[test]
+fn invalid_options() {
+    for option in [
+        "--max-steps=abc",
+        "--max-foo=1",
+        "-Ofast",
+        "--allow-net",
+        "--message-format=xml",
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_nova"))
+            .args(["run", "missing.nv", option])
+            .output()
+            .expect("nova should run");
+        assert!(!output.status.success(), "{option} was accepted");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(&format!("invalid option '{option}'")),
+            "{stdout}"
+        );
+    }
+}
+
+#[test]
+fn lsp_rejects_run_options() {
+    for option in ["-O2", "--sandbox", "--max-steps=10", "--allow-time"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_nova"))
+            .args(["lsp", option])
+            .output()
+            .expect("nova should run");
+        assert!(!output.status.success(), "{option} was accepted");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(&format!("option '{option}' does not apply to lsp")),
+            "{stdout}"
+        );
+    }
+}
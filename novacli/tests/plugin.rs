@@ -0,0 +1,134 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const PROGRAM: &str = "module uses
+
+import native \"stats\"
+
+println(stats::clamp(15, 0, 10))
+println(stats::mean([1.0, 2.0, 3.0]))
+println(stats::count([\"a\", \"b\"]))
+stats::times(3, fn(i: Int) {
+    println(i * 10)
+})
+";
+
+// builds the sample plugin of the workspace next to the nova binary
+fn build_plugin() -> PathBuf {
+    let nova = Path::new(env!("CARGO_BIN_EXE_nova"));
+    let directory = nova.parent().unwrap().to_path_buf();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "-p", "plugin"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"));
+    // the binary lives in target/release when the tests run with --release
+    if directory.ends_with("release") {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().expect("cargo should run");
+    assert!(status.success(), "the plugin crate failed to build");
+    directory
+}
+
+fn program(name: &str, source: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("nova-plugin-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(name);
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+fn nova(plugins: &Path, arguments: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nova"))
+        .args(arguments)
+        .env("NOVA_PLUGIN_PATH", plugins)
+        .output()
+        .expect("nova should run")
+}
+
+#[test]
+fn import_native() {
+    let plugins = build_plugin();
+    let path = program("import.nv", PROGRAM);
+    let output = nova(&plugins, &["run", &path.to_string_lossy()]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "10\n2\n2\n0\n10\n20\n"
+    );
+}
+
+#[test]
+fn plugin_flag() {
+    let plugins = build_plugin();
+    let library = plugins.join(format!(
+        "{}stats{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let path = program(
+        "flag.nv",
+        "module flag\n\nprintln(stats::clamp(-5, 0, 10))\n",
+    );
+    let output = nova(
+        &plugins,
+        &[
+            "run",
+            &path.to_string_lossy(),
+            "--plugin",
+            &library.to_string_lossy(),
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "0\n");
+}
+
+#[test]
+fn sandbox() {
+    let plugins = build_plugin();
+    let path = program("sandbox.nv", PROGRAM);
+    let path = path.to_string_lossy();
+    let output = nova(&plugins, &["run", &path, "--sandbox"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("needs the plugin capability"));
+    let output = nova(&plugins, &["run", &path, "--sandbox", "--allow-plugin"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // plugins may be granted for the directory they live in only
+    let allowed = format!("--allow-plugin={}", plugins.to_string_lossy());
+    let output = nova(&plugins, &["run", &path, "--sandbox", &allowed]);
+    assert!(output.status.success(), "{:?}", output);
+    let elsewhere = format!("--allow-plugin={}", std::env::temp_dir().to_string_lossy());
+    let output = nova(&plugins, &["run", &path, "--sandbox", &elsewhere]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("may not access"));
+
+    let library = plugins.join(format!(
+        "{}stats{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let flag = program("sandbox_flag.nv", "module flag\n");
+    let output = nova(
+        &plugins,
+        &[
+            "run",
+            &flag.to_string_lossy(),
+            "--sandbox",
+            "--plugin",
+            &library.to_string_lossy(),
+        ],
+    );
+    assert!(!output.status.success());
+
+    // names may not leave the directories plugins are looked up in
+    let climbing = program(
+        "climbing.nv",
+        "module climbing\n\nimport native \"../stats\"\n",
+    );
+    let output = nova(&plugins, &["run", &climbing.to_string_lossy()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("outside of the plugin directories"));
+}
@@ -0,0 +1,145 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// feeds the lines to `nova repl` and returns everything it printed
+fn session(lines: &str) -> String {
+    session_with(&[], lines)
+}
+
+fn session_with(options: &[&str], lines: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nova"))
+        .arg("repl")
+        .args(options)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("nova repl should start");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(lines.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+// the echoes of a session, one per line, without the prompts
+fn echoes(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .map(|line| line.replace(">> ", "").replace(".. ", ""))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[test]
+fn bindings_persist() {
+    let output = session(
+        "let x = 1
+fn add(a: Int, b: Int) -> Int {
+    return a + b
+}
+add(x, 2)
+x = 5
+add(x, x)
+",
+    );
+    assert_eq!(echoes(&output), ["3 : Int", "10 : Int"]);
+}
+
+#[test]
+fn values_are_shown_by_their_type() {
+    let output = session(
+        "struct P { x: Int, name: String }
+let p = P { x: 1, name: \"a\" }
+p
+[p]
+\"text\"
+",
+    );
+    assert_eq!(
+        echoes(&output),
+        [
+            "P { x: 1, name: \"a\" } : P",
+            "[P { x: 1, name: \"a\" }] : [P]",
+            "text : String"
+        ]
+    );
+}
+
+#[test]
+fn errors_are_recovered_from() {
+    let output = session(
+        "let x = 1
+let y: Int = \"no\"
+let z = [1][5]
+fn twice(a: Int) -> Int {
+    return a * 2
+}
+twice(x)
+y
+",
+    );
+    let echoes = echoes(&output);
+    assert!(output.contains("Parsing Error"), "{output}");
+    assert!(output.contains("Runtime Error"), "{output}");
+    // the bindings of the chunks that failed are gone, the rest still work
+    assert!(echoes.contains(&"2 : Int".to_string()), "{output}");
+    assert!(output.contains("Unknown identifier"), "{output}");
+}
+
+#[test]
+fn brackets_in_chars_and_comments() {
+    let output = session(
+        "let c = '('
+c
+let d = '\\''
+// (
+[
+    1, // ]
+    2
+]
+\"{\"
+",
+    );
+    assert_eq!(echoes(&output), ["( : Char", "[1,2] : [Int]", "{ : String"]);
+}
+
+#[test]
+fn options_apply() {
+    let lines = "fn down(n: Int) -> Int {
+    if n == 0 { return 0 }
+    return down(n - 1)
+}
+down(100)
+random(1, 1)
+";
+    let output = session_with(&["--max-depth=20"], lines);
+    assert!(output.contains("Limit Error"), "{output}");
+    assert_eq!(echoes(&output).last().unwrap(), "1 : Int");
+    // the self call in tail position reuses its frame at -O2
+    let output = session_with(&["--max-depth=20", "-O2", "--sandbox"], lines);
+    assert!(!output.contains("Limit Error"), "{output}");
+    assert!(output.contains("Runtime Error"), "{output}");
+    assert_eq!(echoes(&output)[0], "0 : Int");
+}
+
+#[test]
+fn traces_name_the_top_level() {
+    let output = session(
+        "fn first(x: ?Int) -> Int {
+    return x.unwrap()
+}
+first(?Int)
+",
+    );
+    assert!(output.contains("tried to unwrap a None value"), "{output}");
+    assert!(output.contains("  at first (repl:2:5)\n"), "{output}");
+    assert!(output.contains("  at <repl> (repl:1:1)\n"), "{output}");
+    assert!(!output.contains("at ?"), "{output}");
+}
@@ -0,0 +1,72 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// a type error spanning a chained call that is long enough to be elided
+const CHAINED: &str = "module chained
+
+let total: Int = [1, 2, 3]
+    .map(fn(x: Int) -> Int { return x * 2 })
+    .map(fn(x: Int) -> Int {
+        let y = x + 1
+        return y
+    })
+println(total)
+";
+
+#[test]
+fn span_over_a_chained_call() {
+    let directory = std::env::temp_dir().join(format!("nova-spans-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("chained.nv");
+    std::fs::write(&path, CHAINED).unwrap();
+    let file = path.to_string_lossy();
+
+    // colors are left out when stdout is not a terminal
+    let output = Command::new(env!("CARGO_BIN_EXE_nova"))
+        .args(["run", &file])
+        .output()
+        .expect("nova should run");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!(
+        "Parsing Error in {file}:3:18
+  |
+3 | let total: Int = [1, 2, 3]
+  |                  ^^^^^^^^^
+4 |     .map(fn(x: Int) -> Int {{ return x * 2 }})
+...
+7 |         return y
+8 |     }})
+  |     ^^ Cannot assign [Int] to Int
+--> {file}:3:12
+  |
+3 | let total: Int = [1, 2, 3]
+  |            --- expected because of this annotation
+"
+    );
+    assert!(stdout.starts_with(&expected), "{stdout}");
+}
+
+#[test]
+fn unreadable_source_still_shows_the_message() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nova"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("nova repl should start");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"let y: Int = \"no\"\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Parsing Error in repl:1:14\nCannot assign String to Int\n--> repl:1:8\nexpected because of this annotation\n"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,419 @@
+use std::io;
+
+use common::error::NovaError;
+use serde_json::{json, Value};
+use vm::{
+    debugger::{Debugger, Mode},
+    state::VmData,
+    stream::{self, Buffer, Streams},
+};
+
+use crate::{
+    protocol::{read_message, write_message},
+    NovaCore, Options,
+};
+
+// the vm only ever runs a single thread
+const THREAD_ID: i64 = 1;
+// variable references, every frame after the globals gets its own
+const GLOBALS_REFERENCE: i64 = 1;
+const FRAMES_REFERENCE: i64 = 2;
+
+// a debug adapter protocol server over stdin and stdout, the program
+// output is captured and forwarded to the client as output events
+pub struct Dap {
+    client: Client,
+    session: Option<Session>,
+    // breakpoints that were set before the program was launched
+    pending: Vec<(String, Vec<usize>)>,
+    stop_on_entry: bool,
+    // applied to the program of every launch
+    options: Options,
+}
+
+struct Session {
+    core: NovaCore,
+    debugger: Debugger,
+    finished: bool,
+    stdout: Buffer,
+    stderr: Buffer,
+}
+
+struct Client {
+    seq: i64,
+}
+
+pub fn new(options: Options) -> Dap {
+    Dap {
+        client: Client { seq: 1 },
+        session: None,
+        pending: vec![],
+        stop_on_entry: false,
+        options,
+    }
+}
+
+impl Dap {
+    pub fn run(&mut self) -> Result<(), NovaError> {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+        while let Some(request) = read_message(&mut input)? {
+            if !self.handle(&request) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // returns false once the client disconnects
+    fn handle(&mut self, request: &Value) -> bool {
+        let command = request["command"].as_str().unwrap_or_default();
+        let arguments = &request["arguments"];
+        match command {
+            "initialize" => self.client.respond(
+                request,
+                json!({
+                    "supportsConfigurationDoneRequest": true,
+                }),
+            ),
+            "launch" => match self.launch(arguments) {
+                Ok(()) => {
+                    self.client.respond(request, json!({}));
+                    self.client.event("initialized", json!({}));
+                }
+                Err(error) => self.client.fail(request, &error.message()),
+            },
+            "setBreakpoints" => {
+                let body = self.set_breakpoints(arguments);
+                self.client.respond(request, body)
+            }
+            "configurationDone" => {
+                self.client.respond(request, json!({}));
+                let mode = if self.stop_on_entry {
+                    Mode::Step
+                } else {
+                    Mode::Continue
+                };
+                self.resume(mode, true);
+            }
+            "threads" => self.client.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "stackTrace" => {
+                let body = self.stack_trace();
+                self.client.respond(request, body)
+            }
+            "scopes" => {
+                let frame = arguments["frameId"].as_i64().unwrap_or_default();
+                self.client.respond(
+                    request,
+                    json!({ "scopes": [
+                        {
+                            "name": "Locals",
+                            "variablesReference": FRAMES_REFERENCE + frame,
+                            "expensive": false,
+                        },
+                        {
+                            "name": "Globals",
+                            "variablesReference": GLOBALS_REFERENCE,
+                            "expensive": false,
+                        },
+                    ]}),
+                )
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"]
+                    .as_i64()
+                    .unwrap_or_default();
+                let body = self.variables(reference);
+                self.client.respond(request, body)
+            }
+            "next" | "stepIn" | "stepOut" | "continue" => {
+                let depth = match &self.session {
+                    Some(session) => session.core.vm.state.callstack.len(),
+                    None => {
+                        self.client.fail(request, "no program is running");
+                        return true;
+                    }
+                };
+                let mode = match command {
+                    "next" => Mode::Next(depth),
+                    "stepIn" => Mode::Step,
+                    "stepOut" => Mode::Finish(depth),
+                    _ => Mode::Continue,
+                };
+                self.client
+                    .respond(request, json!({ "allThreadsContinued": true }));
+                self.resume(mode, false);
+            }
+            "disconnect" | "terminate" => {
+                self.client.respond(request, json!({}));
+                return false;
+            }
+            _ => self
+                .client
+                .fail(request, &format!("unsupported request '{command}'")),
+        }
+        true
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<(), NovaError> {
+        let Some(program) = arguments["program"].as_str() else {
+            return Err(NovaError::File {
+                msg: "launch needs the path of the program".to_string(),
+            });
+        };
+        self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+
+        let mut core = NovaCore::new(program)?;
+        core.configure(&self.options)?;
+        core.process()?;
+        // stdin carries the protocol, the program gets no input
+        let (stdout, stderr) = (stream::buffer(), stream::buffer());
+        core.vm.state.streams = Streams {
+            stdout: stream::writer(stdout.clone()),
+            stderr: stream::writer(stderr.clone()),
+            stdin: stream::reader(io::empty()),
+        };
+        let debugger = vm::debugger::new(core.compiler.global.items.clone());
+        let mut session = Session {
+            core,
+            debugger,
+            finished: false,
+            stdout,
+            stderr,
+        };
+        for (path, lines) in self.pending.drain(..) {
+            for line in lines {
+                let _ = session
+                    .debugger
+                    .add_breakpoint(&session.core.vm, &path, line);
+            }
+        }
+        self.session = Some(session);
+        Ok(())
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let path = arguments["source"]["path"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let lines = arguments["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .map(|line| line as usize)
+                    .collect::<Vec<usize>>()
+            })
+            .unwrap_or_default();
+
+        let Some(session) = &mut self.session else {
+            self.pending.retain(|(pending, _)| pending != &path);
+            self.pending.push((path, lines.clone()));
+            let breakpoints = lines
+                .iter()
+                .map(|line| json!({ "verified": false, "line": line }))
+                .collect::<Vec<Value>>();
+            return json!({ "breakpoints": breakpoints });
+        };
+
+        // every request replaces all breakpoints of the source
+        session
+            .debugger
+            .breakpoints
+            .retain(|breakpoint| breakpoint.filepath != path);
+        let breakpoints = lines
+            .iter()
+            .map(
+                |line| match session.debugger.add_breakpoint(&session.core.vm, &path, *line) {
+                    Ok(_) => json!({ "verified": true, "line": line }),
+                    Err(error) => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": error.message(),
+                    }),
+                },
+            )
+            .collect::<Vec<Value>>();
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self) -> Value {
+        let Some(session) = &self.session else {
+            return json!({ "stackFrames": [], "totalFrames": 0 });
+        };
+        let frames = session
+            .debugger
+            .frames(&session.core.vm)
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| match &frame.position {
+                Some(position) => json!({
+                    "id": id,
+                    "name": frame.name,
+                    "source": {
+                        "name": std::path::Path::new(&position.filepath)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        "path": position.filepath,
+                    },
+                    "line": position.line,
+                    "column": position.row.max(1),
+                }),
+                None => json!({
+                    "id": id,
+                    "name": frame.name,
+                    "line": 0,
+                    "column": 0,
+                }),
+            })
+            .collect::<Vec<Value>>();
+        json!({ "totalFrames": frames.len(), "stackFrames": frames })
+    }
+
+    fn variables(&mut self, reference: i64) -> Value {
+        let Some(session) = &mut self.session else {
+            return json!({ "variables": [] });
+        };
+        let vm = &mut session.core.vm;
+        let values = if reference == GLOBALS_REFERENCE {
+            // functions live in globals too, they are not worth showing
+            session
+                .debugger
+                .globals(vm)
+                .into_iter()
+                .filter(|(_, value)| !matches!(value, VmData::Function(_) | VmData::Closure(_)))
+                .collect()
+        } else {
+            let frames = session.debugger.frames(vm);
+            match frames.get((reference - FRAMES_REFERENCE) as usize) {
+                Some(frame) => session.debugger.locals(vm, frame),
+                None => vec![],
+            }
+        };
+        let variables = values
+            .into_iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": vm.describe(value),
+                    "variablesReference": 0,
+                })
+            })
+            .collect::<Vec<Value>>();
+        json!({ "variables": variables })
+    }
+
+    // runs until the debugger wants to stop or the program ends, the
+    // instruction execution was paused on is only checked on entry
+    fn resume(&mut self, mode: Mode, check_first: bool) {
+        let Dap {
+            client, session, ..
+        } = self;
+        let Some(session) = session else {
+            return;
+        };
+        if session.finished {
+            return;
+        }
+        session.debugger.mode = mode;
+        let vm = &mut session.core.vm;
+        let mut check = check_first;
+        loop {
+            if check && session.debugger.should_stop(vm) {
+                let reason = if session.debugger.at_breakpoint(vm) {
+                    "breakpoint"
+                } else if check_first {
+                    "entry"
+                } else {
+                    "step"
+                };
+                client.event(
+                    "stopped",
+                    json!({ "reason": reason, "threadId": THREAD_ID }),
+                );
+                return;
+            }
+            check = true;
+
+            let result = vm.checkpoint().and_then(|_| vm.step());
+            for (category, buffer) in [("stdout", &session.stdout), ("stderr", &session.stderr)] {
+                let output = buffer.take();
+                if !output.is_empty() {
+                    client.event("output", json!({ "category": category, "output": output }));
+                }
+            }
+            let exit_code = match result {
+                Ok(true) => continue,
+                Ok(false) => 0,
+                // exit called from inside a native unwinds as an error
+                Err(_) if vm.exited => 0,
+                Err(error) => {
+                    let output = match error.position() {
+                        Some(position) => format!(
+                            "Runtime Error in {}:{}:{}: {}\n",
+                            position.filepath,
+                            position.line,
+                            position.row,
+                            error.message()
+                        ),
+                        None => format!("Runtime Error: {}\n", error.message()),
+                    };
+                    client.event("output", json!({ "category": "stderr", "output": output }));
+                    1
+                }
+            };
+            session.finished = true;
+            client.event("exited", json!({ "exitCode": exit_code }));
+            client.event("terminated", json!({}));
+            return;
+        }
+    }
+}
+
+impl Client {
+    fn respond(&mut self, request: &Value, body: Value) {
+        let message = json!({
+            "seq": self.next_seq(),
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+            "body": body,
+        });
+        write_message(&message);
+    }
+
+    fn fail(&mut self, request: &Value, error: &str) {
+        let message = json!({
+            "seq": self.next_seq(),
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": error,
+        });
+        write_message(&message);
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        let message = json!({
+            "seq": self.next_seq(),
+            "type": "event",
+            "event": event,
+            "body": body,
+        });
+        write_message(&message);
+    }
+
+    fn next_seq(&mut self) -> i64 {
+        let seq = self.seq;
+        self.seq += 1;
+        seq
+    }
+}
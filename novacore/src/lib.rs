@@ -1,14 +1,46 @@
 use assembler::Assembler;
 use common::{
-    error::NovaError,
+    bytecode::{self, Bytecode},
+    error::{MessageFormat, NovaError},
     nodes::SymbolKind,
+    resolver::ModuleResolver,
     ttype::{generate_unique_string, TType},
 };
 use compiler::Compiler;
 use lexer::Lexer;
 use optimizer::Optimizer;
 use parser::Parser;
-use vm::{state::State, Vm};
+use std::{
+    any::Any,
+    collections::HashMap,
+    io::{BufRead, Write},
+    sync::Arc,
+};
+use vm::{
+    bind::{Arguments, Marshal, Native},
+    capability::{self, Capability, Permissions},
+    state::{State, VmData},
+    stream,
+    value::Value,
+    CallBack, Limits, Vm,
+};
+
+pub mod dap;
+pub mod lsp;
+pub mod plugin;
+mod protocol;
+pub mod repl;
+
+// the command line flags that shape how a program is compiled and run,
+// the repl and the debug adapter apply them to every program they start
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub optimization_level: usize,
+    // None leaves every capability granted
+    pub permissions: Option<Permissions>,
+    pub limits: Limits,
+    pub plugins: Vec<String>,
+}
 
 #[derive(Debug)]
 pub struct NovaCore {
@@ -16,22 +48,184 @@ pub struct NovaCore {
     lexer: Lexer,
     parser: Parser,
     compiler: Compiler,
-    _optimizer: Optimizer,
+    optimizer: Optimizer,
     assembler: Assembler,
     vm: Vm,
+    message_format: MessageFormat,
+    plugins: Arc<plugin::Plugins>,
+    // the capability each native needs before it may run
+    capabilities: HashMap<String, Capability>,
 }
 
 impl NovaCore {
     pub fn new(filepath: &str) -> Result<NovaCore, NovaError> {
-        Ok(NovaCore {
+        Ok(NovaCore::with_lexer(filepath, Lexer::new(filepath)?))
+    }
+
+    fn with_lexer(filepath: &str, lexer: Lexer) -> NovaCore {
+        let plugins = Arc::new(plugin::new());
+        let mut parser = parser::new(filepath);
+        parser.plugins = plugins.clone();
+        NovaCore {
             filepath: filepath.to_string(),
-            lexer: Lexer::new(filepath)?,
-            parser: parser::new(filepath),
+            lexer,
+            parser,
             compiler: compiler::new(),
-            _optimizer: optimizer::new(),
+            optimizer: optimizer::new(),
             assembler: assembler::new_empty(),
             vm: vm::new(),
-        })
+            message_format: MessageFormat::Human,
+            plugins,
+            capabilities: capability::builtins(),
+        }
+    }
+
+    // compiles source that does not live on disk, name is what errors and
+    // relative imports refer to
+    pub fn from_source(name: &str, source: &str) -> NovaCore {
+        NovaCore::with_lexer(name, Lexer::from_source(name, source))
+    }
+
+    pub fn repl() -> NovaCore {
+        NovaCore::with_lexer("repl", Lexer::default())
+    }
+
+    // plugins the program was built with have to be loaded again
+    pub fn from_bytecode(filepath: &str, options: &Options) -> Result<NovaCore, NovaError> {
+        let bytes = match std::fs::read(filepath) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                return Err(NovaError::File {
+                    msg: format!(" '{filepath}' is not a valid filepath"),
+                })
+            }
+        };
+        let bytecode = Bytecode::from_bytes(&bytes)?;
+        let mut novacore = NovaCore::with_lexer(filepath, Lexer::default());
+        novacore.initnova();
+        novacore.configure(options)?;
+        novacore.link(&bytecode.natives)?;
+        novacore.vm.runtime_errors_table = bytecode.runtime_error_table;
+        novacore.vm.debug_info = bytecode.debug_info;
+        novacore.vm.state.program = bytecode.program;
+        novacore.compiler.global.items = bytecode.globals;
+        Ok(novacore)
+    }
+
+    // rebuilds the native table in the order the bytecode expects,
+    // every name must be registered by the host
+    fn link(&mut self, natives: &[String]) -> Result<(), NovaError> {
+        let mut native_functions = vec![];
+        for native in natives.iter() {
+            match self.compiler.native_functions.get_index(native.to_string()) {
+                Some(index) => native_functions.push(self.vm.native_functions[index].clone()),
+                None => {
+                    return Err(NovaError::Runtime {
+                        msg: format!("native function '{native}' is not registered by the host"),
+                    })
+                }
+            }
+        }
+        self.vm.native_functions = native_functions;
+        Ok(())
+    }
+
+    // the permissions are set before the plugins load, so they decide
+    // which plugins may load
+    pub fn configure(&mut self, options: &Options) -> Result<(), NovaError> {
+        self.set_optimization_level(options.optimization_level);
+        if let Some(permissions) = &options.permissions {
+            self.set_permissions(permissions.clone());
+        }
+        self.set_limits(options.limits);
+        for plugin in options.plugins.iter() {
+            self.load_plugin(plugin)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_optimization_level(&mut self, level: usize) {
+        self.optimizer.level = level;
+    }
+
+    // imports are read through the resolver, by default they come from disk
+    pub fn set_resolver(&mut self, resolver: impl ModuleResolver + 'static) {
+        self.parser.resolver = Arc::new(resolver);
+    }
+
+    // json output is for tools, the progress of check is left out
+    pub fn set_message_format(&mut self, format: MessageFormat) {
+        self.message_format = format;
+    }
+
+    fn progress(&self, text: String) {
+        if self.message_format == MessageFormat::Human {
+            println!("{text}");
+        }
+    }
+
+    // hands data to the natives, they read it with State::context
+    pub fn set_context(&mut self, context: impl Any + Send + Sync) {
+        self.vm.state.context = Some(Arc::new(context));
+    }
+
+    // program output, error output and input go to the process streams
+    // unless the host plugs in its own, vm::stream::buffer captures output
+    pub fn set_stdout(&mut self, stdout: impl Write + Send + 'static) {
+        self.vm.state.streams.stdout = stream::writer(stdout);
+    }
+
+    pub fn set_stderr(&mut self, stderr: impl Write + Send + 'static) {
+        self.vm.state.streams.stderr = stream::writer(stderr);
+    }
+
+    pub fn set_stdin(&mut self, stdin: impl BufRead + Send + 'static) {
+        self.vm.state.streams.stdin = stream::reader(stdin);
+    }
+
+    pub fn set_permissions(&mut self, permissions: Permissions) {
+        self.vm.state.permissions = permissions;
+        self.share_permissions();
+    }
+
+    // execution stops with a limit error once the program goes over one
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.vm.limits = limits;
+    }
+
+    // takes every capability away, allow hands them back one at a time
+    pub fn sandbox(&mut self) {
+        self.vm.state.permissions = capability::none();
+        self.share_permissions();
+    }
+
+    pub fn allow(&mut self, capability: Capability) {
+        self.vm.state.permissions.allow(capability);
+        self.share_permissions();
+    }
+
+    // fs-read, fs-write and plugin can be granted for a single directory
+    pub fn allow_path(&mut self, capability: Capability, directory: &str) {
+        self.vm.state.permissions.allow_path(capability, directory);
+        self.share_permissions();
+    }
+
+    pub fn deny(&mut self, capability: Capability) {
+        self.vm.state.permissions.deny(capability);
+        self.share_permissions();
+    }
+
+    // plugins are opened while parsing too, long before the vm runs
+    fn share_permissions(&mut self) {
+        self.plugins
+            .set_permissions(self.vm.state.permissions.clone());
+    }
+
+    // a native registered after this needs the capability to run, this is
+    // how host and plugin natives join the permission model
+    pub fn restrict(&mut self, function_id: &str, capability: Capability) {
+        self.capabilities
+            .insert(function_id.to_string(), capability);
     }
 
     pub fn add_function(
@@ -39,8 +233,77 @@ impl NovaCore {
         function_id: &str,
         function_type: TType,
         function_kind: SymbolKind,
-        function_pointer: fn(&mut State) -> Result<(), NovaError>,
+        function: impl Fn(&mut State) -> Result<(), NovaError> + Send + Sync + 'static,
+    ) {
+        self.register(
+            function_id,
+            function_type,
+            function_kind,
+            Arc::new(move |vm: &mut Vm| function(&mut vm.state)),
+        );
+    }
+
+    // like add_function, but the native gets the whole vm so it can call
+    // the functions and closures it was passed with Vm::call
+    pub fn add_vm_function(
+        &mut self,
+        function_id: &str,
+        function_type: TType,
+        function_kind: SymbolKind,
+        function: impl Fn(&mut Vm) -> Result<(), NovaError> + Send + Sync + 'static,
+    ) {
+        self.register(
+            function_id,
+            function_type,
+            function_kind,
+            Arc::new(function),
+        );
+    }
+
+    // loads a native plugin library, its functions are called as module::function
+    pub fn load_plugin(&mut self, path: &str) -> Result<(), NovaError> {
+        self.plugins.open(std::path::Path::new(path))?;
+        self.register_plugins();
+        Ok(())
+    }
+
+    // natives of the plugins opened since the last call, by load_plugin
+    // or by an `import native` while parsing
+    fn register_plugins(&mut self) {
+        for ((function_id, function_type, function_kind), function) in self.plugins.take() {
+            if let Some((module, _)) = function_id.split_once("::") {
+                self.parser.modules.insert(module.to_string());
+            }
+            self.register(&function_id, function_type, function_kind, function);
+        }
+    }
+
+    // registers a native made with vm::native! or vm::bind::function,
+    // its type comes from the rust signature
+    pub fn add_native(&mut self, function_id: &str, function_kind: SymbolKind, native: Native) {
+        self.register(function_id, native.ttype, function_kind, native.function);
+    }
+
+    fn register(
+        &mut self,
+        function_id: &str,
+        function_type: TType,
+        function_kind: SymbolKind,
+        function_pointer: CallBack,
     ) {
+        let function_pointer = match self.capabilities.get(function_id) {
+            Some(capability) => {
+                let arity = match &function_type {
+                    TType::Function { parameters, .. } => parameters
+                        .iter()
+                        .filter(|parameter| **parameter != TType::None)
+                        .count(),
+                    _ => 0,
+                };
+                capability::guard(*capability, function_id, arity, function_pointer)
+            }
+            None => function_pointer,
+        };
         match function_kind {
             SymbolKind::Function => {
                 let compiler_id = {
@@ -66,7 +329,7 @@ impl NovaCore {
                     .insert(function_id.to_string(), function_type.clone());
                 self.vm
                     .native_functions
-                    .insert(self.vm.native_functions.len(), function_pointer);
+                    .insert(self.vm.native_functions.len(), function_pointer.clone());
             }
             _ => {
                 self.parser.environment.insert_symbol(
@@ -90,253 +353,201 @@ impl NovaCore {
 
         self.parser.modules.insert("terminal".to_string());
         self.parser.modules.insert("Cast".to_string());
+        self.parser.modules.insert("gc".to_string());
     }
 
     fn initnova(&mut self) {
         // add printf function
-        self.add_function(
+        self.add_native(
             "printf",
-            TType::Function {
-                parameters: vec![
-                    TType::String,
-                    TType::List {
-                        inner: Box::new(TType::String),
-                    },
-                ],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::Function,
-            native::io::printf,
+            native::io::printf(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::args",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::List {
-                        inner: Box::new(TType::String),
-                    }),
-                }),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::retrieve_command_line_args,
+            native::terminal::retrieve_command_line_args(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::hideCursor",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::hide_cursor,
+            native::terminal::hide_cursor(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::showCursor",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::show_cursor,
+            native::terminal::show_cursor(),
         );
-        self.add_function(
+        self.add_native(
             "Cast::int",
-            TType::Function {
-                parameters: vec![TType::Generic {
-                    name: "a".to_string(),
-                }],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::Int),
-                }),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::str::to_int,
+            native::str::to_int(),
         );
-        self.add_function(
+        self.add_native(
             "Cast::string",
-            TType::Function {
-                parameters: vec![TType::Generic {
-                    name: "a".to_string(),
-                }],
-                return_type: Box::new(TType::String),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::str::to_string,
+            native::str::to_string(),
         );
-        self.add_function(
+        self.add_native(
             "Cast::float",
-            TType::Function {
-                parameters: vec![TType::Any],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::Float),
-                }),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::float::int_to_float,
+            native::float::int_to_float(),
         );
-        self.add_function(
+        self.add_native(
             "List::len",
-            TType::Function {
-                parameters: vec![TType::List {
-                    inner: Box::new(TType::Generic {
-                        name: "a".to_string(),
-                    }),
-                }],
-                return_type: Box::new(TType::Int),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::list::len,
+            native::list::len(),
         );
-        self.add_function(
+        self.add_native(
             "sleep",
-            TType::Function {
-                parameters: vec![TType::Int],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::time::sleep,
+            native::time::sleep(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::rawmode",
-            TType::Function {
-                parameters: vec![TType::Bool],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::rawmode,
+            native::terminal::rawmode(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::getch",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::Char),
-                }),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::getch,
+            native::terminal::getch(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::rawread",
-            TType::Function {
-                parameters: vec![TType::Int],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::Char),
-                }),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::rawread,
+            native::terminal::rawread(),
+        );
+        self.add_native(
+            "eprint",
+            common::nodes::SymbolKind::GenericFunction,
+            native::io::eprint(),
+        );
+        self.add_native(
+            "eprintln",
+            common::nodes::SymbolKind::GenericFunction,
+            native::io::eprintln(),
         );
-        self.add_function(
+        self.add_native(
             "readln",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::String),
-            },
             common::nodes::SymbolKind::GenericFunction,
-            native::io::read_line,
+            native::io::read_line(),
         );
-        self.add_function(
+        self.add_native(
             "terminal::clearScreen",
-            TType::Function {
-                parameters: vec![TType::None],
-                return_type: Box::new(TType::Void),
-            },
             common::nodes::SymbolKind::Function,
-            native::terminal::clear_screen,
+            native::terminal::clear_screen(),
         );
-        self.add_function(
+        self.add_native(
             "List::push",
+            common::nodes::SymbolKind::GenericFunction,
+            native::list::push(),
+        );
+        self.add_native(
+            "List::pop",
+            common::nodes::SymbolKind::GenericFunction,
+            native::list::pop(),
+        );
+        // natives that call back into nova
+        let a = TType::Generic {
+            name: "a".to_string(),
+        };
+        let b = TType::Generic {
+            name: "b".to_string(),
+        };
+        self.add_vm_function(
+            "List::map",
             TType::Function {
                 parameters: vec![
                     TType::List {
-                        inner: Box::new(TType::Generic {
-                            name: "a".to_string(),
-                        }),
+                        inner: Box::new(a.clone()),
                     },
-                    TType::Generic {
-                        name: "a".to_string(),
+                    TType::Function {
+                        parameters: vec![a.clone()],
+                        return_type: Box::new(b.clone()),
                     },
                 ],
-                return_type: Box::new(TType::Void),
+                return_type: Box::new(TType::List { inner: Box::new(b) }),
             },
             common::nodes::SymbolKind::GenericFunction,
-            native::list::push,
+            native::list::map,
         );
-        self.add_function(
-            "List::pop",
+        self.add_vm_function(
+            "List::sortBy",
             TType::Function {
-                parameters: vec![TType::List {
-                    inner: Box::new(TType::Generic {
-                        name: "a".to_string(),
-                    }),
-                }],
-                return_type: Box::new(TType::Option {
-                    inner: Box::new(TType::Generic {
-                        name: "a".to_string(),
-                    }),
-                }),
+                parameters: vec![
+                    TType::List {
+                        inner: Box::new(a.clone()),
+                    },
+                    TType::Function {
+                        parameters: vec![a.clone(), a.clone()],
+                        return_type: Box::new(TType::Bool),
+                    },
+                ],
+                return_type: Box::new(TType::List { inner: Box::new(a) }),
             },
             common::nodes::SymbolKind::GenericFunction,
-            native::list::pop,
+            native::list::sort_by,
         );
-        self.add_function(
+        self.add_native(
             "random",
-            TType::Function {
-                parameters: vec![TType::Int, TType::Int],
-                return_type: Box::new(TType::Int),
-            },
             common::nodes::SymbolKind::Function,
-            native::rand::random_int,
+            native::rand::random_int(),
         );
-        self.add_function(
+        self.add_native(
             "String::len",
-            TType::Function {
-                parameters: vec![TType::String],
-                return_type: Box::new(TType::Int),
-            },
             common::nodes::SymbolKind::Function,
-            native::str::strlen,
+            native::str::strlen(),
         );
-        self.add_function(
+        self.add_native(
             "String::chars",
-            TType::Function {
-                parameters: vec![TType::String],
-                return_type: Box::new(TType::List {
-                    inner: Box::new(TType::Char),
-                }),
-            },
             common::nodes::SymbolKind::Function,
-            native::str::str_to_chars,
+            native::str::str_to_chars(),
         );
-        self.add_function(
+        self.add_native(
             "List::string",
-            TType::Function {
-                parameters: vec![TType::List {
-                    inner: Box::new(TType::Char),
-                }],
-                return_type: Box::new(TType::String),
-            },
             common::nodes::SymbolKind::Function,
-            native::str::chars_to_str,
+            native::str::chars_to_str(),
         );
-        self.add_function(
+        self.add_native(
             "chr",
-            TType::Function {
-                parameters: vec![TType::Int],
-                return_type: Box::new(TType::Char),
-            },
             common::nodes::SymbolKind::Function,
-            native::char::int_to_char,
+            native::char::int_to_char(),
         );
-        self.add_function(
+        self.add_native(
             "readFile",
-            TType::Function {
-                parameters: vec![TType::String],
-                return_type: Box::new(TType::String),
-            },
             common::nodes::SymbolKind::Function,
-            native::io::read_file,
+            native::io::read_file(),
+        );
+        self.add_native(
+            "writeFile",
+            common::nodes::SymbolKind::Function,
+            native::io::write_file(),
+        );
+        self.add_native(
+            "gc::collect",
+            common::nodes::SymbolKind::Function,
+            native::gc::collect(),
+        );
+        self.add_native(
+            "gc::count",
+            common::nodes::SymbolKind::Function,
+            native::gc::count(),
+        );
+        self.add_native(
+            "gc::freed",
+            common::nodes::SymbolKind::Function,
+            native::gc::freed(),
+        );
+        self.add_native(
+            "gc::heapSize",
+            common::nodes::SymbolKind::Function,
+            native::gc::heap_size(),
+        );
+        self.add_native(
+            "gc::threshold",
+            common::nodes::SymbolKind::Function,
+            native::gc::threshold(),
         );
     }
 
@@ -345,51 +556,199 @@ impl NovaCore {
         let tokenlist = self.lexer.tokenize()?;
         self.parser.input = tokenlist;
         self.parser.parse()?;
+        self.register_plugins();
         let ast = self.parser.ast.clone();
         let filepath = self.filepath.clone();
         self.compiler.init();
         let asm = self
             .compiler
             .compile_program(ast.clone(), filepath, true, true, false)?;
-        self.assembler.input = asm;
+        self.assembler.input = self.optimizer.optimize(asm);
         self.assembler.assemble();
         self.vm.runtime_errors_table = self.assembler.runtime_error_table.clone();
+        self.vm.debug_info = self.assembler.debug_info.clone();
         self.vm.state.program = self.assembler.output.clone();
         Ok(())
     }
 
+    pub fn build(mut self, output: &str) -> Result<(), NovaError> {
+        self.process()?;
+        let bytecode = bytecode::new(
+            self.vm.state.program,
+            self.vm.runtime_errors_table,
+            self.compiler.native_functions.items,
+            self.compiler.global.items,
+            self.vm.debug_info,
+        );
+        if std::fs::write(output, bytecode.to_bytes()?).is_err() {
+            return Err(NovaError::File {
+                msg: format!(" could not write to '{output}'"),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn exec(mut self) -> Result<(), NovaError> {
+        self.vm.run()?;
+        Ok(())
+    }
+
     pub fn run(mut self) -> Result<(), NovaError> {
         self.process()?;
         self.vm.run()?;
         Ok(())
     }
 
-    pub fn check(mut self) -> Result<(), NovaError> {
+    // runs the top level of the program once so its functions can be called
+    pub fn load(&mut self) -> Result<(), NovaError> {
+        if self.vm.state.program.is_empty() {
+            self.process()?;
+        }
+        self.vm.run()
+    }
+
+    // calls a top level function, overloads are told apart by their
+    // parameter types the same way the compiler names them
+    pub fn call(
+        &mut self,
+        function_id: &str,
+        parameters: &[TType],
+        arguments: &[Value],
+    ) -> Result<Value, NovaError> {
+        let callee = self.function(function_id, parameters)?;
+        if arguments.len() != parameters.len() {
+            return Err(NovaError::Runtime {
+                msg: format!(
+                    "'{function_id}' takes {} arguments but {} were given",
+                    parameters.len(),
+                    arguments.len()
+                ),
+            });
+        }
+        for (argument, ttype) in arguments.iter().zip(parameters.iter()) {
+            if !argument.fits(ttype) {
+                return Err(NovaError::Runtime {
+                    msg: format!(
+                        "argument {:?} of '{function_id}' is not a {}",
+                        argument,
+                        ttype.to_string()
+                    ),
+                });
+            }
+        }
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.vm.state.from_value(argument))
+            .collect::<Vec<_>>();
+        match self.vm.call(callee, &arguments)? {
+            Some(result) => self.vm.state.to_value(result),
+            None => Ok(Value::None),
+        }
+    }
+
+    // like call, but the signature comes from the rust types of the arguments
+    // and the result is converted to R
+    pub fn invoke<A: Arguments, R: Marshal>(
+        &mut self,
+        function_id: &str,
+        arguments: A,
+    ) -> Result<R, NovaError> {
+        let callee = self.function(function_id, &A::ttypes())?;
+        let arguments = arguments.into_vm(&mut self.vm.state);
+        let result = self.vm.call(callee, &arguments)?;
+        R::from_vm(&self.vm.state, result.unwrap_or(VmData::None))
+    }
+
+    // the global slot a top level function was stored in
+    fn function(&self, function_id: &str, parameters: &[TType]) -> Result<VmData, NovaError> {
+        let signature = if parameters.is_empty() {
+            vec![TType::None]
+        } else {
+            parameters.to_vec()
+        };
+        let compiler_id = generate_unique_string(function_id, &signature);
+        let Some(index) = self
+            .compiler
+            .global
+            .get_index(compiler_id.clone())
+            .or_else(|| self.compiler.global.get_index(function_id.to_string()))
+        else {
+            return Err(NovaError::Runtime {
+                msg: format!("no function '{function_id}' with parameters ({})", {
+                    parameters
+                        .iter()
+                        .map(|ttype| ttype.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                }),
+            });
+        };
+        match self.vm.state.stack.get(index) {
+            Some(callee) => Ok(*callee),
+            None => Err(NovaError::Runtime {
+                msg: "the program has to be loaded before calling into it".to_string(),
+            }),
+        }
+    }
+
+    // unlike the other commands every parsing error is returned, not just the first
+    pub fn check(mut self) -> Result<(), Vec<NovaError>> {
         let start = std::time::Instant::now();
         self.initnova();
-        println!("OK | Initialize time: {}ms", start.elapsed().as_millis());
+        self.progress(format!(
+            "OK | Initialize time: {}ms",
+            start.elapsed().as_millis()
+        ));
 
-        let tokenlist = self.lexer.tokenize()?;
-        self.lexer.check();
-        println!("OK | Lexing time: {}ms", start.elapsed().as_millis());
+        let tokenlist = self.lexer.tokenize().map_err(|error| vec![error])?;
+        if self.message_format == MessageFormat::Human {
+            self.lexer.check();
+        }
+        self.progress(format!(
+            "OK | Lexing time: {}ms",
+            start.elapsed().as_millis()
+        ));
 
         self.parser.input = tokenlist;
-        self.parser.parse()?;
-        println!(
+        let parsed = self.parser.parse();
+        for warning in self.parser.warnings.iter() {
+            warning.report(self.message_format);
+        }
+        if parsed.is_err() {
+            return Err(self.parser.errors);
+        }
+        self.register_plugins();
+        self.progress(format!(
             "OK | Parsing + Typechecking time: {}ms",
             start.elapsed().as_millis()
-        );
+        ));
 
-        let ast = self.parser.ast;
+        let ast = self.parser.ast.clone();
         self.compiler.init();
         let asm = self
             .compiler
-            .compile_program(ast, self.filepath, true, true, false)?;
-        println!("OK | Compile time: {}ms", start.elapsed().as_millis());
+            .compile_program(ast, self.filepath.clone(), true, true, false)
+            .map_err(|error| vec![error])?;
+        self.progress(format!(
+            "OK | Compile time: {}ms",
+            start.elapsed().as_millis()
+        ));
+
+        let asm = self.optimizer.optimize(asm);
+        if self.optimizer.level > 0 {
+            self.progress(format!(
+                "OK | Optimization time: {}ms, {} optimizations applied",
+                start.elapsed().as_millis(),
+                self.optimizer.optimizations
+            ));
+        }
 
         self.assembler.input = asm;
         self.assembler.assemble();
-        println!("OK | Assembler time: {}ms", start.elapsed().as_millis());
+        self.progress(format!(
+            "OK | Assembler time: {}ms",
+            start.elapsed().as_millis()
+        ));
 
         self.vm.runtime_errors_table = self.assembler.runtime_error_table.clone();
         self.vm.state.program = self.assembler.output;
@@ -398,7 +757,8 @@ impl NovaCore {
 
     pub fn run_debug(mut self) -> Result<(), NovaError> {
         self.process()?;
-        self.vm.run_debug()?;
+        let mut debugger = vm::debugger::new(self.compiler.global.items.clone());
+        debugger.run(&mut self.vm)?;
         Ok(())
     }
 
@@ -411,6 +771,7 @@ impl NovaCore {
         let asm = self
             .compiler
             .compile_program(ast, self.filepath, true, true, false)?;
+        let asm = self.optimizer.optimize(asm);
         let mut dis = disassembler::new();
         dis.dis_asm(asm);
         Ok(())
@@ -0,0 +1,380 @@
+use std::{collections::HashMap, io, sync::Arc};
+
+use common::{
+    error::NovaError, fileposition::FilePosition, resolver::NoPlugins,
+    ttype::generate_unique_string,
+};
+use lexer::Lexer;
+use parser::Parser;
+use serde_json::{json, Value};
+
+use crate::{
+    protocol::{read_message, write_message},
+    NovaCore,
+};
+
+// a language server over stdin and stdout, every open document is
+// parsed and typechecked again whenever it changes
+pub struct Lsp {
+    documents: HashMap<String, String>,
+    analysis: HashMap<String, Parser>,
+}
+
+pub fn new() -> Lsp {
+    Lsp {
+        documents: HashMap::default(),
+        analysis: HashMap::default(),
+    }
+}
+
+impl Lsp {
+    pub fn run(&mut self) -> Result<(), NovaError> {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+        while let Some(message) = read_message(&mut input)? {
+            if !self.handle(&message) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // returns false once the client asks the server to exit
+    fn handle(&mut self, message: &Value) -> bool {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+                "serverInfo": { "name": "nova" },
+            }),
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.update(uri, text);
+                return true;
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                // the server asks for full document sync
+                if let Some(change) = params["contentChanges"].as_array().and_then(|c| c.last()) {
+                    let text = change["text"].as_str().unwrap_or_default();
+                    self.update(uri, text);
+                }
+                return true;
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                self.analysis.remove(uri);
+                return true;
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            "shutdown" => Value::Null,
+            "exit" => return false,
+            _ => {
+                // notifications we do not care about need no answer
+                if message["id"].is_null() {
+                    return true;
+                }
+                write_message(&json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "error": { "code": -32601, "message": format!("unsupported method '{method}'") },
+                }));
+                return true;
+            }
+        };
+        write_message(&json!({
+            "jsonrpc": "2.0",
+            "id": message["id"],
+            "result": result,
+        }));
+        true
+    }
+
+    fn update(&mut self, uri: &str, text: &str) {
+        let filepath = uri_to_path(uri);
+        let (parser, errors) = analyze(&filepath, text);
+        let diagnostics = errors
+            .iter()
+            .map(|error| diagnostic(error, &filepath, text))
+            .collect::<Vec<Value>>();
+        write_message(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }));
+        self.documents.insert(uri.to_string(), text.to_string());
+        self.analysis.insert(uri.to_string(), parser);
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let (uri, line, column) = text_position(params);
+        let Some(parser) = self.analysis.get(&uri) else {
+            return Value::Null;
+        };
+        match parser.symbols.at(&uri_to_path(&uri), line, column) {
+            Some(reference) => json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": format!("```nova\n{}: {}\n```", reference.name, reference.ttype.to_string()),
+                },
+                "range": range(&reference.position, reference.name.chars().count()),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let (uri, line, column) = text_position(params);
+        let Some(parser) = self.analysis.get(&uri) else {
+            return Value::Null;
+        };
+        let reference = parser.symbols.at(&uri_to_path(&uri), line, column);
+        match reference.and_then(|reference| reference.definition.as_ref().map(|d| (reference, d))) {
+            Some((reference, definition)) => json!({
+                "uri": path_to_uri(&definition.filepath),
+                "range": range(definition, reference.name.chars().count()),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    // fields and extends methods of the value in front of the dot
+    fn completion(&self, params: &Value) -> Value {
+        let (uri, line, column) = text_position(params);
+        let (Some(parser), Some(text)) = (self.analysis.get(&uri), self.documents.get(&uri)) else {
+            return json!([]);
+        };
+        let Some(source) = text.lines().nth(line - 1) else {
+            return json!([]);
+        };
+        let before = source.chars().take(column - 1).collect::<String>();
+        let partial = before
+            .chars()
+            .rev()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .count();
+        let receiver_end = before.chars().count() - partial;
+        if receiver_end == 0 || before.chars().nth(receiver_end - 1) != Some('.') {
+            return json!([]);
+        }
+        // the dot sits at column receiver_end, the receiver ends right before it
+        let Some(receiver) = parser
+            .symbols
+            .ending_at(&uri_to_path(&uri), line, receiver_end)
+        else {
+            return json!([]);
+        };
+
+        let ttype = &receiver.ttype;
+        let mut items = vec![];
+        if let Some(fields) = ttype
+            .custom_to_string()
+            .and_then(|name| parser.environment.custom_types.get(&name))
+        {
+            for (name, field_type) in fields {
+                // every struct carries its name in a hidden field
+                if name == "type" {
+                    continue;
+                }
+                items.push(json!({
+                    "label": name,
+                    "kind": 5,
+                    "detail": field_type.to_string(),
+                }));
+            }
+        }
+        if let Some(prefix) = ttype.extends_prefix() {
+            let prefix = format!("{prefix}::");
+            let mut methods = parser
+                .environment
+                .values
+                .iter()
+                .flat_map(|scope| scope.iter())
+                .filter_map(|(id, symbol)| {
+                    let name = id.strip_prefix(&prefix)?;
+                    // overloadable functions are stored under their signature
+                    let name = match &symbol.ttype {
+                        common::ttype::TType::Function { parameters, .. } => name
+                            .strip_suffix(&generate_unique_string("", parameters))
+                            .unwrap_or(name),
+                        _ => name,
+                    };
+                    Some((name.to_string(), symbol.ttype.to_string()))
+                })
+                .collect::<Vec<(String, String)>>();
+            methods.sort();
+            methods.dedup();
+            for (name, detail) in methods {
+                items.push(json!({
+                    "label": name,
+                    "kind": 2,
+                    "detail": detail,
+                }));
+            }
+        }
+        json!(items)
+    }
+}
+
+// parses and typechecks the source with the same natives a program would see.
+// opening a document must not run code, so native imports are not loaded
+fn analyze(filepath: &str, source: &str) -> (Parser, Vec<NovaError>) {
+    let mut core = NovaCore::repl();
+    core.filepath = filepath.to_string();
+    core.parser = parser::new(filepath);
+    core.parser.plugins = Arc::new(NoPlugins);
+    core.initnova();
+    match Lexer::from_source(filepath, source).tokenize() {
+        Ok(tokens) => {
+            core.parser.input = tokens;
+            let _ = core.parser.parse();
+            let mut errors = core.parser.errors.clone();
+            errors.extend(core.parser.warnings.iter().cloned());
+            (core.parser, errors)
+        }
+        Err(error) => (core.parser, vec![error]),
+    }
+}
+
+fn diagnostic(error: &NovaError, filepath: &str, text: &str) -> Value {
+    let message = error.message();
+    let severity = match error {
+        NovaError::Warning { .. } => 2,
+        _ => 1,
+    };
+    match error.position() {
+        Some(position) if position.filepath == filepath => json!({
+            "range": span(position, text),
+            "severity": severity,
+            "source": "nova",
+            "message": message,
+            "relatedInformation": related(error),
+        }),
+        // errors inside imported files are reported at the top of the document
+        Some(position) => json!({
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+            "severity": severity,
+            "source": "nova",
+            "message": format!("{}:{}:{}: {}", position.filepath, position.line, position.row, message),
+        }),
+        None => json!({
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+            "severity": severity,
+            "source": "nova",
+            "message": message,
+        }),
+    }
+}
+
+// the secondary locations of an error, like where a symbol was declared
+fn related(error: &NovaError) -> Value {
+    match error {
+        NovaError::Parsing {
+            extra: Some(extra), ..
+        } => json!(extra
+            .iter()
+            .map(|(message, position)| json!({
+                "location": {
+                    "uri": path_to_uri(&position.filepath),
+                    "range": range(position, position.end_row.saturating_sub(position.row).max(1)),
+                },
+                "message": message,
+            }))
+            .collect::<Vec<Value>>()),
+        _ => json!([]),
+    }
+}
+
+// the range an error covers, positions without an end fall back to
+// underlining the word they point at
+fn span(position: &FilePosition, text: &str) -> Value {
+    if (position.end_line, position.end_row) > (position.line, position.row) {
+        return json!({
+            "start": { "line": position.line.saturating_sub(1), "character": position.row.saturating_sub(1) },
+            "end": { "line": position.end_line.saturating_sub(1), "character": position.end_row.saturating_sub(1) },
+        });
+    }
+    let length = text
+        .lines()
+        .nth(position.line.saturating_sub(1))
+        .map(|line| {
+            line.chars()
+                .skip(position.row.saturating_sub(1))
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .count()
+        })
+        .unwrap_or_default()
+        .max(1);
+    range(position, length)
+}
+
+// lsp positions count from zero, file positions count from one
+fn range(position: &FilePosition, length: usize) -> Value {
+    let line = position.line.saturating_sub(1);
+    let character = position.row.saturating_sub(1);
+    json!({
+        "start": { "line": line, "character": character },
+        "end": { "line": line, "character": character + length },
+    })
+}
+
+fn text_position(params: &Value) -> (String, usize, usize) {
+    let uri = params["textDocument"]["uri"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+    let character = params["position"]["character"]
+        .as_u64()
+        .unwrap_or_default() as usize;
+    (uri, line + 1, character + 1)
+}
+
+fn uri_to_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut bytes = vec![];
+    let mut index = 0;
+    while index < path.len() {
+        let escaped = path
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) if path[index] == b'%' => {
+                bytes.push(byte);
+                index += 3;
+            }
+            _ => {
+                bytes.push(path[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+// every byte but letters, digits, `-._~` and the slashes is escaped
+fn path_to_uri(path: &str) -> String {
+    let path = std::fs::canonicalize(path)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(path.to_string());
+    let mut uri = "file://".to_string();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
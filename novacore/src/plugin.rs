@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    ffi::{c_char, CStr},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use common::{error::NovaError, nodes::SymbolKind, resolver::PluginLoader, ttype::TType};
+use libloading::Library;
+use vm::{
+    bind::{Module, PLUGIN_BUILD, PLUGIN_LAYOUT, PLUGIN_VERSION},
+    capability::{Capability, Permissions},
+    CallBack, Vm,
+};
+
+pub type Signature = (String, TType, SymbolKind);
+
+// plugin libraries loaded so far. the host and every parser share it, so a
+// library named by --plugin and by `import native` is only loaded once
+#[derive(Default)]
+pub struct Plugins {
+    loaded: Mutex<HashMap<PathBuf, Vec<Signature>>>,
+    // natives the host has not registered yet
+    pending: Mutex<Vec<(Signature, CallBack)>>,
+    // a copy of the permissions of the program, loading a library needs
+    // the plugin capability
+    permissions: Mutex<Permissions>,
+}
+
+pub fn new() -> Plugins {
+    Plugins::default()
+}
+
+impl std::fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let loaded = self.loaded.lock().unwrap();
+        f.debug_list().entries(loaded.keys()).finish()
+    }
+}
+
+impl Plugins {
+    // loads the library at path and returns the signatures of its natives,
+    // the natives themselves wait until the host takes them
+    pub fn open(&self, path: &Path) -> Result<Vec<Signature>, NovaError> {
+        let shown = path.display().to_string();
+        self.permissions
+            .lock()
+            .unwrap()
+            .check(Capability::Plugin, &shown, Some(&shown))?;
+        let path = match std::fs::canonicalize(path) {
+            Ok(path) => path,
+            Err(_) => {
+                return Err(NovaError::File {
+                    msg: format!(" '{}' is not a valid plugin path", path.display()),
+                })
+            }
+        };
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(signatures) = loaded.get(&path) {
+            return Ok(signatures.clone());
+        }
+
+        let (library, module) = load_library(&path)?;
+        let mut signatures = vec![];
+        let mut pending = self.pending.lock().unwrap();
+        for (name, function_kind, native) in module.functions {
+            let signature = (
+                format!("{}::{name}", module.name),
+                native.ttype,
+                function_kind,
+            );
+            let loaded = Loaded {
+                function: native.function,
+                _library: library.clone(),
+            };
+            let function: CallBack = Arc::new(move |vm: &mut Vm| loaded.call(vm));
+            signatures.push(signature.clone());
+            pending.push((signature, function));
+        }
+        loaded.insert(path, signatures.clone());
+        Ok(signatures)
+    }
+
+    pub fn take(&self) -> Vec<(Signature, CallBack)> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    pub fn set_permissions(&self, permissions: Permissions) {
+        *self.permissions.lock().unwrap() = permissions;
+    }
+}
+
+impl PluginLoader for Plugins {
+    fn load(&self, importer: &str, name: &str) -> Result<Vec<Signature>, NovaError> {
+        self.open(&find(importer, name)?)
+    }
+}
+
+// a native from a plugin keeps its library loaded, the fields drop in
+// order so the closure goes before the code it points into
+struct Loaded {
+    function: CallBack,
+    _library: Arc<Library>,
+}
+
+impl Loaded {
+    fn call(&self, vm: &mut Vm) -> Result<(), NovaError> {
+        (self.function)(vm)
+    }
+}
+
+fn load_library(path: &Path) -> Result<(Arc<Library>, Module), NovaError> {
+    let invalid = |reason: String| NovaError::File {
+        msg: format!(" '{}' {reason}", path.display()),
+    };
+    // plugins are trusted like the host itself, loading one runs its initializers
+    let library = match unsafe { Library::new(path) } {
+        Ok(library) => library,
+        Err(error) => return Err(invalid(format!("could not be loaded: {error}"))),
+    };
+    let version = match unsafe { library.get::<*const u32>(b"NOVA_PLUGIN_VERSION\0") } {
+        Ok(version) => unsafe { **version },
+        Err(_) => return Err(invalid("is not a nova plugin".to_string())),
+    };
+    if version != PLUGIN_VERSION {
+        return Err(invalid(format!(
+            "was built for plugin version {version}, this is version {PLUGIN_VERSION}"
+        )));
+    }
+    // the build is read through the c abi, it has to match before any rust
+    // type is shared with the library
+    let build =
+        match unsafe { library.get::<extern "C" fn() -> *const c_char>(b"nova_plugin_build\0") } {
+            Ok(build) => unsafe { CStr::from_ptr(build()) }
+                .to_string_lossy()
+                .to_string(),
+            Err(_) => return Err(invalid("was built by an older nova".to_string())),
+        };
+    let host = PLUGIN_BUILD.trim_end_matches('\0');
+    if build != host {
+        return Err(invalid(format!(
+            "was built by {build}, this is {host}, rebuild the plugin"
+        )));
+    }
+    let layout = match unsafe { library.get::<*const u64>(b"NOVA_PLUGIN_LAYOUT\0") } {
+        Ok(layout) => unsafe { **layout },
+        Err(_) => return Err(invalid("was built by an older nova".to_string())),
+    };
+    if layout != PLUGIN_LAYOUT {
+        return Err(invalid(
+            "does not agree with this nova on the layout of its types, rebuild the plugin"
+                .to_string(),
+        ));
+    }
+    let register = match unsafe { library.get::<fn() -> Module>(b"nova_plugin\0") } {
+        Ok(register) => *register,
+        Err(_) => return Err(invalid("is not a nova plugin".to_string())),
+    };
+    let module = register();
+    Ok((Arc::new(library), module))
+}
+
+// a plugin is a library path relative to the importing file, or a bare name
+// looked up next to it and then in the directories of NOVA_PLUGIN_PATH.
+// like module names, a plugin name may not climb out of those directories
+fn find(importer: &str, name: &str) -> Result<PathBuf, NovaError> {
+    let path = Path::new(name);
+    if path.is_absolute() || path.components().any(|part| part == Component::ParentDir) {
+        return Err(NovaError::File {
+            msg: format!(" '{name}' is outside of the plugin directories"),
+        });
+    }
+    let directory = Path::new(importer).parent().unwrap_or(Path::new(""));
+    let file = format!("{DLL_PREFIX}{name}{DLL_SUFFIX}");
+    let mut candidates = vec![directory.join(name), directory.join(&file)];
+    if let Some(paths) = std::env::var_os("NOVA_PLUGIN_PATH") {
+        for path in std::env::split_paths(&paths) {
+            candidates.push(path.join(&file));
+        }
+    }
+    match candidates.into_iter().find(|candidate| candidate.is_file()) {
+        Some(path) => Ok(path),
+        None => Err(NovaError::File {
+            msg: format!(" no plugin named '{name}' next to '{importer}' or in NOVA_PLUGIN_PATH"),
+        }),
+    }
+}
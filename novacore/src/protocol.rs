@@ -0,0 +1,46 @@
+use std::io::{self, BufRead, Write};
+
+use common::error::NovaError;
+use serde_json::Value;
+
+// the base protocol shared by the debug adapter and the language server,
+// every message is a json body preceded by a content length header
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<Value>, NovaError> {
+    let invalid = |msg: &str| NovaError::File {
+        msg: format!("invalid message: {msg}"),
+    };
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        match input.read_line(&mut header) {
+            Ok(0) => return Ok(None),
+            Ok(_) => {}
+            Err(_) => return Err(invalid("could not read from stdin")),
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(length) = length else {
+        return Err(invalid("missing Content-Length header"));
+    };
+    let mut body = vec![0; length];
+    if input.read_exact(&mut body).is_err() {
+        return Err(invalid("message ended early"));
+    }
+    match serde_json::from_slice(&body) {
+        Ok(message) => Ok(Some(message)),
+        Err(error) => Err(invalid(&error.to_string())),
+    }
+}
+
+pub fn write_message(message: &Value) {
+    let body = message.to_string();
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body).expect("");
+    stdout.flush().expect("");
+}
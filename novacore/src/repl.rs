@@ -0,0 +1,263 @@
+use std::io::{self, BufRead, Write};
+
+use common::{
+    code::Asm,
+    error::NovaError,
+    nodes::{Ast, Statement},
+    shape,
+    ttype::TType,
+};
+use lexer::Lexer;
+use vm::state::VmData;
+
+use crate::{NovaCore, Options};
+
+// the repl keeps one parser environment, compiler tables, assembler labels
+// and vm state alive. every chunk is assembled on its own after the program
+// so far and the vm resumes from where the new chunk starts.
+pub struct Repl {
+    core: NovaCore,
+    // the trailing ret of the program, the next chunk is written over it
+    start: usize,
+    globals: usize,
+    locals: usize,
+}
+
+pub fn new(options: &Options) -> Result<Repl, NovaError> {
+    let mut core = NovaCore::repl();
+    core.configure(options)?;
+    core.initnova();
+    core.compiler.init();
+    let mut repl = Repl {
+        start: 0,
+        globals: 0,
+        locals: 0,
+        core,
+    };
+    // run the builtin wrappers so their globals are in place
+    let init = repl.core.compiler.asm.clone();
+    repl.core.compiler.asm.clear();
+    repl.execute(init)?;
+    Ok(repl)
+}
+
+impl Repl {
+    pub fn run(&mut self) -> Result<(), NovaError> {
+        println!("Nova 0.1.0 repl, type :quit to exit");
+        let stdin = io::stdin();
+        let mut source = String::new();
+        loop {
+            if source.is_empty() {
+                print!(">> ");
+            } else {
+                print!(".. ");
+            }
+            io::stdout().flush().expect("");
+
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(_) => {
+                    return Err(NovaError::File {
+                        msg: "Could not read from stdin".to_string(),
+                    })
+                }
+            }
+            if source.is_empty() {
+                match line.trim() {
+                    "" => continue,
+                    ":quit" | ":q" => break,
+                    _ => {}
+                }
+            }
+            source.push_str(&line);
+            // keep reading until every bracket is closed
+            if open_brackets(&source) > 0 {
+                continue;
+            }
+            match self.eval(&source) {
+                Ok(Some((value, ttype))) => println!("{value} : {}", ttype.to_string()),
+                Ok(None) => {}
+                Err(error) => error.show(),
+            }
+            source.clear();
+            if self.core.vm.exited {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // typechecks, compiles and executes a chunk of source, returning the
+    // value of a trailing expression shown the way print shows it, along
+    // with its type
+    pub fn eval(&mut self, source: &str) -> Result<Option<(String, TType)>, NovaError> {
+        let parser = self.core.parser.clone();
+        let compiler = self.core.compiler.clone();
+
+        let result = self.eval_chunk(source);
+        if result.is_err() {
+            self.core.parser = parser;
+            self.core.compiler = compiler;
+        }
+        result
+    }
+
+    fn eval_chunk(&mut self, source: &str) -> Result<Option<(String, TType)>, NovaError> {
+        let tokens = Lexer::from_source(&self.core.filepath, source).tokenize()?;
+        self.core.parser.input = tokens;
+        self.core.parser.parse_repl()?;
+        self.core.register_plugins();
+        let program = self.core.parser.ast.program.clone();
+
+        let result_type = match program.last() {
+            Some(Statement::Expression { ttype, .. }) if ttype != &TType::Void => {
+                Some(ttype.clone())
+            }
+            _ => None,
+        };
+
+        self.core.compiler.asm.clear();
+        let mut asm = self.core.compiler.compile_program(
+            Ast { program },
+            self.core.filepath.clone(),
+            false,
+            false,
+            false,
+        )?;
+        self.core.compiler.asm.clear();
+        asm.pop();
+        // top level code of the repl has no function, traces name it <repl>
+        asm.insert(
+            0,
+            Asm::SCOPE(
+                "<repl>".to_string(),
+                self.core.compiler.variables.items.clone(),
+            ),
+        );
+
+        // leave the value of the trailing expression on the stack
+        if result_type.is_some() {
+            if let Some(Asm::POP) = asm.last() {
+                asm.pop();
+            }
+        }
+        let asm = self.core.optimizer.optimize(asm);
+
+        self.execute(asm)?;
+
+        let Some(ttype) = result_type.filter(|_| !self.core.vm.exited) else {
+            return Ok(None);
+        };
+        // the value stays on the stack while it is shown, a show function
+        // can run the collector
+        let Some(value) = self.core.vm.state.stack.last().copied() else {
+            return Ok(None);
+        };
+        let text = self.core.vm.show(value, &shape::shape(&ttype));
+        self.core.vm.state.stack.pop();
+        Ok(Some((text?, ttype)))
+    }
+
+    fn execute(&mut self, mut asm: Vec<Asm>) -> Result<(), NovaError> {
+        let start = self.start;
+        asm.push(Asm::RET(false));
+        let assembler = &mut self.core.assembler;
+        assembler.output = std::mem::take(&mut self.core.vm.state.program);
+        assembler.output.truncate(start);
+        assembler.append(asm);
+        self.core
+            .vm
+            .runtime_errors_table
+            .extend(std::mem::take(&mut assembler.runtime_error_table));
+        self.core
+            .vm
+            .debug_info
+            .extend(std::mem::take(&mut assembler.debug_info));
+        let program = std::mem::take(&mut assembler.output);
+
+        // the program is not part of the copy an error goes back to
+        let (state, globals, locals) = (self.core.vm.state.clone(), self.globals, self.locals);
+        self.reserve_stack();
+        self.core.vm.state.program = program;
+        self.core.vm.state.goto(start);
+        self.core.vm.state.callstack.clear();
+
+        if let Err(error) = self.core.vm.run() {
+            let program = std::mem::take(&mut self.core.vm.state.program);
+            self.core.vm.state = state;
+            self.core.vm.state.program = program;
+            self.globals = globals;
+            self.locals = locals;
+            // the chunk is written over by the next one, its labels and
+            // positions must not be found again
+            self.core.assembler.forget(start);
+            self.core
+                .vm
+                .runtime_errors_table
+                .retain(|address, _| *address < start);
+            self.core.vm.debug_info.truncate(start);
+            return Err(error);
+        }
+
+        // the trailing ret is overwritten by the next chunk
+        self.start = self.core.vm.state.program.len() - 2;
+        Ok(())
+    }
+
+    // grows the global and local sections of the stack to fit any
+    // new symbols the compiler has handed out since the last chunk
+    fn reserve_stack(&mut self) {
+        let globals = self.core.compiler.global.len();
+        let locals = self.core.compiler.variables.len();
+        let state = &mut self.core.vm.state;
+
+        state.stack.truncate(self.globals + self.locals);
+        for _ in self.globals..globals {
+            state.stack.insert(self.globals, VmData::None);
+        }
+        for _ in self.locals..locals {
+            state.stack.push(VmData::None);
+        }
+        state.offset = globals;
+        state.window = vec![0, globals];
+
+        self.globals = globals;
+        self.locals = locals;
+    }
+}
+
+// string and char literals and comments are skipped, their brackets
+// do not count
+fn open_brackets(source: &str) -> i64 {
+    let mut depth = 0;
+    // the quote of the literal being skipped
+    let mut quote = None;
+    let mut escaped = false;
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(open) = quote {
+            match c {
+                '\\' if !escaped => {
+                    escaped = true;
+                    continue;
+                }
+                // a char literal never runs past the end of its line
+                '\n' if open == '\'' => quote = None,
+                c if c == open && !escaped => quote = None,
+                _ => {}
+            }
+            escaped = false;
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
@@ -0,0 +1,112 @@
+use common::ttype::TType;
+use novacore::NovaCore;
+use vm::value::Value;
+
+const PROGRAM: &str = "module called
+
+struct P { x: Int, name: String }
+
+fn add(a: Int, b: Int) -> Int { return a + b }
+fn add(a: Float, b: Float) -> Float { return a + b }
+fn make(x: Int) -> P { return P { x: x, name: \"made\" } }
+fn bump(p: P) -> P { return P { x: p.x + 1, name: p.name } }
+fn greet() { println(\"hi\") }
+";
+
+fn loaded() -> NovaCore {
+    let mut novacore = NovaCore::from_source("called.nv", PROGRAM);
+    novacore.set_stdout(vm::stream::buffer());
+    novacore.load().expect("the program should load");
+    novacore
+}
+
+fn p() -> TType {
+    TType::Custom {
+        name: "P".to_string(),
+        type_params: vec![],
+    }
+}
+
+#[test]
+fn overloads_are_told_apart() {
+    let mut novacore = loaded();
+    let int = novacore.call(
+        "add",
+        &[TType::Int, TType::Int],
+        &[Value::Int(1), Value::Int(2)],
+    );
+    assert_eq!(int.unwrap(), Value::Int(3));
+    let float = novacore.call(
+        "add",
+        &[TType::Float, TType::Float],
+        &[Value::Float(1.0), Value::Float(0.5)],
+    );
+    assert_eq!(float.unwrap(), Value::Float(1.5));
+    assert_eq!(novacore.call("greet", &[], &[]).unwrap(), Value::None);
+}
+
+#[test]
+fn signature_mismatch() {
+    let mut novacore = loaded();
+    let wrong_type = novacore.call(
+        "add",
+        &[TType::Int, TType::Int],
+        &[Value::Int(1), Value::String("2".to_string())],
+    );
+    assert!(wrong_type.unwrap_err().message().contains("is not a Int"));
+    let wrong_arity = novacore.call("add", &[TType::Int, TType::Int], &[Value::Int(1)]);
+    assert!(wrong_arity
+        .unwrap_err()
+        .message()
+        .contains("takes 2 arguments but 1 were given"));
+    // a list is not a struct, even with the right fields
+    let untagged = novacore.call(
+        "bump",
+        &[p()],
+        &[Value::List(vec![
+            Value::Int(1),
+            Value::String("a".to_string()),
+        ])],
+    );
+    assert!(untagged.is_err());
+}
+
+#[test]
+fn missing_function() {
+    let mut novacore = loaded();
+    let missing = novacore.call("subtract", &[TType::Int], &[Value::Int(1)]);
+    assert_eq!(
+        missing.unwrap_err().message(),
+        "no function 'subtract' with parameters (Int)"
+    );
+    let unloaded = NovaCore::from_source("called.nv", PROGRAM).call("greet", &[], &[]);
+    assert!(unloaded.is_err());
+}
+
+#[test]
+fn structs_cross_by_name() {
+    let mut novacore = loaded();
+    let made = novacore.call("make", &[TType::Int], &[Value::Int(4)]);
+    assert_eq!(
+        made.unwrap(),
+        Value::Struct {
+            name: "P".to_string(),
+            fields: vec![Value::Int(4), Value::String("made".to_string())],
+        }
+    );
+    let bumped = novacore.call(
+        "bump",
+        &[p()],
+        &[Value::Struct {
+            name: "P".to_string(),
+            fields: vec![Value::Int(1), Value::String("a".to_string())],
+        }],
+    );
+    assert_eq!(
+        bumped.unwrap(),
+        Value::Struct {
+            name: "P".to_string(),
+            fields: vec![Value::Int(2), Value::String("a".to_string())],
+        }
+    );
+}
@@ -0,0 +1,66 @@
+use novacore::NovaCore;
+
+fn errors(body: &str) -> Vec<String> {
+    let source = format!("module checked\n\n{body}\n");
+    match NovaCore::from_source("checked.nv", &source).check() {
+        Ok(()) => vec![],
+        Err(errors) => errors.iter().map(|error| error.message()).collect(),
+    }
+}
+
+#[test]
+fn failed_lets_are_still_declared() {
+    // a, b and c each fail once, only d is really unknown
+    let found = errors(
+        "let a: Int = \"x\"
+println(a + 1)
+let b = 1 + true
+println(b)
+let c: String = 5
+println(c + \"!\")
+println(d)
+let ok = a + 2",
+    );
+    assert_eq!(found.len(), 4, "{found:?}");
+    assert_eq!(
+        found
+            .iter()
+            .filter(|msg| msg.contains("Not a valid symbol"))
+            .count(),
+        1,
+        "{found:?}"
+    );
+}
+
+// each broken statement leaves a bracket open, the errors after it are
+// still found
+#[test]
+fn unclosed_brackets_do_not_hide_later_errors() {
+    for broken in [
+        "let a = (1 +",
+        "fn f(x: Int -> Int {\n    return x\n}",
+        "struct S { a: Int",
+        "let xs = [1, 2",
+        "fn g() -> Int {\n    let d = [1, 2\n    return d[0]\n}",
+    ] {
+        let found = errors(&format!(
+            "{broken}\nlet n: Int = \"x\"\nprintln(later)\nif true {{\n    let m: Bool = 1\n}}"
+        ));
+        assert_eq!(found.len(), 4, "{broken}: {found:?}");
+        assert_eq!(found[1], "Cannot assign String to Int", "{broken}");
+        assert_eq!(found[2], "E2 Not a valid symbol: later", "{broken}");
+        assert_eq!(found[3], "Cannot assign Int to Bool", "{broken}");
+    }
+}
+
+#[test]
+fn statement_keywords_end_a_broken_statement() {
+    // the let on the next line is not swallowed by the open bracket
+    let found = errors("let a = (1 +\nlet b: Int = \"x\"\nfn f() -> Int {\n    return 1 + true\n}");
+    assert_eq!(found.len(), 3, "{found:?}");
+    assert_eq!(found[1], "Cannot assign String to Int");
+    assert!(
+        found[2].contains("cannot apply operation Addition"),
+        "{found:?}"
+    );
+}
@@ -0,0 +1,31 @@
+// helpers shared by the integration tests, every test file is a crate of
+// its own and uses only some of them
+#![allow(dead_code)]
+
+use ::common::error::NovaError;
+use novacore::NovaCore;
+use vm::stream;
+
+// a core for the program `module name`, the prelude holds the declarations
+// a test file shares and comes before the body. output goes to the buffer
+pub fn core(name: &str, prelude: &str, body: &str) -> (NovaCore, stream::Buffer) {
+    let source = format!("module {name}\n\n{prelude}{body}\n");
+    let buffer = stream::buffer();
+    let mut novacore = NovaCore::from_source(&format!("{name}.nv"), &source);
+    novacore.set_stdout(buffer.clone());
+    (novacore, buffer)
+}
+
+// runs a program, returning what it printed along with how the run ended
+pub fn run(name: &str, prelude: &str, body: &str) -> (String, Result<(), NovaError>) {
+    let (novacore, buffer) = core(name, prelude, body);
+    let result = novacore.run();
+    (buffer.take(), result)
+}
+
+// the output of a program that has to run
+pub fn output(name: &str, prelude: &str, body: &str) -> String {
+    let (output, result) = run(name, prelude, body);
+    result.expect("the program should run");
+    output
+}
@@ -0,0 +1,39 @@
+mod common;
+
+fn output(body: &str) -> String {
+    common::output(
+        "collected",
+        "struct Box { items: [String], label: String }\n\n",
+        body,
+    )
+}
+
+#[test]
+fn garbage_is_freed_and_live_values_survive() {
+    let shown = output(
+        "// reachable only through a closure and a struct
+let kept = [\"a\", \"b\", \"c\"]
+let join = fn() -> String { return kept[0] + kept[1] + kept[2] }
+let box = Box { items: [\"x\", \"y\"], label: \"box\" }
+
+let before = gc::freed()
+let count = gc::count()
+for i <- 0; i < 1000; i += 1 {
+    let garbage = [Cast::string(i), \"tmp\"]
+    let other = Box { items: garbage, label: \"garbage\" }
+}
+let used = gc::heapSize()
+gc::collect()
+println(gc::count() > count)
+println(gc::freed() - before >= 4000)
+println(gc::heapSize() < used)
+
+// collecting again with nothing new to free keeps the live values
+let freed = gc::freed()
+gc::collect()
+println(gc::freed() - freed < 10)
+println(join())
+println(box.items[0] + box.items[1] + box.label)",
+    );
+    assert_eq!(shown, "true\ntrue\ntrue\ntrue\nabc\nxybox\n");
+}
@@ -0,0 +1,79 @@
+use common::resolver;
+use novacore::NovaCore;
+use vm::stream;
+
+const MAIN: &str = "module main
+
+import \"lib/shapes.nv\"
+
+println(area(Square { side: 3 }))
+println(twice(4))
+";
+
+const SHAPES: &str = "module shapes
+
+import \"../util.nv\"
+
+struct Square { side: Int }
+
+fn area(s: Square) -> Int { return s.side * s.side }
+";
+
+const UTIL: &str = "module util
+
+fn twice(x: Int) -> Int { return x * 2 }
+";
+
+#[test]
+fn imports_from_memory() {
+    let mut modules = resolver::memory();
+    modules.insert("lib/shapes.nv", SHAPES);
+    modules.insert_bytes("./util.nv", UTIL.as_bytes()).unwrap();
+    let buffer = stream::buffer();
+    let mut novacore = NovaCore::from_source("main.nv", MAIN);
+    novacore.set_resolver(modules);
+    novacore.set_stdout(buffer.clone());
+    novacore.run().expect("the program should run");
+    assert_eq!(buffer.take(), "9\n8\n");
+}
+
+#[test]
+fn unknown_module_in_memory() {
+    let mut novacore = NovaCore::from_source("main.nv", MAIN);
+    novacore.set_resolver(resolver::memory());
+    let error = novacore.run().unwrap_err();
+    assert!(
+        error
+            .message()
+            .contains("'lib/shapes.nv' is not a known module"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn directory_stays_inside_its_root() {
+    let root = std::env::temp_dir().join(format!("nova-imports-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    std::fs::write(root.join("lib/shapes.nv"), SHAPES).unwrap();
+    std::fs::write(root.join("util.nv"), UTIL).unwrap();
+
+    let buffer = stream::buffer();
+    let mut novacore = NovaCore::from_source("main.nv", MAIN);
+    novacore.set_resolver(resolver::directory(&root.to_string_lossy()));
+    novacore.set_stdout(buffer.clone());
+    novacore.run().expect("the program should run");
+    assert_eq!(buffer.take(), "9\n8\n");
+
+    for import in ["\"../util.nv\"", "\"lib/../../util.nv\"", "super.util"] {
+        let source = format!("module main\n\nimport {import}\n");
+        let mut novacore = NovaCore::from_source("main.nv", &source);
+        novacore.set_resolver(resolver::directory(&root.join("lib").to_string_lossy()));
+        let error = novacore.run().unwrap_err();
+        assert!(
+            error
+                .message()
+                .contains("is outside of the module directory"),
+            "{import}: {error:?}"
+        );
+    }
+}
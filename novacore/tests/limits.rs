@@ -0,0 +1,99 @@
+use ::common::error::{Limit, NovaError};
+use vm::Limits;
+
+mod common;
+
+// runs the program with one limit set, it has to stop on that limit
+fn stopped(body: &str, limits: Limits) -> (Limit, String, usize, usize) {
+    let (mut novacore, _) = common::core("limited", "", body);
+    novacore.set_limits(limits);
+    let error = novacore
+        .run()
+        .expect_err("the program should hit its limit");
+    let position = error
+        .position()
+        .cloned()
+        .expect("the error should have a position");
+    assert_eq!(position.filepath, "limited.nv");
+    match error {
+        NovaError::Traced { error, .. } => match *error {
+            NovaError::Limit { limit, msg, .. } => (limit, msg, position.line, position.row),
+            other => panic!("expected a limit error, got {other:?}"),
+        },
+        other => panic!("expected a traced error, got {other:?}"),
+    }
+}
+
+// recursion that never ends, each call of f pushes more values while
+// f calls itself from the return on line 5
+const RECURSION: &str = "fn f(n: Int) -> Int {
+    let m = n + 1
+    return f(m) + 1
+}
+println(f(0))";
+
+#[test]
+fn steps() {
+    let (limit, msg, line, column) = stopped(
+        "let x = 0
+while true {
+    x += 1
+}",
+        Limits {
+            steps: Some(1000),
+            ..Limits::default()
+        },
+    );
+    assert_eq!(limit, Limit::Steps);
+    assert_eq!(msg, "execution stopped after 1000 steps");
+    // the loop condition
+    assert_eq!((line, column), (4, 1));
+}
+
+#[test]
+fn heap() {
+    let (limit, msg, line, column) = stopped(
+        "let xs = []: Int
+while true {
+    xs.push(1)
+}",
+        Limits {
+            heap: Some(5000),
+            ..Limits::default()
+        },
+    );
+    assert_eq!(limit, Limit::Heap);
+    assert_eq!(msg, "out of memory, more than 5000 heap cells in use");
+    assert_eq!((line, column), (5, 5));
+}
+
+#[test]
+fn stack() {
+    let (limit, msg, line, column) = stopped(
+        RECURSION,
+        Limits {
+            stack: Some(500),
+            ..Limits::default()
+        },
+    );
+    assert_eq!(limit, Limit::Stack);
+    assert_eq!(
+        msg,
+        "value stack overflow, more than 500 values on the stack"
+    );
+    assert_eq!((line, column), (4, 5));
+}
+
+#[test]
+fn depth() {
+    let (limit, msg, line, column) = stopped(
+        RECURSION,
+        Limits {
+            depth: Some(100),
+            ..Limits::default()
+        },
+    );
+    assert_eq!(limit, Limit::Depth);
+    assert_eq!(msg, "stack overflow, more than 100 nested calls");
+    assert_eq!((line, column), (5, 5));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+fn output(body: &str) -> String {
+    common::output("lists", "struct P { name: String, age: Int }\n\n", body)
+}
+
+#[test]
+fn map() {
+    let shown = output(
+        "let scale = 3
+println([1, 2, 3].map(fn(x: Int) -> Int { return x * scale }))
+println([1, 2].map(fn(x: Int) -> String { return Cast::string(x) + \"!\" }).len())
+println([]: Int.map(fn(x: Int) -> Int { return x }).len())",
+    );
+    assert_eq!(shown, "[3,6,9]\n2\n0\n");
+}
+
+#[test]
+fn sort_by() {
+    let shown = output(
+        "let xs = [3, 1, 2]
+println(xs.sortBy(fn(a: Int, b: Int) -> Bool { return a < b }))
+println(xs.sortBy(fn(a: Int, b: Int) -> Bool { return a > b }))
+// the original list is left alone
+println(xs)
+// equal keys keep their order
+let people = [P { name: \"a\", age: 2 }, P { name: \"b\", age: 1 }, P { name: \"c\", age: 2 }]
+for p in people.sortBy(fn(x: P, y: P) -> Bool { return x.age < y.age }) {
+    print(p.name)
+}
+println(\"\")",
+    );
+    assert_eq!(shown, "[1,2,3]\n[3,2,1]\n[3,1,2]\nbac\n");
+}
+
+#[test]
+fn sort_by_survives_collections() {
+    // every comparison leaves garbage behind, so the collector runs while the
+    // native holds the list being sorted
+    let shown = output(
+        "let words = []: String
+for i <- 0; i < 200; i += 1 {
+    words.push(Cast::string((i * 37) % 200))
+}
+let sorted = words.sortBy(fn(a: String, b: String) -> Bool {
+    let garbage = []: Int
+    for j <- 0; j < 150; j += 1 {
+        garbage.push(j)
+    }
+    return Cast::int(a).unwrap() < Cast::int(b).unwrap()
+})
+println(gc::count() > 0)
+println(sorted.len())
+print(sorted[0])
+print(sorted[1])
+println(sorted[199])",
+    );
+    assert_eq!(shown, "true\n200\n01199\n");
+}
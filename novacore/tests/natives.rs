@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+
+use ::common::{error::NovaError, nodes::SymbolKind};
+use vm::{bind, state::State, stream};
+
+mod common;
+
+fn core(body: &str) -> (novacore::NovaCore, stream::Buffer) {
+    common::core("natives", "", body)
+}
+
+struct Greeting {
+    text: String,
+}
+
+#[test]
+fn closures_keep_host_state_across_calls() {
+    let seen = Arc::new(Mutex::new(vec![]));
+    let (mut novacore, buffer) = core(
+        "println(record(3))
+println(record(4))
+println(record(5))",
+    );
+    let log = seen.clone();
+    novacore.add_native(
+        "record",
+        SymbolKind::Function,
+        bind::function(move |value: i64| {
+            let mut log = log.lock().unwrap();
+            log.push(value);
+            log.iter().sum::<i64>()
+        }),
+    );
+    novacore.run().unwrap();
+    assert_eq!(buffer.take(), "3\n7\n12\n");
+    assert_eq!(*seen.lock().unwrap(), vec![3, 4, 5]);
+}
+
+fn greet() -> bind::Native {
+    bind::stateful(
+        |state: &mut State, name: String| match state.context::<Greeting>() {
+            Some(greeting) => Ok(format!("{} {name}", greeting.text)),
+            None => Err(NovaError::Runtime {
+                msg: "no greeting was set".to_string(),
+            }),
+        },
+    )
+}
+
+#[test]
+fn natives_read_the_context_of_their_own_core() {
+    let mut outputs = vec![];
+    for text in ["hello", "goodbye"] {
+        let (mut novacore, buffer) = core("println(greet(\"bob\"))");
+        novacore.add_native("greet", SymbolKind::Function, greet());
+        novacore.set_context(Greeting {
+            text: text.to_string(),
+        });
+        novacore.run().unwrap();
+        outputs.push(buffer.take());
+    }
+    assert_eq!(outputs, ["hello bob\n", "goodbye bob\n"]);
+}
+
+#[test]
+fn context_of_another_type_is_not_found() {
+    let (mut novacore, _) = core("println(greet(\"bob\"))");
+    novacore.add_native("greet", SymbolKind::Function, greet());
+    novacore.set_context(42i64);
+    let error = novacore.run().unwrap_err();
+    assert_eq!(error.message(), "no greeting was set");
+}
@@ -0,0 +1,112 @@
+use ::common::error::NovaError;
+
+mod common;
+
+// V overloads every operator, O only orders and W overloads nothing
+const TYPES: &str = "struct V { x: Int }
+struct O { x: Int }
+struct W { x: Int }
+
+fn extends add(a: V, b: V) -> V { return V { x: a.x + b.x } }
+fn extends sub(a: V, b: V) -> V { return V { x: a.x - b.x } }
+fn extends mul(a: V, b: Int) -> V { return V { x: a.x * b } }
+fn extends div(a: V, b: Int) -> V { return V { x: a.x / b } }
+fn extends rem(a: V, b: Int) -> V { return V { x: a.x % b } }
+fn extends index(a: V, i: Int) -> Int { return a.x * 10 + i }
+fn extends eq(a: V, b: V) -> Bool { return a.x % 10 == b.x % 10 }
+fn extends cmp(a: V, b: V) -> Int { return a.x - b.x }
+fn extends cmp(a: O, b: O) -> Int { return b.x - a.x }
+
+";
+
+fn run(body: &str) -> (String, Result<(), NovaError>) {
+    common::run("operators", TYPES, body)
+}
+
+fn output(body: &str) -> String {
+    common::output("operators", TYPES, body)
+}
+
+#[test]
+fn arithmetic() {
+    let shown = output(
+        "let a = V { x: 7 }
+println(a + V { x: 2 })
+println(a - V { x: 2 })
+println(a * 3)
+println(a / 2)
+println(a % 4)
+println(a + a - V { x: 1 } * 2)",
+    );
+    assert_eq!(
+        shown,
+        "V { x: 9 }\nV { x: 5 }\nV { x: 21 }\nV { x: 3 }\nV { x: 3 }\nV { x: 12 }\n"
+    );
+}
+
+#[test]
+fn compound_assignment() {
+    let shown = output(
+        "let c = V { x: 1 }
+c += V { x: 2 }
+println(c)
+c -= V { x: 5 }
+println(c)
+let n = 1
+n += 2
+println(n)",
+    );
+    assert_eq!(shown, "V { x: 3 }\nV { x: -2 }\n3\n");
+}
+
+#[test]
+fn compound_assignment_without_overload() {
+    let (_, result) = run("let w = W { x: 1 }
+w += W { x: 2 }");
+    let error = result.unwrap_err();
+    assert_eq!(error.message(), "Cannot apply AdditionAssignment to W");
+    let (_, result) = run("let w = \"a\"
+w -= \"b\"");
+    assert!(result.is_err());
+}
+
+#[test]
+fn indexing() {
+    assert_eq!(output("println(V { x: 4 }[2])"), "42\n");
+    let (_, result) = run("println(W { x: 4 }[2])");
+    assert_eq!(result.unwrap_err().message(), "Cannot index into W");
+}
+
+#[test]
+fn equality_uses_eq() {
+    // eq only looks at the last digit, cmp would tell these apart
+    let shown = output(
+        "println(V { x: 3 } == V { x: 13 })
+println(V { x: 3 } != V { x: 13 })
+println(V { x: 3 } == V { x: 4 })",
+    );
+    assert_eq!(shown, "true\nfalse\nfalse\n");
+}
+
+#[test]
+fn equality_falls_back_on_cmp() {
+    let shown = output(
+        "println(O { x: 3 } == O { x: 3 })
+println(O { x: 3 } != O { x: 3 })
+println(O { x: 3 } == O { x: 4 })",
+    );
+    assert_eq!(shown, "true\nfalse\nfalse\n");
+}
+
+#[test]
+fn ordering_uses_cmp() {
+    // O orders backwards
+    let shown = output(
+        "println(V { x: 1 } < V { x: 2 })
+println(V { x: 1 } >= V { x: 2 })
+println(O { x: 1 } < O { x: 2 })
+println(O { x: 1 } > O { x: 2 })
+println(O { x: 2 } <= O { x: 2 })",
+    );
+    assert_eq!(shown, "true\nfalse\nfalse\ntrue\ntrue\n");
+}
@@ -0,0 +1,139 @@
+mod common;
+
+// the types are shared by every test
+const TYPES: &str = "struct Person { name: String, age: Int }
+enum Shape { Circle: Float, Square: Int, Dot }
+
+";
+
+fn output(body: &str) -> String {
+    common::output("matched", TYPES, body)
+}
+
+#[test]
+fn literals_and_ranges() {
+    let shown = output(
+        "fn kind(x: Int) -> String {
+    match x {
+        0 -> { return \"zero\" }
+        -3..0 -> { return \"small negative\" }
+        1..=9 -> { return \"digit\" }
+        _ -> { return \"other\" }
+    }
+    return \"\"
+}
+fn letter(c: Char) -> String {
+    match c {
+        'a'..='z' -> { return \"lower\" }
+        _ -> { return \"not lower\" }
+    }
+    return \"\"
+}
+fn greeting(s: String) -> String {
+    match s {
+        \"hi\" -> { return \"short\" }
+        _ -> { return \"long\" }
+    }
+    return \"\"
+}
+println(kind(0))
+println(kind(-1))
+println(kind(-3))
+println(kind(-4))
+println(kind(9))
+println(kind(10))
+println(letter('q'))
+println(letter('Q'))
+println(greeting(\"hi\"))
+println(greeting(\"hello\"))",
+    );
+    assert_eq!(
+        shown,
+        "zero\nsmall negative\nsmall negative\nother\ndigit\nother\nlower\nnot lower\nshort\nlong\n"
+    );
+}
+
+#[test]
+fn guards() {
+    let shown = output(
+        "fn sign(x: ?Int) -> String {
+    match x {
+        Some(n) if n < 0 -> { return \"negative\" }
+        Some(0) -> { return \"zero\" }
+        Some(n) -> { return \"positive\" }
+        None -> { return \"nothing\" }
+    }
+    return \"\"
+}
+println(sign(Some(-5)))
+println(sign(Some(0)))
+println(sign(Some(5)))
+println(sign(?Int))",
+    );
+    assert_eq!(shown, "negative\nzero\npositive\nnothing\n");
+}
+
+#[test]
+fn tuples() {
+    let shown = output(
+        "fn both(t: #(Int, Bool)) -> String {
+    match t {
+        #(0, true) -> { return \"zero and true\" }
+        #(n, true) -> { return Cast::string(n) + \" and true\" }
+        #(_, false) -> { return \"false\" }
+    }
+    return \"\"
+}
+println(both(#(0, true)))
+println(both(#(4, true)))
+println(both(#(4, false)))",
+    );
+    assert_eq!(shown, "zero and true\n4 and true\nfalse\n");
+}
+
+#[test]
+fn structs() {
+    let shown = output(
+        "fn who(p: Person) -> String {
+    match p {
+        Person { name: \"bob\" } -> { return \"bob\" }
+        Person { name, age: 0..18 } -> { return name + \" is young\" }
+        Person { name: n, age } -> { return n + \" is \" + Cast::string(age) }
+    }
+    return \"\"
+}
+println(who(Person { name: \"bob\", age: 3 }))
+println(who(Person { name: \"amy\", age: 12 }))
+println(who(Person { name: \"sam\", age: 40 }))",
+    );
+    assert_eq!(shown, "bob\namy is young\nsam is 40\n");
+}
+
+#[test]
+fn enums_and_nesting() {
+    let shown = output(
+        "fn area(s: ?Shape) -> String {
+    match s {
+        Some(Shape::Circle(r)) -> { return \"circle \" + Cast::string(r) }
+        Some(Square(1)) -> { return \"unit square\" }
+        Some(Square(n)) -> { return \"square \" + Cast::string(n * n) }
+        Some(Dot) -> { return \"dot\" }
+        None -> { return \"no shape\" }
+    }
+    return \"\"
+}
+println(area(Some(Shape::Circle(1.5))))
+println(area(Some(Shape::Square(1))))
+println(area(Some(Shape::Square(3))))
+println(area(Some(Shape::Dot())))
+println(area(?Shape))
+match #(Shape::Square(2), Person { name: \"eve\", age: 7 }) {
+    #(Square(2), Person { age: 7, name }) -> println(name)
+    #(_, _) -> println(\"no\")
+}",
+    );
+    assert_eq!(
+        shown,
+        "circle 1.5\nunit square\nsquare 9\ndot\nno shape\neve\n"
+    );
+}
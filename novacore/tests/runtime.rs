@@ -0,0 +1,81 @@
+use ::common::error::NovaError;
+use vm::capability::Capability;
+
+mod common;
+
+// programs may start processes
+fn run(body: &str) -> (String, Result<(), NovaError>) {
+    let (mut novacore, buffer) = common::core("runtime", "", body);
+    novacore.allow(Capability::Process);
+    let result = novacore.run();
+    (buffer.take(), result)
+}
+
+#[test]
+fn unwrapping_none_is_a_runtime_error() {
+    let (output, result) = run("fn first(x: ?Int) -> Int {
+    return x.unwrap()
+}
+println(first(Some(1)))
+println(first(?Int))
+println(\"unreachable\")");
+    assert_eq!(output, "1\n");
+    match result {
+        Err(NovaError::Traced { error, trace }) => {
+            assert_eq!(error.message(), "tried to unwrap a None value");
+            assert_eq!(trace[0].function, "first");
+            assert_eq!(trace[0].position.as_ref().map(|p| p.line), Some(4));
+        }
+        other => panic!("expected a traced error, got {other:?}"),
+    }
+}
+
+#[test]
+fn trace_names_functions_without_parameters() {
+    let (_, result) = run("fn first(x: ?Int) -> Int {
+    return x.unwrap()
+}
+fn outer() -> Int {
+    return first(?Int)
+}
+println(outer())");
+    match result {
+        Err(NovaError::Traced { trace, .. }) => {
+            let names = trace
+                .iter()
+                .map(|frame| frame.function.as_str())
+                .collect::<Vec<&str>>();
+            assert_eq!(names, ["first", "outer", "main"]);
+        }
+        other => panic!("expected a traced error, got {other:?}"),
+    }
+}
+
+#[test]
+fn exit_ends_the_run() {
+    let (output, result) = run("println(\"before\")
+exit()
+println(\"after\")");
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(output, "before\n");
+}
+
+#[test]
+fn exit_needs_no_capability() {
+    let (mut novacore, buffer) = common::core("runtime", "", "println(1)\nexit()\nprintln(2)");
+    novacore.set_permissions(vm::capability::none());
+    assert!(novacore.run().is_ok());
+    assert_eq!(buffer.take(), "1\n");
+}
+
+#[test]
+fn exit_unwinds_through_natives() {
+    let (output, result) = run("let xs = [1, 2, 3].map(fn(x: Int) -> Int {
+    if x == 2 { exit() }
+    println(x)
+    return x
+})
+println(\"after\")");
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(output, "1\n");
+}
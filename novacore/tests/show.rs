@@ -0,0 +1,122 @@
+mod common;
+
+// struct declarations are shared by every test
+const TYPES: &str = "struct P { x: Int, name: String }
+struct Q { v: Int }
+struct R { v: Int }
+enum Shape { Circle: Float, Dot }
+
+fn extends show(self: Q) -> String { return \"Q!\" }
+fn extends toString(self: R) -> String { return \"R\" + Cast::string(self.v) }
+
+";
+
+fn output(body: &str) -> String {
+    common::output("shown", TYPES, body)
+}
+
+#[test]
+fn concrete_values() {
+    let shown = output(
+        "println(P { x: 1, name: \"a\" })
+println(Shape::Circle(1.5))
+println(Shape::Dot())
+println(#(1, \"a\", 'c'))
+println(Cast::string(P { x: 2, name: \"b\" }))",
+    );
+    assert_eq!(
+        shown,
+        "P { x: 1, name: \"a\" }
+Shape::Circle(1.5)
+Shape::Dot
+#(1, \"a\", 'c')
+P { x: 2, name: \"b\" }
+"
+    );
+}
+
+#[test]
+fn generic_values() {
+    let shown = output(
+        "fn show_one(x: $A) { println(x) }
+fn show_all(xs: [$A]) { println(xs) }
+fn text(x: $A) -> String { return Cast::string(x) }
+show_one(P { x: 1, name: \"a\" })
+show_one(Shape::Circle(2.0))
+show_one(\"plain\")
+show_all([P { x: 1, name: \"a\" }, P { x: 2, name: \"b\" }])
+show_all([\"a\", \"b\"])
+println(text(Shape::Dot()))",
+    );
+    assert_eq!(
+        shown,
+        "P { x: 1, name: \"a\" }
+Shape::Circle(2)
+plain
+[P { x: 1, name: \"a\" },P { x: 2, name: \"b\" }]
+[\"a\",\"b\"]
+Shape::Dot
+"
+    );
+}
+
+#[test]
+fn nested_containers() {
+    let shown = output(
+        "println([[1, 2], [3]])
+println([[P { x: 1, name: \"a\" }], []: P])
+println(#(P { x: 1, name: \"a\" }, [Shape::Dot()]))
+println(Some(P { x: 3, name: \"c\" }))",
+    );
+    assert_eq!(
+        shown,
+        "[[1,2],[3]]
+[[P { x: 1, name: \"a\" }],[]]
+#(P { x: 1, name: \"a\" }, [Shape::Dot])
+P { x: 3, name: \"c\" }
+"
+    );
+}
+
+#[test]
+fn overrides() {
+    let shown = output(
+        "fn show_one(x: $A) { println(x) }
+println(Q { v: 1 })
+println(R { v: 2 })
+println([Q { v: 1 }, Q { v: 2 }])
+show_one(R { v: 3 })
+println(Cast::string(Q { v: 4 }))",
+    );
+    assert_eq!(shown, "Q!\nR2\n[Q!,Q!]\nR3\nQ!\n");
+}
+
+#[test]
+fn strings_are_quoted_inside_every_container() {
+    // the same string shows the same whether its list is generic or not
+    let shown = output(
+        "fn show_all(xs: [$A]) { println(xs) }
+println(\"a\")
+println('c')
+println([\"a\", \"b\"])
+show_all([\"a\", \"b\"])
+println(['c'])
+println([[\"a\"], []: String])
+println(Cast::string([\"a\"]))
+println(#(\"a\", ['c']))
+println(P { x: 1, name: \"a\" })",
+    );
+    assert_eq!(
+        shown,
+        "a
+c
+[\"a\",\"b\"]
+[\"a\",\"b\"]
+['c']
+[[\"a\"],[]]
+[\"a\"]
+#(\"a\", ['c'])
+P { x: 1, name: \"a\" }
+"
+    );
+}
@@ -0,0 +1,151 @@
+use ::common::error::NovaError;
+
+mod common;
+
+// Int and P implement Describe, only P implements Size
+const TRAITS: &str = "struct P { x: Int }
+struct Q { x: Int }
+
+trait Describe {
+    fn describe(self: $Self) -> String
+}
+
+trait Size {
+    fn size(self: $Self) -> Int
+    fn double(self: $Self) -> Int
+}
+
+impl Describe for Int {
+    fn describe(self: Int) -> String {
+        return \"Int \" + Cast::string(self)
+    }
+}
+
+impl Describe for P {
+    fn describe(self: P) -> String {
+        return \"P \" + Cast::string(self.x)
+    }
+}
+
+impl Size for P {
+    fn size(self: P) -> Int {
+        return self.x
+    }
+    fn double(self: P) -> Int {
+        return self.x * 2
+    }
+}
+
+";
+
+fn run(body: &str) -> (String, Result<(), NovaError>) {
+    common::run("traits", TRAITS, body)
+}
+
+fn error(body: &str) -> String {
+    let (_, result) = run(body);
+    result
+        .expect_err("the program should not compile")
+        .message()
+}
+
+#[test]
+fn bound_calls_the_impl_of_the_argument() {
+    let (output, result) = run("fn show(x: $A: Describe) {
+    println(x.describe() + \"!\")
+}
+show(10)
+show(P { x: 2 })");
+    result.expect("the program should run");
+    assert_eq!(output, "Int 10!\nP 2!\n");
+}
+
+#[test]
+fn multiple_bounds() {
+    let (output, result) = run("fn both(x: $A: Describe + Size) -> String {
+    return x.describe() + \" \" + Cast::string(x.size() + x.double())
+}
+// a bounded generic hands its methods on to the next call
+fn twice(x: $B: Describe + Size) -> String {
+    return both(x) + \", \" + both(x)
+}
+println(both(P { x: 3 }))
+println(twice(P { x: 1 }))");
+    result.expect("the program should run");
+    assert_eq!(output, "P 3 9\nP 1 3, P 1 3\n");
+}
+
+#[test]
+fn unmet_bound() {
+    let show = "fn show(x: $A: Describe) {
+    println(x.describe())
+}
+";
+    assert_eq!(
+        error(&format!("{show}show(Q {{ x: 1 }})")),
+        "Q does not implement trait Describe"
+    );
+    assert_eq!(
+        error(&format!("{show}show(\"text\")")),
+        "String does not implement trait Describe"
+    );
+    // Int only meets the first of the two bounds
+    assert_eq!(
+        error(
+            "fn both(x: $A: Describe + Size) {
+    println(x.describe())
+}
+both(1)"
+        ),
+        "Int does not implement trait Size"
+    );
+}
+
+#[test]
+fn impl_missing_a_method() {
+    assert_eq!(
+        error(
+            "impl Size for Q {
+    fn size(self: Q) -> Int {
+        return self.x
+    }
+}"
+        ),
+        "Q does not implement method double of trait Size"
+    );
+}
+
+#[test]
+fn impl_errors() {
+    assert_eq!(
+        error(
+            "impl Size for Q {
+    fn size(self: Q) -> String {
+        return \"\"
+    }
+    fn double(self: Q) -> Int {
+        return 0
+    }
+}"
+        ),
+        "Method size does not match trait Size for Q"
+    );
+    assert_eq!(
+        error(
+            "impl Describe for P {
+    fn describe(self: P) -> String {
+        return \"\"
+    }
+}"
+        ),
+        "P already implements Describe"
+    );
+    assert_eq!(
+        error("impl Missing for P {}"),
+        "Trait Missing does not exist"
+    );
+    assert_eq!(
+        error("fn f(x: $A: Missing) {}"),
+        "Trait Missing does not exist"
+    );
+}
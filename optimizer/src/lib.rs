@@ -1,55 +1,498 @@
+use std::collections::HashMap;
+
 use common::code::Asm;
+
+// a pass takes the whole program and hands back the rewritten one,
+// every rewrite it makes is counted in optimizations
+type Pass = fn(&mut Optimizer, Vec<Asm>) -> Vec<Asm>;
+
+// upper bound on how many times the pass list is rerun
+const MAX_ROUNDS: usize = 16;
+
 #[derive(Debug)]
 pub struct Optimizer {
     pub optimizations: usize,
+    pub level: usize,
 }
 
 pub fn new() -> Optimizer {
-    Optimizer { optimizations: 0 }
+    Optimizer {
+        optimizations: 0,
+        level: 0,
+    }
 }
 
 impl Optimizer {
-    pub fn optimize(&mut self, _input: Vec<Asm>) -> Vec<Asm> {
-        // first pass
-        //self.tail_call(input)
-        todo!()
-    }
-
-    // under construction
-    // fn tail_call(&mut self, input: Vec<Asm>) -> Vec<Asm> {
-    //     let mut output = vec![];
-    //     let mut function_stack = vec![];
-    //     for instruction in input {
-    //         match instruction.clone() {
-    //             Asm::FUNCTION(index) => {
-    //                 function_stack.push((index, name));
-    //                 output.push(instruction);
-    //             }
-    //             Asm::LABEL(label) => {
-    //                 if let Some((index, _)) = function_stack.last() {
-    //                     if label == *index {
-    //                         function_stack.pop();
-    //                     }
-    //                 }
-    //                 output.push(instruction);
-    //             }
-    //             Asm::RET(_) => {
-    //                 if let Some((_, name)) = function_stack.last() {
-    //                     if let Some(Asm::DCALL(dindex)) = output.clone().last() {
-    //                         if &name == &dname {
-    //                             output.pop();
-    //                             output.push(Asm::TCALL(*dindex));
-    //                             self.optimizations += 1;
-    //                         }
-    //                     }
-    //                 }
-    //                 output.push(instruction);
-    //             }
-    //             _ => {
-    //                 output.push(instruction);
-    //             }
-    //         }
-    //     }
-    //  output
-    //}
+    // level 0 leaves the program alone, level 1 runs the local peephole
+    // passes and level 2 adds the passes that rewrite control flow
+    fn passes(&self) -> Vec<Pass> {
+        let mut passes: Vec<Pass> = vec![];
+        if self.level >= 1 {
+            passes.push(Optimizer::constant_fold);
+            passes.push(Optimizer::dup_pop);
+            passes.push(Optimizer::dead_code);
+        }
+        if self.level >= 2 {
+            passes.push(Optimizer::jump_threading);
+            passes.push(Optimizer::tail_call);
+        }
+        passes
+    }
+
+    pub fn optimize(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        let passes = self.passes();
+        let mut output = input;
+        for _ in 0..MAX_ROUNDS {
+            let before = self.optimizations;
+            for pass in passes.iter() {
+                output = pass(self, output);
+            }
+            if before == self.optimizations {
+                break;
+            }
+        }
+        output
+    }
+
+    // folds arithmetic on two constants into a single constant
+    fn constant_fold(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        let mut output: Vec<Asm> = vec![];
+        for instruction in input {
+            let folded = match (&output[..], &instruction) {
+                ([.., Asm::INTEGER(v)], Asm::NEG) => v.checked_neg().map(Asm::INTEGER),
+                ([.., Asm::FLOAT(v)], Asm::NEG) => Some(Asm::FLOAT(-v)),
+                ([.., Asm::INTEGER(v2), Asm::INTEGER(v1)], operation) => {
+                    let (v2, v1) = (*v2, *v1);
+                    match operation {
+                        Asm::IADD => v2.checked_add(v1).map(Asm::INTEGER),
+                        Asm::ISUB => v2.checked_sub(v1).map(Asm::INTEGER),
+                        Asm::IMUL => v2.checked_mul(v1).map(Asm::INTEGER),
+                        Asm::IDIV => v2.checked_div(v1).map(Asm::INTEGER),
+                        Asm::ILSS => Some(Asm::BOOL(v2 < v1)),
+                        Asm::IGTR => Some(Asm::BOOL(v2 > v1)),
+                        _ => None,
+                    }
+                }
+                ([.., Asm::FLOAT(v2), Asm::FLOAT(v1)], operation) => {
+                    let (v2, v1) = (*v2, *v1);
+                    match operation {
+                        Asm::FADD => Some(Asm::FLOAT(v2 + v1)),
+                        Asm::FSUB => Some(Asm::FLOAT(v2 - v1)),
+                        Asm::FMUL => Some(Asm::FLOAT(v2 * v1)),
+                        Asm::FDIV => Some(Asm::FLOAT(v2 / v1)),
+                        Asm::FLSS => Some(Asm::BOOL(v2 < v1)),
+                        Asm::FGTR => Some(Asm::BOOL(v2 > v1)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match folded {
+                Some(constant) => {
+                    // neg only consumes one operand
+                    if let Asm::NEG = instruction {
+                        output.pop();
+                    } else {
+                        output.pop();
+                        output.pop();
+                    }
+                    output.push(constant);
+                    self.optimizations += 1;
+                }
+                None => output.push(instruction),
+            }
+        }
+        output
+    }
+
+    // removes values that are pushed and then immediately thrown away
+    fn dup_pop(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        let mut output: Vec<Asm> = vec![];
+        for instruction in input {
+            if let Asm::POP = instruction {
+                if let Some(
+                    Asm::DUP
+                    | Asm::INTEGER(_)
+                    | Asm::FLOAT(_)
+                    | Asm::BOOL(_)
+                    | Asm::Char(_)
+                    | Asm::STRING(_)
+                    | Asm::NONE
+                    | Asm::GET(_)
+                    | Asm::GETGLOBAL(_),
+                ) = output.last()
+                {
+                    output.pop();
+                    self.optimizations += 1;
+                    continue;
+                }
+            }
+            output.push(instruction);
+        }
+        output
+    }
+
+    // nothing after an unconditional jump or a return runs until the next label
+    fn dead_code(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        let mut output = vec![];
+        let mut reachable = true;
+        for instruction in input {
+            match instruction {
+                Asm::LABEL(_) => {
+                    reachable = true;
+                    output.push(instruction);
+                }
+                Asm::JMP(_) | Asm::BJMP(_) | Asm::RET(_) | Asm::EXIT if reachable => {
+                    reachable = false;
+                    output.push(instruction);
+                }
+                _ if reachable => output.push(instruction),
+                _ => self.optimizations += 1,
+            }
+        }
+        output
+    }
+
+    // a jump to a label that only jumps again goes straight to the final label
+    fn jump_threading(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        let mut labels = HashMap::new();
+        for (position, instruction) in input.iter().enumerate() {
+            if let Asm::LABEL(label) = instruction {
+                labels.insert(*label, position);
+            }
+        }
+
+        // follow the chain of jumps that start right at a label
+        let resolve = |label: u64| -> u64 {
+            let mut target = label;
+            let mut seen = vec![target];
+            while let Some(position) = labels.get(&target) {
+                let next = input[*position..]
+                    .iter()
+                    .find(|instruction| {
+                        !matches!(instruction, Asm::LABEL(_) | Asm::LINE(_) | Asm::SCOPE(..))
+                    });
+                match next {
+                    Some(Asm::JMP(next) | Asm::BJMP(next)) if !seen.contains(next) => {
+                        target = *next;
+                        seen.push(target);
+                    }
+                    _ => break,
+                }
+            }
+            target
+        };
+
+        let mut output = vec![];
+        for (position, instruction) in input.iter().enumerate() {
+            let threaded = match instruction {
+                Asm::JMP(label) | Asm::BJMP(label) => {
+                    let target = resolve(*label);
+                    match labels.get(&target) {
+                        Some(destination) if target != *label => {
+                            // the vm can only jump forward with jmp and backward with bjmp
+                            if *destination > position {
+                                Some(Asm::JMP(target))
+                            } else {
+                                Some(Asm::BJMP(target))
+                            }
+                        }
+                        _ => None,
+                    }
+                }
+                Asm::JUMPIFFALSE(label) => {
+                    let target = resolve(*label);
+                    match labels.get(&target) {
+                        Some(destination) if target != *label && *destination > position => {
+                            Some(Asm::JUMPIFFALSE(target))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match threaded {
+                Some(jump) => {
+                    output.push(jump);
+                    self.optimizations += 1;
+                }
+                None => output.push(instruction.clone()),
+            }
+        }
+        output
+    }
+
+    // a function that returns the result of calling itself reuses its own frame
+    fn tail_call(&mut self, input: Vec<Asm>) -> Vec<Asm> {
+        // functions are stored into their global right after their end label
+        let mut function_globals = HashMap::new();
+        for window in input.windows(2) {
+            if let [Asm::LABEL(label), Asm::STOREGLOBAL(index)] = window {
+                function_globals.insert(*label, *index);
+            }
+        }
+
+        let mut output = vec![];
+        let mut function_stack: Vec<(u64, Option<u32>)> = vec![];
+        for instruction in input {
+            match instruction {
+                Asm::FUNCTION(label) => {
+                    function_stack.push((label, function_globals.get(&label).copied()));
+                }
+                Asm::CLOSURE(label) => {
+                    function_stack.push((label, None));
+                }
+                Asm::LABEL(label) => {
+                    if let Some((end, _)) = function_stack.last() {
+                        if label == *end {
+                            function_stack.pop();
+                        }
+                    }
+                }
+                Asm::RET(_) => {
+                    if let (Some((_, Some(current))), Some(Asm::DCALL(index))) =
+                        (function_stack.last(), output.last())
+                    {
+                        if current == index {
+                            let index = *index;
+                            output.pop();
+                            output.push(Asm::TCALL(index));
+                            self.optimizations += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            output.push(instruction);
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::code::Asm;
+
+    use super::{Optimizer, Pass};
+
+    // asm has no PartialEq, it is compared by how it prints
+    fn run(pass: Pass, input: Vec<Asm>) -> (Vec<String>, usize) {
+        let mut optimizer = super::new();
+        let output = pass(&mut optimizer, input);
+        (shown(&output), optimizer.optimizations)
+    }
+
+    fn shown(asm: &[Asm]) -> Vec<String> {
+        asm.iter()
+            .map(|instruction| format!("{instruction:?}"))
+            .collect()
+    }
+
+    #[test]
+    fn constant_fold() {
+        let (output, count) = run(
+            Optimizer::constant_fold,
+            vec![
+                Asm::INTEGER(2),
+                Asm::INTEGER(3),
+                Asm::IADD,
+                Asm::INTEGER(4),
+                Asm::IMUL,
+                Asm::NEG,
+            ],
+        );
+        assert_eq!(output, shown(&[Asm::INTEGER(-20)]));
+        assert_eq!(count, 3);
+        let (output, _) = run(
+            Optimizer::constant_fold,
+            vec![Asm::FLOAT(1.5), Asm::FLOAT(0.5), Asm::FLSS],
+        );
+        assert_eq!(output, shown(&[Asm::BOOL(false)]));
+    }
+
+    #[test]
+    fn constant_fold_leaves_what_would_fail() {
+        // division by zero and overflow are left for the vm to report
+        for input in [
+            vec![Asm::INTEGER(1), Asm::INTEGER(0), Asm::IDIV],
+            vec![Asm::INTEGER(i64::MAX), Asm::INTEGER(1), Asm::IADD],
+            vec![Asm::INTEGER(i64::MIN), Asm::NEG],
+        ] {
+            let expected = shown(&input);
+            let (output, count) = run(Optimizer::constant_fold, input);
+            assert_eq!(output, expected);
+            assert_eq!(count, 0);
+        }
+    }
+
+    #[test]
+    fn dup_pop() {
+        let (output, count) = run(
+            Optimizer::dup_pop,
+            vec![
+                Asm::GET(0),
+                Asm::POP,
+                Asm::DUP,
+                Asm::POP,
+                Asm::CALL,
+                Asm::POP,
+            ],
+        );
+        assert_eq!(output, shown(&[Asm::CALL, Asm::POP]));
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn dead_code() {
+        let (output, count) = run(
+            Optimizer::dead_code,
+            vec![
+                Asm::JMP(1),
+                Asm::INTEGER(1),
+                Asm::POP,
+                Asm::LABEL(1),
+                Asm::RET(false),
+                Asm::INTEGER(2),
+                Asm::LABEL(2),
+                Asm::INTEGER(3),
+            ],
+        );
+        assert_eq!(
+            output,
+            shown(&[
+                Asm::JMP(1),
+                Asm::LABEL(1),
+                Asm::RET(false),
+                Asm::LABEL(2),
+                Asm::INTEGER(3),
+            ])
+        );
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn jump_threading_picks_the_direction() {
+        let (output, count) = run(
+            Optimizer::jump_threading,
+            vec![
+                Asm::LABEL(1),
+                Asm::INTEGER(0),
+                // label 2 only jumps on to 3, which lies ahead
+                Asm::JMP(2),
+                Asm::LABEL(2),
+                Asm::JMP(3),
+                // label 4 jumps back to 1, so this jump has to become bjmp
+                Asm::JMP(4),
+                Asm::LABEL(4),
+                Asm::BJMP(1),
+                Asm::LABEL(3),
+            ],
+        );
+        assert_eq!(
+            output,
+            shown(&[
+                Asm::LABEL(1),
+                Asm::INTEGER(0),
+                Asm::JMP(3),
+                Asm::LABEL(2),
+                Asm::JMP(3),
+                Asm::BJMP(1),
+                Asm::LABEL(4),
+                Asm::BJMP(1),
+                Asm::LABEL(3),
+            ])
+        );
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn jump_threading_keeps_conditional_jumps_forward() {
+        let input = vec![
+            Asm::LABEL(1),
+            Asm::BOOL(true),
+            Asm::JUMPIFFALSE(2),
+            Asm::LABEL(2),
+            Asm::BJMP(1),
+        ];
+        let expected = shown(&input);
+        let (output, count) = run(Optimizer::jump_threading, input);
+        assert_eq!(output, expected);
+        assert_eq!(count, 0);
+    }
+
+    // fn f stored in global 3, calls to g in global 4 and from a closure inside f
+    fn function(body: Vec<Asm>) -> Vec<Asm> {
+        [
+            vec![Asm::FUNCTION(10), Asm::OFFSET(1, 0)],
+            body,
+            vec![Asm::LABEL(10), Asm::STOREGLOBAL(3)],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn tail_call_of_itself() {
+        let (output, count) = run(
+            Optimizer::tail_call,
+            function(vec![Asm::GET(0), Asm::DCALL(3), Asm::RET(true)]),
+        );
+        assert_eq!(
+            output,
+            shown(&function(vec![Asm::GET(0), Asm::TCALL(3), Asm::RET(true)]))
+        );
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn tail_call_leaves_other_calls() {
+        for body in [
+            // another function
+            vec![Asm::GET(0), Asm::DCALL(4), Asm::RET(true)],
+            // not in tail position
+            vec![
+                Asm::GET(0),
+                Asm::DCALL(3),
+                Asm::INTEGER(1),
+                Asm::IADD,
+                Asm::RET(true),
+            ],
+            // a closure inside f that calls f, its frame is not the one of f
+            vec![
+                Asm::CLOSURE(11),
+                Asm::GET(0),
+                Asm::DCALL(3),
+                Asm::RET(true),
+                Asm::LABEL(11),
+                Asm::RET(true),
+            ],
+            vec![
+                Asm::FUNCTION(12),
+                Asm::GET(0),
+                Asm::DCALL(3),
+                Asm::RET(true),
+                Asm::LABEL(12),
+                Asm::RET(true),
+            ],
+        ] {
+            let input = function(body);
+            let expected = shown(&input);
+            let (output, count) = run(Optimizer::tail_call, input);
+            assert_eq!(output, expected);
+            assert_eq!(count, 0);
+        }
+        // a call at the top level has no frame to reuse
+        let input = vec![Asm::GET(0), Asm::DCALL(3), Asm::RET(false)];
+        let expected = shown(&input);
+        assert_eq!(run(Optimizer::tail_call, input).0, expected);
+    }
+
+    #[test]
+    fn levels() {
+        let input = || vec![Asm::INTEGER(1), Asm::INTEGER(2), Asm::IADD, Asm::POP];
+        let mut optimizer = super::new();
+        assert_eq!(shown(&optimizer.optimize(input())), shown(&input()));
+        optimizer.level = 1;
+        // folded into a constant that is thrown away
+        assert!(optimizer.optimize(input()).is_empty());
+    }
 }
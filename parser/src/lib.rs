@@ -1,23 +1,21 @@
 use std::collections::HashMap;
 
 use common::{
-    environment::{new_environment, Environment},
+    environment::{new_environment, Environment, Implementation},
     error::NovaError,
     fileposition::FilePosition,
-    nodes::{Arg, Ast, Atom, Expr, Field, Statement, Symbol, SymbolKind},
+    nodes::{Arg, Arm, Ast, Atom, Expr, Field, Pattern, Statement, Symbol, SymbolKind},
+    resolver::{FileResolver, ModuleResolver, NoPlugins, PluginLoader},
+    symbols::{Reference, SymbolTable},
     table::{self, Table},
     tokens::{KeyWord, Operator, Token, TokenList, Unary},
     ttype::{generate_unique_string, TType},
 };
 use dym::Lexicon;
 use lexer::Lexer;
+use std::sync::Arc;
 
-fn extract_current_directory(path: &str) -> Option<String> {
-    if let Some(last_slash_index) = path.rfind('/') {
-        return Some(path[..last_slash_index + 1].to_string());
-    }
-    None
-}
+mod pattern;
 
 #[derive(Debug, Clone)]
 pub struct Parser {
@@ -27,6 +25,21 @@ pub struct Parser {
     pub ast: Ast,
     pub environment: Environment,
     pub modules: table::Table<String>,
+    pub symbols: SymbolTable,
+    // every error found so far, parsing carries on after each one
+    pub errors: Vec<NovaError>,
+    // things that are allowed but likely a mistake, they do not fail the parse
+    pub warnings: Vec<NovaError>,
+    // where imports are read from
+    pub resolver: Arc<dyn ModuleResolver>,
+    // where `import native` finds plugins
+    pub plugins: Arc<dyn PluginLoader>,
+    // trait bounds found while the parameters of a function are parsed
+    bounds: Option<Vec<(String, String)>>,
+    // the function being parsed is a method of an impl block
+    implementing: bool,
+    // a match guard is being parsed, the -> after it starts the arm
+    guard: bool,
 }
 
 pub fn new(filepath: &str) -> Parser {
@@ -149,10 +162,33 @@ pub fn new(filepath: &str) -> Parser {
         index: 0,
         environment: env,
         modules: table::new(),
+        symbols: common::symbols::new(),
+        errors: vec![],
+        warnings: vec![],
+        resolver: Arc::new(FileResolver),
+        plugins: Arc::new(NoPlugins),
+        bounds: None,
+        implementing: false,
+        guard: false,
     }
 }
 
 impl Parser {
+    // remembers what an identifier in the source resolved to,
+    // the definition is taken from the symbol the name refers to
+    fn record(&mut self, name: &str, symbol: &str, ttype: &TType, position: &FilePosition) {
+        let definition = self
+            .environment
+            .find(symbol)
+            .and_then(|symbol| symbol.pos.clone());
+        self.symbols.insert(Reference {
+            name: name.to_string(),
+            ttype: ttype.clone(),
+            position: position.clone(),
+            definition,
+        });
+    }
+
     fn check_and_map_types(
         &self,
         type_list1: &[TType],
@@ -405,16 +441,26 @@ impl Parser {
         }
     }
 
-    fn get_line_and_row(&self) -> (usize, usize) {
-        let line = self.current_token().line();
-        let row = self.current_token().row();
-        (line, row)
-    }
-
     fn get_current_token_position(&self) -> FilePosition {
         self.current_token().position()
     }
 
+    // the span from start to the end of the last consumed token
+    fn span_from(&self, start: &FilePosition) -> FilePosition {
+        match self.index.checked_sub(1).and_then(|index| self.input.get(index)) {
+            Some(token) => start.to(&token.position()),
+            None => start.clone(),
+        }
+    }
+
+    // the span from where the expression starts to the last consumed token
+    fn span_after(&self, expr: &Expr) -> FilePosition {
+        match expr.get_position() {
+            Some(start) => self.span_from(&start),
+            None => self.get_current_token_position(),
+        }
+    }
+
     fn consume_operator(&mut self, op: Operator) -> Result<(), NovaError> {
         if let Token::Operator { operator, .. } = self.current_token() {
             if op == operator {
@@ -693,6 +739,7 @@ impl Parser {
             } => {
                 self.advance();
                 // call get closure
+                let start = self.get_current_token_position();
                 let (typeinput, input, output, statement, captured) = self.bar_closure()?;
                 //dbg!(typeinput.clone(), input.clone(), output.clone(), statement.clone());
                 let last_closure = Expr::Closure {
@@ -703,6 +750,7 @@ impl Parser {
                     args: input,
                     body: statement,
                     captures: captured,
+                    position: self.span_from(&start),
                 };
                 argument_types.push(last_closure.get_type());
                 arguments.push(last_closure);
@@ -720,36 +768,14 @@ impl Parser {
                 //dbg!(&name);
                 identifier = format!("{}::{}", name, identifier);
             }
+        } else if let Some(TType::Generic { name }) = argument_types.first() {
+            let generic = name.clone();
+            self.varargs(&identifier, &mut argument_types, &mut arguments);
+            return self.trait_method_call(&generic, &identifier, arguments, argument_types, pos);
         } else if let Some(ttype) = argument_types.get(0) {
-            match ttype {
-                TType::List { .. } => {
-                    identifier = format!("List::{}", identifier);
-                }
-                TType::Option { .. } => {
-                    identifier = format!("Option::{}", identifier);
-                }
-                TType::Function { parameters, .. } => {
-                    let repeated_elements: String = "(_)".repeat(parameters.len());
-                    identifier = format!("Function{}::{}",repeated_elements, identifier);
-                }
-                TType::Tuple { elements } => {
-                    let repeated_elements: String = "(_)".repeat(elements.len());
-                    identifier = format!("Tuple{}::{}",repeated_elements, identifier);
-                }
-                TType::Bool => {
-                    identifier = format!("Bool::{}", identifier);
-                }
-                TType::Int => {
-                    identifier = format!("Int::{}", identifier);
-                }
-                TType::Float => {
-                    identifier = format!("Float::{}", identifier);
-                }
-                TType::Char => {
-                    identifier = format!("Char::{}", identifier);
-                }
-                TType::String => {
-                    identifier = format!("String::{}", identifier);
+            match ttype.extends_prefix() {
+                Some(prefix) if ttype.custom_to_string().is_none() => {
+                    identifier = format!("{}::{}", prefix, identifier);
                 }
                 _ => {
                     return Err(self.generate_error_with_pos(
@@ -777,6 +803,7 @@ impl Parser {
             .environment
             .get_function_type(&identifier, &argument_types)
         {
+            self.record(&old_identifier, &function_id, &function_type, &pos);
             self.handle_function_call(
                 function_type,
                 function_id,
@@ -789,6 +816,7 @@ impl Parser {
             self.environment.get_type_capture(&identifier)
         {
             //println!("captured id {}", identifier);
+            self.record(&old_identifier, &function_id, &function_type, &pos);
             let pos = self.get_current_token_position();
             self.environment.captured.last_mut().unwrap().insert(
                 identifier.clone(),
@@ -812,6 +840,7 @@ impl Parser {
                 .environment
                 .get_function_type(&old_identifier, &argument_types)
             {
+                self.record(&old_identifier, &function_id, &function_type, &pos);
                 self.handle_function_call(
                     function_type,
                     function_id,
@@ -824,6 +853,7 @@ impl Parser {
                 self.environment.get_type_capture(&old_identifier)
             {
                 //println!("captured id {}", identifier);
+                self.record(&old_identifier, &function_id, &function_type, &pos);
                 let pos = self.get_current_token_position();
                 self.environment.captured.last_mut().unwrap().insert(
                     identifier.clone(),
@@ -889,12 +919,30 @@ impl Parser {
         let mut generic_list = self.collect_generics(&[*return_type.clone()]);
         generic_list.extend(self.collect_generics(&parameters));
         //dbg!(generic_list.clone());
-        let mut type_map = self.check_and_map_types(
-            &parameters,
-            &argument_types,
-            &mut HashMap::default(),
-            pos.clone(),
-        )?;
+        // arguments are checked one at a time so a mismatch points at the argument
+        let mut type_map = if parameters.len() == arguments.len() {
+            let mut type_map = HashMap::default();
+            for ((parameter, argument_type), argument) in parameters
+                .iter()
+                .zip(argument_types.iter())
+                .zip(arguments.iter())
+            {
+                type_map = self.check_and_map_types(
+                    std::slice::from_ref(parameter),
+                    std::slice::from_ref(argument_type),
+                    &mut type_map,
+                    argument.get_position().unwrap_or_else(|| pos.clone()),
+                )?;
+            }
+            type_map
+        } else {
+            self.check_and_map_types(
+                &parameters,
+                &argument_types,
+                &mut HashMap::default(),
+                pos.clone(),
+            )?
+        };
 
         if let SymbolKind::GenericFunction | SymbolKind::Constructor = function_kind {
             self.map_generic_types(&parameters, &argument_types, &mut type_map, pos.clone())?;
@@ -904,6 +952,26 @@ impl Parser {
         self.modify_type_map(&mut type_map, pos.clone(), generic_list)?;
         return_type = Box::new(self.get_output(*return_type, &mut type_map, pos.clone())?);
 
+        // a function with trait bounds is handed the methods of each bound
+        // after its arguments, picked here where the types are known
+        let mut arguments = arguments;
+        if let Some(bounds) = self.environment.bounds.get(&function_id).cloned() {
+            for (generic, trait_name) in bounds {
+                let Some(ttype) = type_map.get(&generic).cloned() else {
+                    return Err(NovaError::SimpleTypeError {
+                        msg: format!("Generic type {} could not be inferred", generic),
+                        position: pos,
+                    });
+                };
+                arguments.extend(self.conformance(
+                    &ttype,
+                    &trait_name,
+                    &function_id,
+                    pos.clone(),
+                )?);
+            }
+        }
+
         if let Some(subtype) = self.environment.generic_type_map.get(&function_id) {
             function_id = subtype.clone();
         }
@@ -915,6 +983,7 @@ impl Parser {
                 arguments,
                 position: pos.clone(),
             },
+            position: pos,
         });
     }
 
@@ -1097,9 +1166,18 @@ impl Parser {
             rightexpr.reverse();
             arguments.append(&mut rightexpr);
             leftexpr.reverse();
+            // the collected arguments span from the first to the last one
+            let position = match (leftexpr.first(), leftexpr.last()) {
+                (Some(first), Some(last)) => first
+                    .get_position()
+                    .zip(last.get_position())
+                    .map(|(first, last)| first.to(&last)),
+                _ => None,
+            };
             arguments.push(Expr::ListConstructor {
                 ttype: type_flag.clone(),
                 elements: leftexpr.clone(),
+                position: position.unwrap_or_else(|| self.get_current_token_position()),
             });
         }
     }
@@ -1115,6 +1193,7 @@ impl Parser {
             } => {
                 self.advance();
                 // call get closure
+                let start = self.get_current_token_position();
                 let (typeinput, input, output, statement, captured) = self.bar_closure()?;
                 //dbg!(typeinput.clone(), input.clone(), output.clone(), statement.clone());
                 let last_closure = Expr::Closure {
@@ -1125,6 +1204,7 @@ impl Parser {
                     args: input,
                     body: statement,
                     captures: captured,
+                    position: self.span_from(&start),
                 };
                 argument_types.push(last_closure.get_type());
                 arguments.push(last_closure);
@@ -1142,6 +1222,7 @@ impl Parser {
             .environment
             .get_function_type(&identifier, &argument_types)
         {
+            self.record(&identifier, &function_id, &function_type, &pos);
             self.handle_function_call(
                 function_type,
                 function_id,
@@ -1154,6 +1235,7 @@ impl Parser {
             self.environment.get_type_capture(&identifier)
         {
             //println!("captured id: call {}", identifier);
+            self.record(&identifier, &function_id, &function_type, &pos);
             let pos = self.get_current_token_position();
             self.environment.captured.last_mut().unwrap().insert(
                 identifier.clone(),
@@ -1294,12 +1376,18 @@ impl Parser {
                     };
                 //dbg!(&new_fields);
                 if let Some((index, field_type)) = self.find_field(&identifier, &new_fields) {
+                    let field_type = field_type.clone();
+                    self.record(&identifier, &type_name, &field_type, &pos);
+                    let position = match lhs.get_position() {
+                        Some(start) => start.to(&pos),
+                        None => pos.clone(),
+                    };
                     lhs = Expr::Field {
                         ttype: field_type.clone(),
                         name: type_name.clone(),
                         index,
                         expr: Box::new(lhs),
-                        position: pos.clone(),
+                        position,
                     };
                 } else {
                     return self.generate_field_not_found_error(
@@ -1362,6 +1450,9 @@ impl Parser {
     }
 
     fn chain(&mut self, mut lhs: Expr) -> Result<Expr, NovaError> {
+        let start = lhs
+            .get_position()
+            .unwrap_or_else(|| self.get_current_token_position());
         let (identifier, pos) = self.get_identifier()?;
         match self.current_token() {
             Token::Operator {
@@ -1380,6 +1471,7 @@ impl Parser {
                                 value: Atom::Id {
                                     name: identifier.clone(),
                                 },
+                                position: start.clone(),
                             },
                             pos,
                         )?;
@@ -1417,6 +1509,7 @@ impl Parser {
                         name: "anon".to_string(),
                         function: Box::new(lhs),
                         args: arguments,
+                        position: self.span_from(&start),
                     };
                 } else {
                     return Err(self.generate_error_with_pos(
@@ -1437,6 +1530,7 @@ impl Parser {
                 lhs = self.field(identifier.clone(), lhs, pos)?;
             }
         }
+        lhs.set_position(self.span_from(&start));
 
         Ok(lhs)
     }
@@ -1476,6 +1570,9 @@ impl Parser {
         mut lhs: Expr,
         container_type: TType,
     ) -> Result<Expr, NovaError> {
+        let start = lhs
+            .get_position()
+            .unwrap_or_else(|| self.get_current_token_position());
         match container_type {
             TType::List {
                 inner: element_type,
@@ -1567,7 +1664,7 @@ impl Parser {
                         end: end_expr,
                         step: step,
                         container: Box::new(lhs),
-                        position,
+                        position: self.span_from(&start),
                     };
                 } else {
                     if let Some(start_expr) = start_expr {
@@ -1588,7 +1685,7 @@ impl Parser {
                             name: identifier.clone(),
                             index: start_expr,
                             container: Box::new(lhs),
-                            position,
+                            position: self.span_from(&start),
                         };
                     }
                 }
@@ -1618,9 +1715,10 @@ impl Parser {
                         index: Box::new(Expr::Literal {
                             ttype: TType::Int,
                             value: Atom::Integer { value: index },
+                            position,
                         }),
                         container: Box::new(lhs),
-                        position,
+                        position: self.span_from(&start),
                     };
                     if self.current_token().is_symbol('[') {
                         lhs = self.index(identifier.clone(), lhs, element_type.clone())?;
@@ -1637,6 +1735,48 @@ impl Parser {
                     ));
                 }
             }
+            TType::Custom { .. } => {
+                // a[i] calls the extends function index of the type
+                self.consume_symbol('[')?;
+                let index = self.expr()?;
+                self.consume_symbol(']')?;
+                let Some(call) = self.overloaded_operator(
+                    "index",
+                    &[lhs.clone(), index.clone()],
+                    self.span_from(&start),
+                )?
+                else {
+                    return Err(self.generate_error_with_pos(
+                        format!("Cannot index into {}", container_type.to_string()),
+                        format!(
+                            "No function index with {} and {} as arguments",
+                            container_type.to_string(),
+                            index.get_type().to_string()
+                        ),
+                        self.span_from(&start),
+                    ));
+                };
+                lhs = call;
+                if self.current_token().is_symbol('[') {
+                    lhs = self.index(identifier.clone(), lhs.clone(), lhs.get_type())?;
+                }
+            }
+            // only a let that failed has no type, its error was already reported
+            TType::Any => {
+                self.consume_symbol('[')?;
+                let index = self.expr()?;
+                self.consume_symbol(']')?;
+                lhs = Expr::Indexed {
+                    ttype: TType::Any,
+                    name: identifier.clone(),
+                    index: Box::new(index),
+                    container: Box::new(lhs),
+                    position: self.span_from(&start),
+                };
+                if self.current_token().is_symbol('[') {
+                    lhs = self.index(identifier.clone(), lhs, TType::Any)?;
+                }
+            }
             _ => {
                 return Err(self.generate_error(
                     format!("Cannot index into non-list or non-tuple"),
@@ -1666,15 +1806,22 @@ impl Parser {
             Token::Operator {
                 operator: Operator::RightArrow,
                 ..
-            } => {
+            } if !self.guard => {
                 self.consume_operator(Operator::RightArrow)?;
                 let (field, field_position) = self.get_identifier()?;
                 if let Some(identifier_type) = self.environment.get_type(&identifier) {
-                    let mut arguments =
-                        vec![self.create_literal_expr(identifier.clone(), identifier_type.clone())];
+                    let mut arguments = vec![self.create_literal_expr(
+                        identifier.clone(),
+                        identifier_type.clone(),
+                        pos.clone(),
+                    )];
                     let left_expr = self.field(
                         field.clone(),
-                        self.create_literal_expr(identifier.clone(), identifier_type.clone()),
+                        self.create_literal_expr(
+                            identifier.clone(),
+                            identifier_type.clone(),
+                            pos.clone(),
+                        ),
                         field_position.clone(),
                     )?;
                     arguments.extend(self.argument_list()?);
@@ -1701,13 +1848,17 @@ impl Parser {
                             &mut type_map,
                             field_position.clone(),
                         )?;
-                        return_type =
-                            Box::new(self.get_output(*return_type.clone(), &mut type_map, pos)?);
+                        return_type = Box::new(self.get_output(
+                            *return_type.clone(),
+                            &mut type_map,
+                            pos.clone(),
+                        )?);
                         Expr::Call {
                             ttype: *return_type,
                             name: field.to_string(),
                             function: Box::new(left_expr),
                             args: arguments,
+                            position: self.span_from(&pos),
                         }
                     } else {
                         return Err(self.generate_error_with_pos(
@@ -1727,12 +1878,18 @@ impl Parser {
             Token::Symbol { symbol: '[', .. } => {
                 self.handle_indexing(identifier.clone(), pos.clone())?
             }
-            Token::Symbol { symbol: '(', .. } => self.call(identifier.clone(), pos)?,
+            Token::Symbol { symbol: '(', .. } => {
+                let mut call = self.call(identifier.clone(), pos.clone())?;
+                call.set_position(self.span_from(&pos));
+                call
+            }
             _ => {
                 if self.current_token().is_symbol('{')
                     && self.environment.custom_types.contains_key(&identifier)
                 {
-                    self.call(identifier.clone(), pos.clone())?
+                    let mut call = self.call(identifier.clone(), pos.clone())?;
+                    call.set_position(self.span_from(&pos));
+                    call
                 } else {
                     self.handle_literal_or_capture(identifier.clone(), pos.clone())?
                 }
@@ -1742,10 +1899,11 @@ impl Parser {
         Ok(anchor)
     }
 
-    fn create_literal_expr(&self, identifier: String, ttype: TType) -> Expr {
+    fn create_literal_expr(&self, identifier: String, ttype: TType, position: FilePosition) -> Expr {
         Expr::Literal {
             ttype,
             value: Atom::Id { name: identifier },
+            position,
         }
     }
 
@@ -1755,12 +1913,14 @@ impl Parser {
         position: FilePosition,
     ) -> Result<Expr, NovaError> {
         if let Some(ttype) = self.environment.get_type(&identifier) {
+            self.record(&identifier, &identifier, &ttype, &position);
             self.index(
                 identifier.clone(),
-                self.create_literal_expr(identifier.clone(), ttype.clone()),
+                self.create_literal_expr(identifier.clone(), ttype.clone(), position.clone()),
                 ttype.clone(),
             )
         } else if let Some((ttype, _, kind)) = self.environment.get_type_capture(&identifier) {
+            self.record(&identifier, &identifier, &ttype, &position);
             self.environment.captured.last_mut().unwrap().insert(
                 identifier.clone(),
                 Symbol {
@@ -1778,7 +1938,7 @@ impl Parser {
             );
             self.index(
                 identifier.clone(),
-                self.create_literal_expr(identifier.clone(), ttype.clone()),
+                self.create_literal_expr(identifier.clone(), ttype.clone(), position.clone()),
                 ttype.clone(),
             )
         } else {
@@ -1803,10 +1963,24 @@ impl Parser {
         identifier: String,
         position: FilePosition,
     ) -> Result<Expr, NovaError> {
+        // the methods of its bounds are only handed over when it is called
+        if self.environment.bounds.contains_key(&identifier) {
+            return Err(self.generate_error_with_pos(
+                format!("Cannot use {identifier} as a value"),
+                "Functions with trait bounds can only be called".to_string(),
+                position,
+            ));
+        }
         if let Some(ttype) = self.environment.get_type(&identifier) {
             //println!("identifier hloc-not-capture {}", identifier);
-            Ok(self.create_literal_expr(identifier.clone(), ttype.clone()))
+            self.record(&identifier, &identifier, &ttype, &position);
+            Ok(self.create_literal_expr(
+                identifier.clone(),
+                ttype.clone(),
+                position.clone(),
+            ))
         } else if let Some((ttype, _, kind)) = self.environment.get_type_capture(&identifier) {
+            self.record(&identifier, &identifier, &ttype, &position);
             // println!("identifier hloc-capture {}", identifier);
             // println!(
             //     "environment {:?}",
@@ -1827,7 +2001,11 @@ impl Parser {
                 Some(position.clone()),
                 kind,
             );
-            Ok(self.create_literal_expr(identifier.clone(), ttype.clone()))
+            Ok(self.create_literal_expr(
+                identifier.clone(),
+                ttype.clone(),
+                position.clone(),
+            ))
         } else {
             let mut lexicon = Lexicon::new();
             for (id, _) in self.environment.values.last().unwrap().iter() {
@@ -1846,12 +2024,14 @@ impl Parser {
     }
 
     fn factor(&mut self) -> Result<Expr, NovaError> {
+        let sign_start = self.get_current_token_position();
         let sign = if let Ok(Some(sign)) = self.sign() {
             self.advance();
             Some(sign)
         } else {
             None
         };
+        let start = self.get_current_token_position();
         let mut left: Expr;
         match self.current_token() {
             Token::Symbol { symbol: '#', .. } => {
@@ -1865,6 +2045,7 @@ impl Parser {
                 left = Expr::ListConstructor {
                     ttype: TType::Tuple { elements: typelist },
                     elements: expressions,
+                    position: self.span_from(&start),
                 };
             }
             Token::Symbol { symbol: '?', .. } => {
@@ -1875,6 +2056,7 @@ impl Parser {
                         inner: Box::new(option_type),
                     },
                     value: Atom::None,
+                    position: self.span_from(&start),
                 };
             }
             Token::Char { value: char, .. } => {
@@ -1882,6 +2064,7 @@ impl Parser {
                 left = Expr::Literal {
                     ttype: TType::Char,
                     value: Atom::Char { value: char },
+                    position: self.span_from(&start),
                 }
             }
             Token::Identifier { name: id, .. } if id.as_str() == "fn" => {
@@ -2060,6 +2243,7 @@ impl Parser {
                             statements.push(Statement::Return {
                                 ttype: output.clone(),
                                 expr: Expr::None,
+                                position: pos.clone(),
                             });
                         }
                     }
@@ -2073,6 +2257,7 @@ impl Parser {
                     args: input,
                     body: statements,
                     captures: captured,
+                    position: self.span_from(&start),
                 };
             }
             Token::Symbol { symbol: '|', .. }
@@ -2090,6 +2275,7 @@ impl Parser {
                     args: input,
                     body: statement,
                     captures: captured,
+                    position: self.span_from(&start),
                 };
             }
             Token::Symbol { symbol: '[', .. } => {
@@ -2201,6 +2387,7 @@ impl Parser {
                             loops,
                             expr: outexpr,
                             guards,
+                            position: self.span_from(&start),
                         };
                     }
                     _ => {
@@ -2263,6 +2450,7 @@ impl Parser {
                                 inner: Box::new(ttype),
                             },
                             elements: expr_list,
+                            position: self.span_from(&start),
                         };
                     }
                 }
@@ -2285,6 +2473,7 @@ impl Parser {
                         ttype: left.clone().get_type(),
                         op: sign,
                         expr: Box::new(left),
+                        position: self.span_from(&sign_start),
                     };
                 }
             }
@@ -2356,8 +2545,10 @@ impl Parser {
                                     value: Atom::Id {
                                         name: identifier.clone(),
                                     },
+                                    position: pos.clone(),
                                 }),
                                 rhs: Box::new(expr),
+                                position: self.span_from(&start),
                             });
                         }
                     }
@@ -2379,6 +2570,7 @@ impl Parser {
                         ttype: left.clone().get_type(),
                         op: sign,
                         expr: Box::new(left),
+                        position: self.span_from(&sign_start),
                     };
                 }
             }
@@ -2387,6 +2579,7 @@ impl Parser {
                 left = Expr::Literal {
                     ttype: TType::Int,
                     value: Atom::Integer { value: v },
+                    position: self.span_from(&start),
                 };
                 if let Some(sign) = sign {
                     if Unary::Not == sign {
@@ -2401,6 +2594,7 @@ impl Parser {
                         ttype: left.clone().get_type(),
                         op: sign,
                         expr: Box::new(left),
+                        position: self.span_from(&sign_start),
                     };
                 }
             }
@@ -2409,6 +2603,7 @@ impl Parser {
                 left = Expr::Literal {
                     ttype: TType::Float,
                     value: Atom::Float { value: v },
+                    position: self.span_from(&start),
                 };
                 if let Some(sign) = sign {
                     if Unary::Not == sign {
@@ -2423,6 +2618,7 @@ impl Parser {
                         ttype: left.clone().get_type(),
                         op: sign,
                         expr: Box::new(left),
+                        position: self.span_from(&sign_start),
                     };
                 }
             }
@@ -2431,6 +2627,7 @@ impl Parser {
                 left = Expr::Literal {
                     ttype: TType::String,
                     value: Atom::String { value: v },
+                    position: self.span_from(&start),
                 };
             }
 
@@ -2439,6 +2636,7 @@ impl Parser {
                 left = Expr::Literal {
                     ttype: TType::Bool,
                     value: Atom::Bool { value: v },
+                    position: self.span_from(&start),
                 };
             }
             Token::EOF { .. } => {
@@ -2452,7 +2650,7 @@ impl Parser {
                 Token::Operator {
                     operator: Operator::RightArrow,
                     ..
-                } => {
+                } if !self.guard => {
                     self.consume_operator(Operator::RightArrow)?;
                     left = self.handle_inner_function_call(left)?;
                 }
@@ -2566,7 +2764,7 @@ impl Parser {
         let statement = if let Token::Symbol { symbol: '{', .. } = self.current_token() {
             //println!("its a block");
             let block = self.block_expr()?;
-            if let Some(Statement::Return { ttype, expr: _ }) = block.last() {
+            if let Some(Statement::Return { ttype, .. }) = block.last() {
                 output = ttype.clone();
             };
             block
@@ -2578,6 +2776,7 @@ impl Parser {
             let statement = vec![Statement::Return {
                 ttype: expression.get_type(),
                 expr: expression.clone(),
+                position: pos.clone(),
             }];
             statement
         };
@@ -2700,6 +2899,7 @@ impl Parser {
             Ok(Expr::Call {
                 ttype: *return_type,
                 name: function_name,
+                position: self.span_after(&function_expr),
                 function: Box::new(function_expr),
                 args: arguments,
             })
@@ -2737,6 +2937,12 @@ impl Parser {
                         operation,
                         left_expr.get_type(),
                     );
+                } else if let Some(call) = self.overloaded_operator(
+                    Self::operator_method(&operation).unwrap_or_default(),
+                    &[left_expr.clone(), right_expr.clone()],
+                    self.span_after(&left_expr),
+                )? {
+                    left_expr = call;
                 } else {
                     return Err(self.create_type_error(
                         left_expr.clone(),
@@ -2757,6 +2963,12 @@ impl Parser {
             if let Some(operation) = self.current_token().get_operator() {
                 self.advance();
                 let right_expr = self.top_expr()?;
+                let (operation, right_expr) = self.compound_assignment(
+                    operation,
+                    &left_expr,
+                    right_expr,
+                    current_pos.clone(),
+                )?;
                 match left_expr.clone() {
                     Expr::ListConstructor { .. }
                     | Expr::Binop { .. }
@@ -2776,7 +2988,7 @@ impl Parser {
                                 &vec![left_expr.get_type()],
                                 &vec![right_expr.get_type()],
                                 &mut HashMap::default(),
-                                current_pos.clone(),
+                                right_expr.get_position().unwrap_or(current_pos.clone()),
                             )?;
                         }
                         _ => {
@@ -2808,6 +3020,7 @@ impl Parser {
                 left_expr = Expr::Binop {
                     ttype: TType::Void,
                     op: operation,
+                    position: self.span_after(&left_expr),
                     lhs: Box::new(left_expr),
                     rhs: Box::new(right_expr),
                 };
@@ -2858,6 +3071,7 @@ impl Parser {
                                 left_expr = Expr::StoreExpr {
                                     ttype: ttype.clone(),
                                     name: identifier.clone(),
+                                    position: self.span_after(&left_expr),
                                     expr: Box::new(left_expr),
                                     body: expr_block,
                                 };
@@ -2865,6 +3079,7 @@ impl Parser {
                                 left_expr = Expr::StoreExpr {
                                     ttype: TType::Void,
                                     name: identifier.clone(),
+                                    position: self.span_after(&left_expr),
                                     expr: Box::new(left_expr),
                                     body: expr_block,
                                 };
@@ -2917,9 +3132,17 @@ impl Parser {
                         match (left_expr.get_type(), right_expr.get_type()) {
                             (TType::Int, TType::Int) => {}
                             (TType::Float, TType::Float) => {}
+                            (left, right) if left == right && Self::is_ordered(&left) => {}
                             _ => {
+                                if let Some(comparison) =
+                                    self.overloaded_comparison(&left_expr, &right_expr, &operation)?
+                                {
+                                    left_expr = comparison;
+                                    continue;
+                                }
                                 return Err(self.generate_error_with_pos(
-                                    "Comparison operation expects int or float".to_string(),
+                                    "Comparison operation expects int, float, char, string, or a list or tuple of those"
+                                        .to_string(),
                                     format!(
                                         "expected {} , but found {}",
                                         left_expr.get_type().to_string(),
@@ -2933,6 +3156,12 @@ impl Parser {
                             self.create_binop_expr(left_expr, right_expr, operation, TType::Bool);
                     }
                     _ => {
+                        if let Some(comparison) =
+                            self.overloaded_comparison(&left_expr, &right_expr, &operation)?
+                        {
+                            left_expr = comparison;
+                            continue;
+                        }
                         left_expr =
                             self.create_binop_expr(left_expr, right_expr, operation, TType::Bool);
                     }
@@ -2979,12 +3208,20 @@ impl Parser {
                         }
                     }
                     (_, _) => {
-                        return Err(self.create_type_error(
-                            left_expr.clone(),
-                            right_expr.clone(),
-                            operation,
-                            current_pos.clone(),
-                        ));
+                        let Some(call) = self.overloaded_operator(
+                            Self::operator_method(&operation).unwrap_or_default(),
+                            &[left_expr.clone(), right_expr.clone()],
+                            self.span_after(&left_expr),
+                        )?
+                        else {
+                            return Err(self.create_type_error(
+                                left_expr.clone(),
+                                right_expr.clone(),
+                                operation,
+                                current_pos.clone(),
+                            ));
+                        };
+                        left_expr = call;
                     }
                 }
             }
@@ -2992,6 +3229,154 @@ impl Parser {
         Ok(left_expr)
     }
 
+    // types that < and > order, lists and tuples compare item by item
+    fn is_ordered(ttype: &TType) -> bool {
+        match ttype {
+            TType::Int | TType::Float | TType::Char | TType::String => true,
+            TType::List { inner } => Self::is_ordered(inner),
+            TType::Tuple { elements } => elements.iter().all(Self::is_ordered),
+            _ => false,
+        }
+    }
+
+    // the extends function an operator on a custom type resolves to
+    fn operator_method(operation: &Operator) -> Option<&'static str> {
+        match operation {
+            Operator::Addition => Some("add"),
+            Operator::Subtraction => Some("sub"),
+            Operator::Multiplication => Some("mul"),
+            Operator::Division => Some("div"),
+            Operator::Modulo => Some("rem"),
+            _ => None,
+        }
+    }
+
+    // a + b on a custom type calls the extends function add of that type,
+    // there is no overload when the type does not declare one
+    fn overloaded_operator(
+        &mut self,
+        method: &str,
+        arguments: &[Expr],
+        pos: FilePosition,
+    ) -> Result<Option<Expr>, NovaError> {
+        let argument_types: Vec<TType> = arguments.iter().map(|t| t.get_type()).collect();
+        let Some(TType::Custom { name, .. }) = argument_types.first() else {
+            return Ok(None);
+        };
+        let identifier = format!("{}::{}", name, method);
+        let Some((function_type, function_id, function_kind)) = self
+            .environment
+            .get_function_type(&identifier, &argument_types)
+        else {
+            return Ok(None);
+        };
+        let call = self.handle_function_call(
+            function_type,
+            function_id,
+            function_kind,
+            arguments.to_vec(),
+            argument_types,
+            pos.clone(),
+        )?;
+        let expected = match method {
+            "eq" => TType::Bool,
+            "cmp" => TType::Int,
+            _ => return Ok(Some(call)),
+        };
+        if call.get_type() != expected {
+            return Err(self.generate_error_with_pos(
+                format!("{} must return {}", identifier, expected.to_string()),
+                format!("found {}", call.get_type().to_string()),
+                pos,
+            ));
+        }
+        Ok(Some(call))
+    }
+
+    // a += b on a type without a builtin += is assigned a.add(b), the same
+    // goes for -= and sub
+    fn compound_assignment(
+        &mut self,
+        operation: Operator,
+        left_expr: &Expr,
+        right_expr: Expr,
+        pos: FilePosition,
+    ) -> Result<(Operator, Expr), NovaError> {
+        let method = match operation {
+            Operator::AdditionAssignment => "add",
+            Operator::SubtractionAssignment => "sub",
+            _ => return Ok((operation, right_expr)),
+        };
+        let builtin = match left_expr.get_type() {
+            TType::Int | TType::Float => true,
+            TType::String | TType::List { .. } => operation == Operator::AdditionAssignment,
+            _ => false,
+        };
+        if builtin {
+            return Ok((operation, right_expr));
+        }
+        match self.overloaded_operator(
+            method,
+            &[left_expr.clone(), right_expr.clone()],
+            self.span_after(left_expr),
+        )? {
+            Some(call) => Ok((Operator::Assignment, call)),
+            None => Err(self.generate_error_with_pos(
+                format!(
+                    "Cannot apply {:?} to {}",
+                    operation,
+                    left_expr.get_type().to_string()
+                ),
+                format!(
+                    "No function {} with {} and {} as arguments",
+                    method,
+                    left_expr.get_type().to_string(),
+                    right_expr.get_type().to_string()
+                ),
+                pos,
+            )),
+        }
+    }
+
+    // a < b on a custom type compares the result of cmp with 0, a == b uses
+    // eq and falls back on cmp
+    fn overloaded_comparison(
+        &mut self,
+        left_expr: &Expr,
+        right_expr: &Expr,
+        operation: &Operator,
+    ) -> Result<Option<Expr>, NovaError> {
+        let position = self.span_after(left_expr);
+        let arguments = [left_expr.clone(), right_expr.clone()];
+        if let Operator::Equality | Operator::NotEqual = operation {
+            if let Some(call) = self.overloaded_operator("eq", &arguments, position.clone())? {
+                if *operation == Operator::Equality {
+                    return Ok(Some(call));
+                }
+                return Ok(Some(Expr::Unary {
+                    ttype: TType::Bool,
+                    op: Unary::Not,
+                    expr: Box::new(call),
+                    position,
+                }));
+            }
+        }
+        let Some(call) = self.overloaded_operator("cmp", &arguments, position.clone())? else {
+            return Ok(None);
+        };
+        Ok(Some(Expr::Binop {
+            ttype: TType::Bool,
+            op: operation.clone(),
+            lhs: Box::new(call),
+            rhs: Box::new(Expr::Literal {
+                ttype: TType::Int,
+                value: Atom::Integer { value: 0 },
+                position: position.clone(),
+            }),
+            position,
+        }))
+    }
+
     fn create_binop_expr(
         &self,
         left_expr: Expr,
@@ -3002,6 +3387,7 @@ impl Parser {
         Expr::Binop {
             ttype,
             op: operation,
+            position: self.span_after(&left_expr),
             lhs: Box::new(left_expr),
             rhs: Box::new(right_expr),
         }
@@ -3014,10 +3400,15 @@ impl Parser {
         operation: Operator,
         pos: FilePosition,
     ) -> NovaError {
+        // underline the whole operation when both sides know where they are
+        let position = match (left_expr.get_position(), right_expr.get_position()) {
+            (Some(left), Some(right)) => left.to(&right),
+            _ => pos,
+        };
         NovaError::TypeError {
             expected: left_expr.get_type().to_string(),
             found: right_expr.get_type().to_string(),
-            position: pos,
+            position,
             msg: format!(
                 "Type error, cannot apply operation {:?} to {} and {}",
                 operation,
@@ -3078,6 +3469,42 @@ impl Parser {
             Token::Symbol { symbol: '$', .. } => {
                 self.consume_symbol('$')?;
                 let (generictype, _) = self.get_identifier()?;
+                // $T: Show + Eq only accepts types that implement both traits
+                if self.current_token().is_op(Operator::Colon)
+                    && self
+                        .peek_offset(1)
+                        .is_some_and(|token| token.is_identifier())
+                {
+                    self.advance();
+                    loop {
+                        let (trait_name, position) = self.get_identifier()?;
+                        if !self.environment.traits.contains_key(&trait_name) {
+                            return Err(self.generate_error_with_pos(
+                                format!("Trait {trait_name} does not exist"),
+                                "Only traits can bound a generic type".to_string(),
+                                position,
+                            ));
+                        }
+                        match &mut self.bounds {
+                            Some(bounds) => {
+                                let bound = (generictype.clone(), trait_name);
+                                if !bounds.contains(&bound) {
+                                    bounds.push(bound);
+                                }
+                            }
+                            None => return Err(self.generate_error_with_pos(
+                                format!("Cannot bound ${generictype} here"),
+                                "Trait bounds can only be given on the parameters of a function"
+                                    .to_string(),
+                                position,
+                            )),
+                        }
+                        if !self.current_token().is_op(Operator::Addition) {
+                            break;
+                        }
+                        self.advance();
+                    }
+                }
                 Ok(TType::Generic { name: generictype })
             }
             Token::Symbol { symbol: '?', .. } => {
@@ -3169,16 +3596,9 @@ impl Parser {
                 ));
             }
         };
-        let (line, row) = self.get_line_and_row();
+        let position = self.get_current_token_position();
         self.advance();
-        Ok((
-            identifier,
-            FilePosition {
-                line,
-                row,
-                filepath: self.filepath.clone(),
-            },
-        ))
+        Ok((identifier, position))
     }
 
     fn parameter_list(&mut self) -> Result<Vec<(TType, String)>, NovaError> {
@@ -3245,6 +3665,7 @@ impl Parser {
     }
 
     fn alternative(&mut self) -> Result<Vec<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         let test = self.top_expr()?;
         let pos = self.get_current_token_position();
         if test.get_type() != TType::Bool {
@@ -3268,11 +3689,17 @@ impl Parser {
             test,
             body: statements,
             alternative,
+            position,
         }])
     }
 
     fn import_file(&mut self) -> Result<Option<Statement>, NovaError> {
         self.consume_identifier(Some("import"))?;
+        if self.current_token().is_id("native") {
+            if let Some(Token::String { .. }) = self.peek_offset(1) {
+                return self.import_native();
+            }
+        }
         let import_filepath = match self.current_token() {
             Token::String {
                 value: filepath, ..
@@ -3305,28 +3732,57 @@ impl Parser {
             }
             _ => panic!(),
         };
-        let resolved_filepath: String = match extract_current_directory(&self.filepath) {
-            Some(mut current_dir) => {
-                current_dir.push_str(&import_filepath);
-                current_dir
-            }
-            None => import_filepath.clone(),
-        };
-        let tokens = Lexer::new(&resolved_filepath)?.tokenize()?;
+        let resolved_filepath = self.resolver.resolve(&self.filepath, &import_filepath)?;
+        let source = self.resolver.load(&resolved_filepath)?;
+        let tokens = Lexer::from_source(&resolved_filepath, &source).tokenize()?;
         let mut parser = self.clone();
         parser.index = 0;
         parser.filepath = resolved_filepath.clone();
         parser.input = tokens;
-        parser.parse()?;
+        // errors of the imported file are reported along with our own
+        let _ = parser.parse();
+        self.errors.append(&mut parser.errors);
+        self.warnings.append(&mut parser.warnings);
         self.environment = parser.environment.clone();
         self.modules = parser.modules.clone();
+        self.symbols = parser.symbols.clone();
         Ok(Some(Statement::Block {
             body: parser.ast.program.clone(),
             filepath: resolved_filepath,
         }))
     }
 
+    // import native "name" loads a plugin, its functions are natives like any other
+    fn import_native(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
+        self.consume_identifier(Some("native"))?;
+        let name = match self.current_token() {
+            Token::String { value, .. } => value,
+            _ => unreachable!(),
+        };
+        self.advance();
+        let natives = match self.plugins.load(&self.filepath, &name) {
+            Ok(natives) => natives,
+            Err(error) => {
+                return Err(self.generate_error_with_pos(
+                    format!("cannot import native module '{name}'"),
+                    error.message().trim().to_string(),
+                    position,
+                ))
+            }
+        };
+        for (function_id, function_type, function_kind) in natives {
+            if let Some((module, _)) = function_id.split_once("::") {
+                self.modules.insert(module.to_string());
+            }
+            self.environment
+                .insert_symbol(&function_id, function_type, None, function_kind);
+        }
+        Ok(None)
+    }
+
     fn unwrap(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("unwrap"))?;
         let (identifier, pos) = self.get_identifier()?;
         // test if option type
@@ -3352,6 +3808,7 @@ impl Parser {
                     identifier,
                     body,
                     alternative,
+                    position,
                 }));
             } else {
                 return Err(self.generate_error_with_pos(
@@ -3370,187 +3827,100 @@ impl Parser {
     }
 
     fn match_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("match"))?;
         let expr = self.expr()?;
-
-        if let Some(_) = expr.get_type().custom_to_string() {
-        } else {
-            return Err(self.generate_error_with_pos(
-                format!("Match statement expects an enum type"),
-                format!("got {}", expr.get_type().to_string()),
-                self.get_current_token_position(),
-            ));
-        }
+        let ttype = expr.get_type();
 
         let pos = self.get_current_token_position();
-        let mut branches = vec![];
+        let mut arms = vec![];
+        // the patterns of the arms without a guard, an arm whose values
+        // they all match already can never run
+        let mut rows = vec![];
         self.consume_symbol('{')?;
-        let mut default_branch = None;
         while !self.current_token().is_symbol('}') {
-            let (variant, pos) = self.get_identifier()?;
-            if variant == "_" {
-                // check to see if default branch is already defined
-                if default_branch.is_some() {
-                    return Err(self.generate_error_with_pos(
-                        format!("default branch already defined"),
-                        format!("make sure only one default branch is defined"),
-                        pos,
-                    ));
-                }
-                self.consume_operator(Operator::RightArrow)?;
-                default_branch = Some(self.block()?);
-                continue;
-            }
-            // collect identifiers
-            let mut enum_id = String::new();
-            if self.current_token().is_symbol('(') {
-                self.consume_symbol('(')?;
-                if !self.current_token().is_symbol(')') {
-                    enum_id = self.get_identifier()?.0;
-                }
-                self.consume_symbol(')')?;
-            }
-            self.consume_operator(Operator::RightArrow)?;
-
-            if let Some(fields) = self
-                .environment
-                .custom_types
-                .get(&expr.get_type().custom_to_string().unwrap())
+            let start = self.get_current_token_position();
+            let mut bindings = vec![];
+            let pattern = self.pattern(&ttype, &mut bindings)?;
+            if self
+                .useful(
+                    &rows,
+                    std::slice::from_ref(&pattern),
+                    std::slice::from_ref(&ttype),
+                )
+                .is_none()
             {
-                let new_fields = if let Some(x) = self
-                    .environment
-                    .generic_type_struct
-                    .get(&expr.get_type().custom_to_string().unwrap())
-                {
-                    let TType::Custom { type_params, .. } = expr.get_type() else {
-                        panic!("not a custom type")
-                    };
-                    //dbg!(&fields);
-                    fields
-                        .iter()
-                        .map(|(name, ttype)| {
-                            let new_ttype = self.replace_generic_types(ttype, x, &type_params);
-                            (name.clone(), new_ttype)
-                        })
-                        .collect::<Vec<(String, TType)>>()
-                } else {
-                    fields.clone()
-                };
-                let mut tag = 0;
-                // mark if the variant is found
-                let mut found = false;
-                let mut vtype = TType::None;
-
-                for (i, field) in new_fields.iter().enumerate() {
-                    //dbg!(field);
-                    if variant == field.0 {
-                        tag = i;
-                        vtype = field.1.clone();
-                        found = true;
-                    }
-                }
-
-                if vtype != TType::None {
-                    if enum_id.is_empty() {
-                        return Err(self.generate_error_with_pos(
-                            format!("variant '{}' is missing Identifier", variant),
-                            format!("Variant(id), id is missing"),
-                            pos,
-                        ));
-                    }
-                }
+                self.warnings.push(NovaError::Warning {
+                    msg: "unreachable match arm".to_string(),
+                    note: "the arms above already match every value this pattern does".to_string(),
+                    position: self.span_from(&start),
+                });
+            }
 
-                if !found {
+            self.environment.push_block();
+            for (identifier, ttype, pos) in bindings {
+                self.environment
+                    .insert_symbol(&identifier, ttype, Some(pos), SymbolKind::Variable);
+            }
+            let guard = if self.current_token().is_id("if") {
+                self.consume_identifier(Some("if"))?;
+                let guardpos = self.get_current_token_position();
+                self.guard = true;
+                let guard = self.top_expr();
+                self.guard = false;
+                let guard = guard?;
+                if guard.get_type() != TType::Bool {
                     return Err(self.generate_error_with_pos(
-                        format!("variant '{}' not found in type", variant),
-                        format!("make sure the variant is in the type"),
-                        pos,
+                        "match guard must return a bool".to_string(),
+                        format!("got {}", guard.get_type().to_string()),
+                        self.span_from(&guardpos),
                     ));
                 }
+                Some(guard)
+            } else {
+                None
+            };
+            self.consume_operator(Operator::RightArrow)?;
+            // get expression if no { }
+            let body = if self.current_token().is_symbol('{') {
+                self.block()?
+            } else {
+                let position = self.get_current_token_position();
+                let body = self.expr()?;
+                vec![Statement::Expression {
+                    ttype: body.get_type(),
+                    expr: body,
+                    position,
+                }]
+            };
+            self.environment.pop_block();
 
-                self.environment.push_block();
-                self.environment
-                    .insert_symbol(&enum_id, vtype, None, SymbolKind::Variable);
-                // get expression if no { }
-
-                let body = if self.current_token().is_symbol('{') {
-                    let body = self.block()?;
-                    branches.push((tag, Some(enum_id.clone()), body.clone()));
-                    body.clone()
-                } else {
-                    let body = self.expr()?;
-                    branches.push((
-                        tag,
-                        Some(enum_id.clone()),
-                        vec![Statement::Expression {
-                            ttype: body.clone().get_type(),
-                            expr: body.clone(),
-                        }],
-                    ));
-                    vec![Statement::Expression {
-                        ttype: body.clone().get_type(),
-                        expr: body,
-                    }]
-                };
-
-                self.environment.pop_block();
-                if enum_id.is_empty() {
-                    branches.push((tag, None, body));
-                } else {
-                    branches.push((tag, Some(enum_id.clone()), body));
-                }
+            if guard.is_none() {
+                rows.push(vec![pattern.clone()]);
             }
+            arms.push(Arm {
+                pattern,
+                guard,
+                body,
+            });
         }
         self.consume_symbol('}')?;
 
-        if let Some(_) = default_branch.clone() {
-        } else {
-            // check to see if all variants are covered
-            let mut covered = vec![];
-            for (tag, _, _) in branches.clone() {
-                covered.push(tag);
-            }
-            if let Some(fields) = self
-                .environment
-                .custom_types
-                .get(&expr.get_type().custom_to_string().unwrap())
-            {
-                let new_fields = if let Some(x) = self
-                    .environment
-                    .generic_type_struct
-                    .get(&expr.get_type().custom_to_string().unwrap())
-                {
-                    let TType::Custom { type_params, .. } = expr.get_type() else {
-                        panic!("not a custom type")
-                    };
-                    //dbg!(&fields);
-                    fields
-                        .iter()
-                        .map(|(name, ttype)| {
-                            let new_ttype = self.replace_generic_types(ttype, x, &type_params);
-                            (name.clone(), new_ttype)
-                        })
-                        .collect::<Vec<(String, TType)>>()
-                } else {
-                    fields.clone()
-                };
-                for (i, field) in new_fields.iter().enumerate() {
-                    if field.0 != "type" && !covered.contains(&i) {
-                        return Err(self.generate_error_with_pos(
-                            format!("variant '{}' is not covered", field.0),
-                            format!("make sure all variants are covered"),
-                            pos,
-                        ));
-                    }
-                }
-            }
+        if let Some(missing) =
+            self.useful(&rows, &[Pattern::Wildcard], std::slice::from_ref(&ttype))
+        {
+            return Err(self.generate_error_with_pos(
+                format!("{} is not covered", pattern::describe(&missing[0])),
+                "make sure every value is matched, or add a _ arm".to_string(),
+                pos,
+            ));
         }
 
         Ok(Some(Statement::Match {
             ttype: TType::Void,
             expr,
-            arms: branches,
-            default: default_branch,
+            arms,
+            position,
         }))
     }
 
@@ -3568,6 +3938,8 @@ impl Parser {
                 "return" => self.return_statement(),
                 "fn" => self.function_declaration(),
                 "enum" => self.enum_declaration(),
+                "trait" => self.trait_declaration(),
+                "impl" => self.impl_declaration(),
                 "for" => self.for_statement(),
                 "break" => {
                     self.consume_identifier(Some("break"))?;
@@ -3747,7 +4119,10 @@ impl Parser {
         Ok(Some(Statement::Enum {
             ttype: TType::Custom {
                 name: enum_name.clone(),
-                type_params: vec![],
+                type_params: generic_field_names
+                    .iter()
+                    .map(|x| TType::Generic { name: x.clone() })
+                    .collect(),
             },
             identifier: enum_name,
             fields: field_definitions,
@@ -3855,14 +4230,201 @@ impl Parser {
         Ok(Some(Statement::Struct {
             ttype: TType::Custom {
                 name: struct_name.clone(),
-                type_params: vec![],
+                type_params: generic_field_names
+                    .iter()
+                    .map(|x| TType::Generic { name: x.clone() })
+                    .collect(),
             },
             identifier: struct_name,
             fields: field_definitions,
         }))
     }
 
+    // trait Show { fn show(self: $Self) -> String } lists the methods a type
+    // needs to conform, $Self stands for the type that implements them
+    fn trait_declaration(&mut self) -> Result<Option<Statement>, NovaError> {
+        self.consume_identifier(Some("trait"))?;
+        let (trait_name, position) = self.get_identifier()?;
+        if self.environment.traits.contains_key(&trait_name)
+            || self.environment.custom_types.contains_key(&trait_name)
+        {
+            return Err(self.generate_error_with_pos(
+                format!("Trait '{}' is already defined", trait_name),
+                "Cannot redefine a trait or reuse the name of a type".to_string(),
+                position,
+            ));
+        }
+        self.consume_symbol('{')?;
+        let mut methods: Vec<(String, TType)> = vec![];
+        while self.current_token().is_id("fn") {
+            self.advance();
+            let (method, method_position) = self.get_identifier()?;
+            self.consume_symbol('(')?;
+            let parameters = self.parameter_list()?;
+            self.consume_symbol(')')?;
+            let mut output = TType::Void;
+            if self.current_token().is_op(Operator::RightArrow) {
+                self.consume_operator(Operator::RightArrow)?;
+                output = self.ttype()?;
+            }
+            if methods.iter().any(|(name, _)| *name == method) {
+                return Err(self.generate_error_with_pos(
+                    format!("Method {method} is already part of trait {trait_name}"),
+                    "Cannot declare a method twice".to_string(),
+                    method_position,
+                ));
+            }
+            match parameters.first() {
+                Some((TType::Generic { name }, _)) if name == "Self" => {}
+                _ => {
+                    return Err(self.generate_error_with_pos(
+                        format!("Method {method} of trait {trait_name} needs a receiver"),
+                        "The first parameter of a trait method has type $Self".to_string(),
+                        method_position,
+                    ))
+                }
+            }
+            methods.push((
+                method,
+                TType::Function {
+                    parameters: parameters.into_iter().map(|(ttype, _)| ttype).collect(),
+                    return_type: Box::new(output),
+                },
+            ));
+        }
+        self.consume_symbol('}')?;
+        self.environment.traits.insert(trait_name, methods);
+        Ok(None)
+    }
+
+    // impl Show for Point { fn show(self: Point) -> String {..} } declares the
+    // methods of the trait as extends functions of the type. methods that
+    // were declared before the block count too, but every one has to be there
+    fn impl_declaration(&mut self) -> Result<Option<Statement>, NovaError> {
+        self.consume_identifier(Some("impl"))?;
+        let (trait_name, position) = self.get_identifier()?;
+        let Some(methods) = self.environment.traits.get(&trait_name).cloned() else {
+            return Err(self.generate_error_with_pos(
+                format!("Trait {trait_name} does not exist"),
+                "Declare the trait before implementing it".to_string(),
+                position,
+            ));
+        };
+        self.consume_identifier(Some("for"))?;
+        let type_position = self.get_current_token_position();
+        let ttype = self.ttype()?;
+        let Some(prefix) = ttype.extends_prefix() else {
+            return Err(self.generate_error_with_pos(
+                format!("Cannot implement {trait_name} for {}", ttype.to_string()),
+                "Traits are implemented for named types, lists, options, tuples and functions"
+                    .to_string(),
+                type_position,
+            ));
+        };
+        if self
+            .environment
+            .implementations
+            .get(&trait_name)
+            .is_some_and(|implementations| {
+                implementations
+                    .iter()
+                    .any(|implementation| implementation.ttype == ttype)
+            })
+        {
+            return Err(self.generate_error_with_pos(
+                format!("{} already implements {trait_name}", ttype.to_string()),
+                "A type implements a trait only once".to_string(),
+                type_position,
+            ));
+        }
+
+        self.consume_symbol('{')?;
+        let mut body = vec![];
+        while !self.current_token().is_symbol('}') && !self.is_current_eof() {
+            let start = self.get_current_token_position();
+            if !self.current_token().is_id("fn") {
+                return Err(self.generate_error_with_pos(
+                    "Expected a method".to_string(),
+                    format!("An impl block only holds the methods of {trait_name}"),
+                    start,
+                ));
+            }
+            let method = self
+                .peek_offset(1)
+                .and_then(|token| token.expect_id())
+                .unwrap_or_default();
+            if !methods.iter().any(|(name, _)| *name == method) {
+                return Err(self.generate_error_with_pos(
+                    format!("{method} is not a method of trait {trait_name}"),
+                    "Declare other functions outside of the impl block".to_string(),
+                    start,
+                ));
+            }
+            self.implementing = true;
+            if let Some(function) = self.function_declaration()? {
+                body.push(function);
+            }
+        }
+        self.consume_symbol('}')?;
+
+        // every method needs the signature of the trait with $Self filled in
+        let mut functions = vec![];
+        for (method, method_type) in methods.iter() {
+            let expected = self.replace_generic_types(
+                method_type,
+                &["Self".to_string()],
+                std::slice::from_ref(&ttype),
+            );
+            let TType::Function { parameters, .. } = &expected else {
+                continue;
+            };
+            match self
+                .environment
+                .get_function_type(&format!("{prefix}::{method}"), parameters)
+            {
+                Some((found, function_id, _)) if found == expected => {
+                    functions.push((function_id, found))
+                }
+                Some((found, ..)) => {
+                    return Err(self.generate_error_with_pos(
+                        format!(
+                            "Method {method} does not match trait {trait_name} for {}",
+                            ttype.to_string()
+                        ),
+                        format!(
+                            "Expected {}, found {}",
+                            expected.to_string(),
+                            found.to_string()
+                        ),
+                        position,
+                    ))
+                }
+                None => {
+                    return Err(self.generate_error_with_pos(
+                        format!(
+                            "{} does not implement method {method} of trait {trait_name}",
+                            ttype.to_string()
+                        ),
+                        format!("Missing fn {method}: {}", expected.to_string()),
+                        position,
+                    ))
+                }
+            }
+        }
+        self.environment
+            .implementations
+            .entry(trait_name)
+            .or_default()
+            .push(Implementation { ttype, functions });
+
+        Ok(Some(Statement::Block {
+            body,
+            filepath: self.filepath.clone(),
+        }))
+    }
+
     fn for_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("for"))?;
 
         if let Some(Token::Keyword {
@@ -3909,6 +4471,7 @@ impl Parser {
                         end: end_range,
                         inclusive: true,
                         step: None,
+                        position,
                     }))
                 }
                 Token::Operator {
@@ -3934,6 +4497,7 @@ impl Parser {
                         end: end_range,
                         inclusive: false,
                         step: None,
+                        position,
                     }))
                 }
                 _ => {
@@ -3960,6 +4524,7 @@ impl Parser {
                         identifier,
                         expr: array,
                         body,
+                        position,
                     }))
                 }
             }
@@ -3986,11 +4551,13 @@ impl Parser {
                 test,
                 inc,
                 body,
+                position,
             }))
         }
     }
 
     fn while_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("while"))?;
         let testpos = self.get_current_token_position();
         let test = self.top_expr()?;
@@ -4008,10 +4575,12 @@ impl Parser {
         Ok(Some(Statement::While {
             test,
             body: statements,
+            position,
         }))
     }
 
     fn if_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("if"))?;
 
         if self.current_token().is_id("let") {
@@ -4065,6 +4634,7 @@ impl Parser {
                     body,
                     alternative,
                     global,
+                    position,
                 }))
             }
         } else {
@@ -4092,11 +4662,13 @@ impl Parser {
                 test,
                 body,
                 alternative,
+                position,
             }))
         }
     }
 
     fn let_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("let"))?;
         let mut global = false;
         // refactor out into two parsing ways for ident. one with module and one without
@@ -4113,13 +4685,69 @@ impl Parser {
             (identifier, pos) = self.get_identifier()?;
             global = true
         }
-        #[allow(unused_assignments)]
-        let mut ttype = TType::None;
-        #[allow(unused_assignments)]
-        let mut expr = Expr::None;
-        if self.current_token().is_op(Operator::Colon) {
+        let annotation = if self.current_token().is_op(Operator::Colon) {
             self.consume_operator(Operator::Colon)?;
-            ttype = self.ttype()?;
+            let annotation_start = self.get_current_token_position();
+            match self.ttype() {
+                Ok(ttype) => Some((ttype, self.span_from(&annotation_start))),
+                Err(error) => {
+                    return Err(self.declare_failed(&identifier, TType::Any, &pos, error))
+                }
+            }
+        } else {
+            None
+        };
+        let (ttype, expr) = match self.let_value(&identifier, &pos, annotation.clone()) {
+            Ok(value) => value,
+            Err(error) => {
+                let ttype = annotation.map_or(TType::Any, |(ttype, _)| ttype);
+                return Err(self.declare_failed(&identifier, ttype, &pos, error));
+            }
+        };
+
+        // make sure symbol doesnt already exist
+        if self.environment.has(&identifier) {
+            //dbg!(&self.environment);
+            let declared = self
+                .environment
+                .find(&identifier)
+                .and_then(|symbol| symbol.pos.clone())
+                .map(|declared| vec![("first declared here".to_string(), declared)]);
+            return Err(NovaError::Parsing {
+                msg: format!("Symbol '{}' is already instantiated", identifier),
+                note: "Cannot reinstantiate the same symbol in the same scope".to_string(),
+                position: pos.clone(),
+                extra: declared,
+            });
+        } else {
+            self.environment.insert_symbol(
+                &identifier,
+                ttype.clone(),
+                Some(pos.clone()),
+                SymbolKind::Variable,
+            );
+            self.record(&identifier, &identifier, &ttype, &pos);
+            Ok(Some(Statement::Let {
+                ttype,
+                identifier,
+                expr,
+                global,
+                position,
+            }))
+        }
+    }
+
+    // the value of a let, checked against its annotation
+    fn let_value(
+        &mut self,
+        identifier: &str,
+        pos: &FilePosition,
+        annotation: Option<(TType, FilePosition)>,
+    ) -> Result<(TType, Expr), NovaError> {
+        let ttype;
+        let expr;
+        if let Some((annotated, annotation)) = annotation {
+            ttype = annotated;
             self.consume_operator(Operator::Assignment)?;
             expr = self.expr()?;
             match (
@@ -4138,15 +4766,19 @@ impl Parser {
             ) {
                 (Ok(_), Ok(_)) => {}
                 _ => {
-                    return Err(self.generate_error_with_pos(
-                        format!(
+                    return Err(NovaError::Parsing {
+                        msg: format!(
                             "Cannot assign {} to {}",
                             expr.get_type().to_string(),
                             ttype.to_string()
                         ),
-                        "Make sure the expression returns the givin type".to_string(),
-                        pos.clone(),
-                    ));
+                        note: "Make sure the expression returns the givin type".to_string(),
+                        position: expr.get_position().unwrap_or(pos.clone()),
+                        extra: Some(vec![(
+                            "expected because of this annotation".to_string(),
+                            annotation,
+                        )]),
+                    });
                 }
             }
         } else {
@@ -4160,42 +4792,225 @@ impl Parser {
             return Err(self.generate_error_with_pos(
                 format!("Variable '{}' cannot be assinged to void", identifier),
                 "Make sure the expression returns a value".to_string(),
-                pos.clone(),
+                expr.get_position().unwrap_or(pos.clone()),
             ));
         }
-        // make sure symbol doesnt already exist
-        if self.environment.has(&identifier) {
-            //dbg!(&self.environment);
-            return Err(self.generate_error_with_pos(
-                format!("Symbol '{}' is already instantiated", identifier),
-                "Cannot reinstantiate the same symbol in the same scope".to_string(),
-                pos.clone(),
-            ));
-        } else {
+        Ok((ttype, expr))
+    }
+
+    // a let that fails still declares its name, with the annotated type
+    // when there is one, so later uses are not reported as unknown
+    fn declare_failed(
+        &mut self,
+        identifier: &str,
+        ttype: TType,
+        pos: &FilePosition,
+        error: NovaError,
+    ) -> NovaError {
+        if !self.environment.has(identifier) {
             self.environment.insert_symbol(
-                &identifier,
-                ttype.clone(),
+                identifier,
+                ttype,
                 Some(pos.clone()),
                 SymbolKind::Variable,
             );
-            Ok(Some(Statement::Let {
-                ttype,
-                identifier,
-                expr,
-                global,
-            }))
         }
+        error
     }
 
     fn return_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.consume_identifier(Some("return"))?;
         let expr = self.expr()?;
         Ok(Some(Statement::Return {
             ttype: expr.get_type(),
             expr,
+            position,
         }))
     }
 
+    // the hidden parameters of a function with trait bounds, one for every
+    // method of every bound, with $Self standing for the bounded generic
+    fn dictionary(&self, bounds: &[(String, String)]) -> Vec<(String, TType)> {
+        let mut dictionary = vec![];
+        for (generic, trait_name) in bounds.iter() {
+            let methods = self.environment.traits.get(trait_name).cloned();
+            for (method, ttype) in methods.unwrap_or_default() {
+                dictionary.push((
+                    format!("${generic}:{trait_name}::{method}"),
+                    self.replace_generic_types(
+                        &ttype,
+                        &["Self".to_string()],
+                        &[TType::Generic {
+                            name: generic.clone(),
+                        }],
+                    ),
+                ));
+            }
+        }
+        dictionary
+    }
+
+    // the functions that make a type conform to a trait, in the order the
+    // trait lists its methods. they are passed to functions bounded by it
+    fn conformance(
+        &mut self,
+        ttype: &TType,
+        trait_name: &str,
+        function: &str,
+        pos: FilePosition,
+    ) -> Result<Vec<Expr>, NovaError> {
+        if let TType::Generic { name } = ttype {
+            // a generic with the same bound hands on the methods it was given
+            let bounded = self
+                .environment
+                .live_bounds
+                .iter()
+                .flatten()
+                .any(|(generic, bound)| generic == name && bound == trait_name);
+            if bounded {
+                let methods = self.environment.traits.get(trait_name).cloned();
+                return methods
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|(method, _)| self.bound_method(name, trait_name, method, pos.clone()))
+                    .collect();
+            }
+        } else if let Some(implementation) = self
+            .environment
+            .implementations
+            .get(trait_name)
+            .and_then(|implementations| {
+                implementations.iter().find(|implementation| {
+                    self.check_and_map_types(
+                        std::slice::from_ref(&implementation.ttype),
+                        std::slice::from_ref(ttype),
+                        &mut HashMap::default(),
+                        pos.clone(),
+                    )
+                    .is_ok()
+                })
+            })
+        {
+            return Ok(implementation
+                .functions
+                .iter()
+                .map(|(function_id, function_type)| {
+                    self.create_literal_expr(
+                        function_id.clone(),
+                        function_type.clone(),
+                        pos.clone(),
+                    )
+                })
+                .collect());
+        }
+        Err(self.generate_error_with_pos(
+            format!(
+                "{} does not implement trait {trait_name}",
+                ttype.to_string()
+            ),
+            format!("{function} only accepts types that implement {trait_name}"),
+            pos,
+        ))
+    }
+
+    // the hidden parameter holding a method of a bound, captured like any
+    // other parameter when a closure uses it
+    fn bound_method(
+        &mut self,
+        generic: &str,
+        trait_name: &str,
+        method: &str,
+        pos: FilePosition,
+    ) -> Result<Expr, NovaError> {
+        let name = format!("${generic}:{trait_name}::{method}");
+        if let Some(ttype) = self.environment.get_type(&name) {
+            return Ok(self.create_literal_expr(name, ttype, pos));
+        }
+        let Some((ttype, _, kind)) = self.environment.get_type_capture(&name) else {
+            return Err(self.generate_error_with_pos(
+                format!("Method {method} of ${generic} is not available here"),
+                format!("${generic} is not bounded by {trait_name} in this function"),
+                pos,
+            ));
+        };
+        self.environment.captured.last_mut().unwrap().insert(
+            name.clone(),
+            Symbol {
+                id: name.clone(),
+                ttype: ttype.clone(),
+                pos: Some(pos.clone()),
+                kind: SymbolKind::Captured,
+            },
+        );
+        self.environment
+            .insert_symbol(&name, ttype.clone(), Some(pos.clone()), kind);
+        Ok(self.create_literal_expr(name, ttype, pos))
+    }
+
+    // value.method() on a generic value calls the method one of its trait
+    // bounds provides
+    fn trait_method_call(
+        &mut self,
+        generic: &str,
+        method: &str,
+        arguments: Vec<Expr>,
+        argument_types: Vec<TType>,
+        pos: FilePosition,
+    ) -> Result<Expr, NovaError> {
+        let mut traits = self
+            .environment
+            .live_bounds
+            .iter()
+            .flatten()
+            .filter(|(bounded, trait_name)| {
+                bounded == generic
+                    && self
+                        .environment
+                        .traits
+                        .get(trait_name)
+                        .is_some_and(|methods| methods.iter().any(|(name, _)| name == method))
+            })
+            .map(|(_, trait_name)| trait_name.clone())
+            .collect::<Vec<String>>();
+        traits.dedup();
+        let trait_name = match traits.as_slice() {
+            [trait_name] => trait_name.clone(),
+            [] => {
+                return Err(self.generate_error_with_pos(
+                    format!("E1 Not a valid call: {}", method),
+                    format!("${generic} has no trait bound with a method {method}"),
+                    pos,
+                ))
+            }
+            _ => {
+                return Err(self.generate_error_with_pos(
+                    format!("Method {method} of ${generic} is ambiguous"),
+                    format!("It is a method of {}", traits.join(" and ")),
+                    pos,
+                ))
+            }
+        };
+        let function = self.bound_method(generic, &trait_name, method, pos.clone())?;
+        let TType::Function {
+            parameters,
+            return_type,
+        } = function.get_type()
+        else {
+            unreachable!()
+        };
+        let mut type_map = HashMap::default();
+        self.check_and_map_types(&parameters, &argument_types, &mut type_map, pos.clone())?;
+        let return_type = self.get_output(*return_type, &mut type_map, pos.clone())?;
+        Ok(Expr::Call {
+            ttype: return_type,
+            name: method.to_string(),
+            function: Box::new(function),
+            args: arguments,
+            position: pos,
+        })
+    }
+
     fn is_generic(&self, params: &[TType]) -> bool {
         for param in params {
             match param {
@@ -4237,6 +5052,7 @@ impl Parser {
 
     fn function_declaration(&mut self) -> Result<Option<Statement>, NovaError> {
         self.consume_identifier(Some("fn"))?;
+        let implementing = std::mem::take(&mut self.implementing);
 
         let mut is_extended = false;
         let mut is_mod = false;
@@ -4276,17 +5092,24 @@ impl Parser {
             }
             self.consume_symbol(')')?;
             is_mod = true;
+        } else if implementing {
+            // methods of an impl block extend the type of their first parameter
+            get_first = true;
         }
 
         let (mut identifier, pos) = self.get_identifier()?;
+        let name = identifier.clone();
 
         if is_extended || is_mod {
             identifier = format!("{}::{}", custom_type, identifier);
         }
 
-        // get parameters
+        // get parameters, the generics in them may carry trait bounds
         self.consume_symbol('(')?;
-        let parameters = self.parameter_list()?;
+        self.bounds = Some(vec![]);
+        let parameters = self.parameter_list();
+        let bounds = self.bounds.take().unwrap_or_default();
+        let parameters = parameters?;
         //dbg!(&parameters);
         self.consume_symbol(')')?;
         // get output type
@@ -4294,40 +5117,11 @@ impl Parser {
         if !is_extended && get_first {
             //println!("{} {}", identifier, parameters.len());
             if let Some((ttype, _)) = parameters.first() {
-                match ttype {
-                    TType::Custom { name, .. } => {
-                        identifier = format!("{}::{}", name, identifier);
-                    }
-                    TType::List { .. } => {
-                        identifier = format!("List::{}", identifier);
-                    }
-                    TType::Option { .. } => {
-                        identifier = format!("Option::{}", identifier);
-                    }
-                    TType::Function { parameters, .. } => {
-                        let repeated_elements: String = "(_)".repeat(parameters.len());
-                        identifier = format!("Function{}::{}", repeated_elements, identifier);
-                    }
-                    TType::Tuple { elements } => {
-                        let repeated_elements: String = "(_)".repeat(elements.len());
-                        identifier = format!("Tuple{}::{}", repeated_elements, identifier);
-                    }
-                    TType::Bool => {
-                        identifier = format!("Bool::{}", identifier);
-                    }
-                    TType::Int => {
-                        identifier = format!("Int::{}", identifier);
+                match ttype.extends_prefix() {
+                    Some(prefix) => {
+                        identifier = format!("{}::{}", prefix, identifier);
                     }
-                    TType::Float => {
-                        identifier = format!("Float::{}", identifier);
-                    }
-                    TType::String => {
-                        identifier = format!("String::{}", identifier);
-                    }
-                    TType::Char => {
-                        identifier = format!("Char::{}", identifier);
-                    }
-                    _ => {
+                    None => {
                         // error
                         return Err(self.generate_error_with_pos(
                             format!("Cannot extend from type"),
@@ -4450,13 +5244,28 @@ impl Parser {
                 Some(pos.clone()),
                 SymbolKind::GenericFunction,
             );
+            if !bounds.is_empty() {
+                self.environment
+                    .bounds
+                    .insert(identifier.clone(), bounds.clone());
+            }
         }
+        self.record(
+            &name,
+            &identifier,
+            &TType::Function {
+                parameters: typeinput.clone(),
+                return_type: Box::new(output.clone()),
+            },
+            &pos,
+        );
 
         //dbg!(self.environment.values.clone());
         self.environment.no_override.insert(identifier.clone());
         let mut generic_list = self.collect_generics(&typeinput);
         generic_list.extend(self.collect_generics(&vec![output.clone()]));
         self.environment.live_generics.push(generic_list.clone());
+        self.environment.live_bounds.push(bounds.clone());
         //dbg!(generic_list);
         // parse body with scope
         self.environment.push_scope();
@@ -4486,6 +5295,22 @@ impl Parser {
             }
         }
 
+        // every method of every bound comes in as a hidden parameter after the others
+        let dictionary = self.dictionary(&bounds);
+        for (name, ttype) in dictionary.iter() {
+            self.environment.insert_symbol(
+                name,
+                ttype.clone(),
+                Some(pos.clone()),
+                SymbolKind::Parameter,
+            );
+            input.push(Arg {
+                identifier: name.clone(),
+                ttype: ttype.clone(),
+            });
+        }
+
+        let body_errors = self.errors.len();
         let mut statements = self.block()?;
 
         // capture variables -----------------------------------
@@ -4500,6 +5325,7 @@ impl Parser {
 
         self.environment.pop_scope();
         self.environment.live_generics.pop();
+        self.environment.live_bounds.pop();
         for c in captured.iter() {
             if let Some(mc) = self.environment.get_type_capture(&c.clone()) {
                 let pos = self.get_current_token_position();
@@ -4537,6 +5363,7 @@ impl Parser {
                     .collect();
             }
         }
+        captured.retain(|name| !dictionary.iter().any(|(hidden, _)| hidden == name));
 
         for dc in captured.iter() {
             if let Some(v) = self.environment.values.last().unwrap().get(dc) {
@@ -4556,12 +5383,14 @@ impl Parser {
                 statements.push(Statement::Return {
                     ttype: output.clone(),
                     expr: Expr::None,
+                    position: pos.clone(),
                 });
             }
         }
-        // if last statement isnt a return error
+        // if last statement isnt a return error, a return that failed to
+        // parse was already reported
         let (_, has_return) = self.check_returns(&statements, output.clone(), pos.clone())?;
-        if !has_return && output != TType::Void {
+        if !has_return && output != TType::Void && self.errors.len() == body_errors {
             if let Some(Statement::Pass) = statements.last() {
                 // do nothing
             } else {
@@ -4595,7 +5424,8 @@ impl Parser {
             .try_fold(false, |has_return, statement| match statement {
                 Statement::Pass => Ok(true),
                 Statement::Return { ttype, .. } => {
-                    if ttype != &return_type {
+                    // values of a failed let were already reported
+                    if ttype != &return_type && ttype != &TType::Any {
                         Err(self.generate_error_with_pos(
                             "Return type does not match function return type".to_string(),
                             format!(
@@ -4655,18 +5485,13 @@ impl Parser {
                         None => Ok(body_has_return),
                     }
                 }
-                Statement::Match { arms, default, .. } => {
+                Statement::Match { arms, .. } => {
                     let mut has_return = has_return;
                     for arm in arms {
                         let (_, arm_has_return) =
-                            self.check_returns(&arm.2, return_type.clone(), pos.clone())?;
+                            self.check_returns(&arm.body, return_type.clone(), pos.clone())?;
                         has_return = has_return && arm_has_return;
                     }
-                    if let Some(default) = default {
-                        let (_, default_has_return) =
-                            self.check_returns(default, return_type.clone(), pos.clone())?;
-                        has_return = has_return && default_has_return;
-                    }
                     Ok(has_return)
                 }
                 _ => Ok(has_return),
@@ -4675,10 +5500,12 @@ impl Parser {
     }
 
     fn expression_statement(&mut self) -> Result<Option<Statement>, NovaError> {
+        let position = self.get_current_token_position();
         self.expr().map(|expr| {
             Some(Statement::Expression {
                 ttype: expr.get_type(),
                 expr,
+                position,
             })
         })
     }
@@ -4703,11 +5530,17 @@ impl Parser {
         statements.split_last().map_or_else(
             || Err(error.clone()),
             |(last, initial_statements)| {
-                if let Statement::Expression { ttype, expr, .. } = last {
+                if let Statement::Expression {
+                    ttype,
+                    expr,
+                    position,
+                } = last
+                {
                     let mut final_statements = initial_statements.to_vec();
                     final_statements.push(Statement::Return {
                         ttype: ttype.clone(),
                         expr: expr.clone(),
+                        position: position.clone(),
                     });
                     Ok(final_statements)
                 } else {
@@ -4724,9 +5557,86 @@ impl Parser {
         Ok(statements)
     }
 
+    // parses a statement, when it fails the error is kept and parsing
+    // resumes at the start of the next statement
+    fn recovering_statement(&mut self) -> Option<Statement> {
+        let start = self.index;
+        let scopes = (
+            self.environment.values.len(),
+            self.environment.captured.len(),
+            self.environment.live_generics.len(),
+        );
+        match self.statement() {
+            Ok(mut statement) => {
+                if let Some(statement) = statement.as_mut() {
+                    if let Some(position) = statement.get_position() {
+                        statement.extend_position(&self.span_from(&position));
+                    }
+                }
+                statement
+            }
+            Err(error) => {
+                self.errors.push(error);
+                // scopes the failed statement opened are never closed
+                self.environment.values.truncate(scopes.0);
+                self.environment.captured.truncate(scopes.1);
+                self.environment.live_generics.truncate(scopes.2);
+                self.synchronize(start);
+                None
+            }
+        }
+    }
+
+    // skips the rest of a broken statement, starting over from its first token
+    // so brackets it opened are matched. a statement ends at a ';', before the
+    // '}' of the enclosing block or at the first new line outside of brackets.
+    // brackets left open end at the next line that starts no further in than
+    // the broken statement, or with a statement keyword it may have swallowed
+    fn synchronize(&mut self, start: usize) {
+        let failed = self.index.max(start + 1);
+        let column = self.input.get(start).map_or(1, |token| token.row());
+        let mut depth = 0usize;
+        let mut index = start;
+        while index < self.input.len() {
+            let token = &self.input[index];
+            if matches!(token, Token::EOF { .. }) {
+                break;
+            }
+            let starts_line = index > 0 && token.line() > self.input[index - 1].line();
+            let outdented = starts_line && token.row() <= column;
+            let closes = token.is_symbol('}') || token.is_symbol(')') || token.is_symbol(']');
+            let continues = token.is_symbol('.') || matches!(token, Token::Operator { .. });
+            if outdented
+                && ((index > start && starts_statement(token))
+                    || (index >= failed && !closes && !continues))
+            {
+                break;
+            }
+            if index >= failed && depth == 0 {
+                if token.is_symbol('}') {
+                    break;
+                }
+                if token.is_symbol(';') {
+                    index += 1;
+                    break;
+                }
+                if starts_line && !continues {
+                    break;
+                }
+            }
+            if token.is_symbol('{') || token.is_symbol('(') || token.is_symbol('[') {
+                depth += 1;
+            } else if token.is_symbol('}') || token.is_symbol(')') || token.is_symbol(']') {
+                depth = depth.saturating_sub(1);
+            }
+            index += 1;
+        }
+        self.index = index;
+    }
+
     fn compound_statement(&mut self) -> Result<Vec<Statement>, NovaError> {
         let mut initial_statements = vec![];
-        if let Some(statement) = self.statement()? {
+        if let Some(statement) = self.recovering_statement() {
             initial_statements.push(statement)
         };
         let statements = {
@@ -4740,14 +5650,15 @@ impl Parser {
                 if self.current_token().is_symbol('}') {
                     break;
                 }
-                if let Some(statement) = self.statement()? {
+                if let Some(statement) = self.recovering_statement() {
                     statements.push(statement);
                 }
                 if self.index == index_change {
-                    return Err(self.generate_error(
+                    self.errors.push(self.generate_error(
                         "Expected statement".to_string(),
                         "Expected statement".to_string(),
                     ));
+                    self.synchronize(index_change);
                 }
             }
             statements
@@ -4755,7 +5666,15 @@ impl Parser {
         Ok(statements)
     }
 
+    // returns the first error, the rest are left in errors
     pub fn parse(&mut self) -> Result<(), NovaError> {
+        self.errors.clear();
+        self.warnings.clear();
+        let result = self.parse_module();
+        self.finish(result)
+    }
+
+    fn parse_module(&mut self) -> Result<(), NovaError> {
         if self.current_token().is_id("module") {
             self.consume_identifier(Some("module"))?;
             let (module_name, _) = self.get_identifier()?;
@@ -4772,4 +5691,41 @@ impl Parser {
         self.ast.program = self.compound_statement()?;
         self.eof()
     }
+
+    // parses a chunk of statements without a module declaration,
+    // the environment is kept so later chunks can see earlier definitions
+    pub fn parse_repl(&mut self) -> Result<(), NovaError> {
+        self.index = 0;
+        self.errors.clear();
+        self.warnings.clear();
+        let result = self
+            .compound_statement()
+            .and_then(|program| {
+                self.ast.program = program;
+                self.eof()
+            });
+        self.finish(result)
+    }
+
+    fn finish(&mut self, result: Result<(), NovaError>) -> Result<(), NovaError> {
+        if let Err(error) = result {
+            self.errors.push(error);
+        }
+        match self.errors.first() {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+}
+
+// keywords that can only begin a statement
+fn starts_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Identifier { name, .. } if matches!(
+            name.as_str(),
+            "let" | "fn" | "struct" | "enum" | "import" | "trait" | "impl" | "if" | "while"
+                | "for" | "match" | "return"
+        )
+    )
 }
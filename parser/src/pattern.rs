@@ -0,0 +1,794 @@
+use common::{
+    error::NovaError,
+    fileposition::FilePosition,
+    nodes::{Atom, Pattern},
+    tokens::{Operator, Token},
+    ttype::TType,
+};
+
+use crate::Parser;
+
+// the names a pattern binds, with their types and where they were written
+pub type Bindings = Vec<(String, TType, FilePosition)>;
+
+// the ways a value can be built, a pattern either names one of them or
+// matches anything. tuples and structs only have the one
+#[derive(Debug, Clone, PartialEq)]
+enum Constructor {
+    Variant(usize),
+    Some,
+    None,
+    Bool(bool),
+    Single,
+    Literal(Atom),
+    Range(Atom, Atom, bool),
+}
+
+impl Parser {
+    // a pattern for a value of the given type
+    pub(crate) fn pattern(
+        &mut self,
+        ttype: &TType,
+        bindings: &mut Bindings,
+    ) -> Result<Pattern, NovaError> {
+        let position = self.get_current_token_position();
+        match self.current_token() {
+            Token::Symbol { symbol: '#', .. } => {
+                self.consume_symbol('#')?;
+                let TType::Tuple { elements } = ttype else {
+                    return Err(self.pattern_error("a tuple", ttype, position));
+                };
+                let patterns = self.pattern_list(elements, bindings)?;
+                Ok(Pattern::Tuple { patterns })
+            }
+            Token::Identifier { .. } => self.named_pattern(ttype, bindings),
+            _ => self.literal_pattern(ttype),
+        }
+    }
+
+    // the patterns between parentheses, one for each type
+    fn pattern_list(
+        &mut self,
+        types: &[TType],
+        bindings: &mut Bindings,
+    ) -> Result<Vec<Pattern>, NovaError> {
+        let position = self.get_current_token_position();
+        self.consume_symbol('(')?;
+        let mut patterns = vec![];
+        while !self.current_token().is_symbol(')') {
+            let ttype = types.get(patterns.len()).cloned().unwrap_or(TType::Any);
+            patterns.push(self.pattern(&ttype, bindings)?);
+            if !self.current_token().is_symbol(')') {
+                self.consume_symbol(',')?;
+            }
+        }
+        self.consume_symbol(')')?;
+        if patterns.len() != types.len() {
+            return Err(self.generate_error_with_pos(
+                format!(
+                    "expected {} patterns, found {}",
+                    types.len(),
+                    patterns.len()
+                ),
+                "there is one pattern for every value inside".to_string(),
+                self.span_from(&position),
+            ));
+        }
+        Ok(patterns)
+    }
+
+    // wildcards, bindings, None and Some, enum variants and structs
+    fn named_pattern(
+        &mut self,
+        ttype: &TType,
+        bindings: &mut Bindings,
+    ) -> Result<Pattern, NovaError> {
+        let (mut name, position) = self.get_identifier()?;
+        // a variant can be written with its enum, Maybe::Just(x)
+        let mut qualifier = None;
+        if self.current_token().is_op(Operator::DoubleColon) {
+            self.consume_operator(Operator::DoubleColon)?;
+            qualifier = Some(name);
+            name = self.get_identifier()?.0;
+        }
+        let position = self.span_from(&position);
+        let call = self.current_token().is_symbol('(');
+        match ttype {
+            _ if name == "_" && qualifier.is_none() => Ok(Pattern::Wildcard),
+            TType::Option { .. } if name == "None" && qualifier.is_none() => Ok(Pattern::None),
+            TType::Option { inner } if name == "Some" && qualifier.is_none() && call => {
+                let mut patterns = self.pattern_list(&[*inner.clone()], bindings)?;
+                Ok(Pattern::Some {
+                    pattern: Box::new(patterns.remove(0)),
+                })
+            }
+            TType::Custom {
+                name: type_name, ..
+            } if self.environment.enums.has(type_name) => {
+                let fields = self.custom_fields(ttype);
+                let tag = fields.iter().position(|(variant, _)| variant == &name);
+                match (tag, &qualifier) {
+                    (Some(tag), None) => {
+                        self.variant_pattern(name, tag, &fields, bindings, position)
+                    }
+                    (Some(tag), Some(qualifier)) if qualifier == type_name => {
+                        self.variant_pattern(name, tag, &fields, bindings, position)
+                    }
+                    (None, None) if !call => self.bind(name, ttype, position, bindings),
+                    _ => Err(self.generate_error_with_pos(
+                        format!("variant '{}' not found in type", name),
+                        format!("{} has the variants {}", type_name, {
+                            let names = fields
+                                .iter()
+                                .map(|(variant, _)| variant.as_str())
+                                .collect::<Vec<&str>>();
+                            names.join(", ")
+                        }),
+                        position,
+                    )),
+                }
+            }
+            TType::Custom {
+                name: type_name, ..
+            } if type_name == &name && qualifier.is_none() => {
+                let fields = self.custom_fields(ttype);
+                if call {
+                    let types = fields
+                        .iter()
+                        .map(|(_, ttype)| ttype.clone())
+                        .collect::<Vec<TType>>();
+                    let fields = self.pattern_list(&types, bindings)?;
+                    return Ok(Pattern::Struct { name, fields });
+                }
+                if self.current_token().is_symbol('{') {
+                    return self.struct_pattern(name, &fields, bindings);
+                }
+                self.bind(name, ttype, position, bindings)
+            }
+            _ if qualifier.is_none() && !call => self.bind(name, ttype, position, bindings),
+            _ => Err(self.pattern_error(&format!("'{name}'"), ttype, position)),
+        }
+    }
+
+    // Just(x) or Nothing, a variant without a value may also be written Nothing()
+    fn variant_pattern(
+        &mut self,
+        name: String,
+        tag: usize,
+        fields: &[(String, TType)],
+        bindings: &mut Bindings,
+        position: FilePosition,
+    ) -> Result<Pattern, NovaError> {
+        let payload = fields[tag].1.clone();
+        if payload == TType::None {
+            if self.current_token().is_symbol('(') {
+                self.pattern_list(&[], bindings)?;
+            }
+            return Ok(Pattern::Variant {
+                name,
+                tag,
+                pattern: None,
+            });
+        }
+        if !self.current_token().is_symbol('(') {
+            return Err(self.generate_error_with_pos(
+                format!("variant '{}' holds a value", name),
+                format!("match it with {name}(pattern), or {name}(_) to ignore the value"),
+                position,
+            ));
+        }
+        let mut patterns = self.pattern_list(&[payload], bindings)?;
+        Ok(Pattern::Variant {
+            name,
+            tag,
+            pattern: Some(Box::new(patterns.remove(0))),
+        })
+    }
+
+    // Person { name: n, age: 42 }, a field on its own binds its name and
+    // the fields left out match anything
+    fn struct_pattern(
+        &mut self,
+        name: String,
+        fields: &[(String, TType)],
+        bindings: &mut Bindings,
+    ) -> Result<Pattern, NovaError> {
+        let mut patterns = vec![Pattern::Wildcard; fields.len()];
+        let mut seen = vec![];
+        self.consume_symbol('{')?;
+        while !self.current_token().is_symbol('}') {
+            let (field, position) = self.get_identifier()?;
+            let Some(index) = fields.iter().position(|(other, _)| other == &field) else {
+                return Err(self.generate_error_with_pos(
+                    format!("field '{}' not found in {}", field, name),
+                    "make sure the field is in the struct".to_string(),
+                    position,
+                ));
+            };
+            if seen.contains(&index) {
+                return Err(self.generate_error_with_pos(
+                    format!("field '{}' is matched more than once", field),
+                    "give each field a single pattern".to_string(),
+                    position,
+                ));
+            }
+            seen.push(index);
+            let ttype = fields[index].1.clone();
+            patterns[index] = if self.current_token().is_op(Operator::Colon) {
+                self.consume_operator(Operator::Colon)?;
+                self.pattern(&ttype, bindings)?
+            } else {
+                self.bind(field, &ttype, position, bindings)?
+            };
+            if !self.current_token().is_symbol('}') {
+                self.consume_symbol(',')?;
+            }
+        }
+        self.consume_symbol('}')?;
+        Ok(Pattern::Struct {
+            name,
+            fields: patterns,
+        })
+    }
+
+    // a literal, or a range of ints or chars like 1..=9 or 'a'..'z'
+    fn literal_pattern(&mut self, ttype: &TType) -> Result<Pattern, NovaError> {
+        let position = self.get_current_token_position();
+        let start = self.literal(ttype)?;
+        // the lexer names `..=` ExclusiveRange and `..` InclusiveRange
+        let inclusive = match self.current_token() {
+            Token::Operator {
+                operator: Operator::ExclusiveRange,
+                ..
+            } => true,
+            Token::Operator {
+                operator: Operator::InclusiveRange,
+                ..
+            } => false,
+            _ => return Ok(Pattern::Literal { value: start }),
+        };
+        self.advance();
+        let end = self.literal(ttype)?;
+        let position = self.span_from(&position);
+        if !matches!(ttype, TType::Int | TType::Char) {
+            return Err(self.pattern_error("a range", ttype, position));
+        }
+        let empty = match (&start, &end) {
+            (Atom::Integer { value: start }, Atom::Integer { value: end }) => {
+                start > end || (start == end && !inclusive)
+            }
+            (Atom::Char { value: start }, Atom::Char { value: end }) => {
+                start > end || (start == end && !inclusive)
+            }
+            _ => false,
+        };
+        if empty {
+            return Err(self.generate_error_with_pos(
+                "range pattern matches nothing".to_string(),
+                "the start of a range must come before its end".to_string(),
+                position,
+            ));
+        }
+        Ok(Pattern::Range {
+            start,
+            end,
+            inclusive,
+        })
+    }
+
+    // an int, char, string or bool, ints may be negative
+    fn literal(&mut self, ttype: &TType) -> Result<Atom, NovaError> {
+        let position = self.get_current_token_position();
+        let negative = self.current_token().is_op(Operator::Subtraction);
+        if negative {
+            self.advance();
+        }
+        let (atom, found) = match self.current_token() {
+            Token::Integer { value, .. } if negative => {
+                (Atom::Integer { value: -value }, TType::Int)
+            }
+            Token::Integer { value, .. } => (Atom::Integer { value }, TType::Int),
+            Token::Char { value, .. } if !negative => (Atom::Char { value }, TType::Char),
+            Token::String { value, .. } if !negative => (Atom::String { value }, TType::String),
+            Token::Bool { value, .. } if !negative => (Atom::Bool { value }, TType::Bool),
+            _ => {
+                return Err(self.generate_error(
+                    format!(
+                        "expected a pattern, got {}",
+                        self.current_token().to_string()
+                    ),
+                    "patterns are literals, names, variants, tuples or structs".to_string(),
+                ))
+            }
+        };
+        self.advance();
+        if &found != ttype {
+            return Err(NovaError::TypeMismatch {
+                expected: ttype.clone(),
+                found,
+                position: self.span_from(&position),
+            });
+        }
+        Ok(atom)
+    }
+
+    fn bind(
+        &mut self,
+        name: String,
+        ttype: &TType,
+        position: FilePosition,
+        bindings: &mut Bindings,
+    ) -> Result<Pattern, NovaError> {
+        if bindings.iter().any(|(other, _, _)| other == &name) {
+            return Err(self.generate_error_with_pos(
+                format!("'{}' is bound more than once in this pattern", name),
+                "use a different name for each value".to_string(),
+                position,
+            ));
+        }
+        bindings.push((name.clone(), ttype.clone(), position));
+        Ok(Pattern::Binding { name })
+    }
+
+    fn pattern_error(&self, pattern: &str, ttype: &TType, position: FilePosition) -> NovaError {
+        self.generate_error_with_pos(
+            format!(
+                "{} pattern cannot match a value of type {}",
+                pattern,
+                ttype.to_string()
+            ),
+            "a pattern must have the type of the value being matched".to_string(),
+            position,
+        )
+    }
+
+    // the fields of a struct or the variants of an enum with the type
+    // arguments filled in, the variant tag is its index
+    pub(crate) fn custom_fields(&self, ttype: &TType) -> Vec<(String, TType)> {
+        let TType::Custom { name, type_params } = ttype else {
+            return vec![];
+        };
+        let Some(fields) = self.environment.custom_types.get(name) else {
+            return vec![];
+        };
+        let generics = self.environment.generic_type_struct.get(name);
+        fields
+            .iter()
+            .filter(|(field, _)| field != "type")
+            .map(|(field, ttype)| match generics {
+                Some(generics) if generics.len() == type_params.len() => (
+                    field.clone(),
+                    self.replace_generic_types(ttype, generics, type_params),
+                ),
+                _ => (field.clone(), ttype.clone()),
+            })
+            .collect()
+    }
+
+    // checks a row of patterns against the rows of the arms before it, the
+    // result is a value only the row matches. a match is exhaustive when
+    // a wildcard finds no such value, an arm is unreachable when its
+    // pattern finds none
+    pub(crate) fn useful(
+        &self,
+        rows: &[Vec<Pattern>],
+        row: &[Pattern],
+        types: &[TType],
+    ) -> Option<Vec<Pattern>> {
+        let Some((first, rest)) = row.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+        let ttype = &types[0];
+        if let Some(constructor) = head(first) {
+            let arguments = self.arguments(&constructor, ttype);
+            let rows = specialize(rows, &constructor, arguments.len());
+            let row = [subpatterns(first), rest.to_vec()].concat();
+            let types = [arguments, types[1..].to_vec()].concat();
+            return self
+                .useful(&rows, &row, &types)
+                .map(|witness| self.construct(&constructor, ttype, witness));
+        }
+        let used = rows
+            .iter()
+            .filter_map(|row| head(&row[0]))
+            .collect::<Vec<Constructor>>();
+        let signature = self.signature(ttype);
+        if let Some(signature) = &signature {
+            if signature
+                .iter()
+                .all(|constructor| used.contains(constructor))
+            {
+                return signature.iter().find_map(|constructor| {
+                    let arguments = self.arguments(constructor, ttype);
+                    let rows = specialize(rows, constructor, arguments.len());
+                    let row = [vec![Pattern::Wildcard; arguments.len()], rest.to_vec()].concat();
+                    let types = [arguments, types[1..].to_vec()].concat();
+                    self.useful(&rows, &row, &types)
+                        .map(|witness| self.construct(constructor, ttype, witness))
+                });
+            }
+        }
+        // only the rows that match anything are left to cover the rest
+        let defaults = rows
+            .iter()
+            .filter(|row| head(&row[0]).is_none())
+            .map(|row| row[1..].to_vec())
+            .collect::<Vec<Vec<Pattern>>>();
+        let witness = self.useful(&defaults, rest, &types[1..])?;
+        let missing = signature
+            .and_then(|signature| {
+                signature
+                    .into_iter()
+                    .find(|constructor| !used.contains(constructor))
+            })
+            .map(|constructor| {
+                let arguments = self.arguments(&constructor, ttype);
+                let wildcards = vec![Pattern::Wildcard; arguments.len()];
+                self.construct(&constructor, ttype, wildcards).remove(0)
+            })
+            .unwrap_or(Pattern::Wildcard);
+        Some([vec![missing], witness].concat())
+    }
+
+    // every constructor of a type, types with endless values have none
+    fn signature(&self, ttype: &TType) -> Option<Vec<Constructor>> {
+        match ttype {
+            TType::Bool => Some(vec![Constructor::Bool(true), Constructor::Bool(false)]),
+            TType::Option { .. } => Some(vec![Constructor::Some, Constructor::None]),
+            TType::Tuple { .. } => Some(vec![Constructor::Single]),
+            TType::Custom { name, .. } if self.environment.enums.has(name) => Some(
+                (0..self.custom_fields(ttype).len())
+                    .map(Constructor::Variant)
+                    .collect(),
+            ),
+            TType::Custom { name, .. } if self.environment.custom_types.contains_key(name) => {
+                Some(vec![Constructor::Single])
+            }
+            _ => None,
+        }
+    }
+
+    // the types of the values inside a constructor
+    fn arguments(&self, constructor: &Constructor, ttype: &TType) -> Vec<TType> {
+        match (constructor, ttype) {
+            (Constructor::Variant(tag), _) => match self.custom_fields(ttype).get(*tag) {
+                Some((_, payload)) if payload != &TType::None => vec![payload.clone()],
+                _ => vec![],
+            },
+            (Constructor::Some, TType::Option { inner }) => vec![*inner.clone()],
+            (Constructor::Single, TType::Tuple { elements }) => elements.clone(),
+            (Constructor::Single, _) => self
+                .custom_fields(ttype)
+                .into_iter()
+                .map(|(_, ttype)| ttype)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    // puts the constructor back around the front of a witness
+    fn construct(
+        &self,
+        constructor: &Constructor,
+        ttype: &TType,
+        mut witness: Vec<Pattern>,
+    ) -> Vec<Pattern> {
+        let count = self.arguments(constructor, ttype).len();
+        let mut arguments = witness.drain(..count).collect::<Vec<Pattern>>();
+        let pattern = match constructor {
+            Constructor::Variant(tag) => Pattern::Variant {
+                name: self
+                    .custom_fields(ttype)
+                    .get(*tag)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_default(),
+                tag: *tag,
+                pattern: arguments.pop().map(Box::new),
+            },
+            Constructor::Some => Pattern::Some {
+                pattern: Box::new(arguments.pop().unwrap_or(Pattern::Wildcard)),
+            },
+            Constructor::None => Pattern::None,
+            Constructor::Bool(value) => Pattern::Literal {
+                value: Atom::Bool { value: *value },
+            },
+            Constructor::Single => match ttype {
+                TType::Custom { name, .. } => Pattern::Struct {
+                    name: name.clone(),
+                    fields: arguments,
+                },
+                _ => Pattern::Tuple {
+                    patterns: arguments,
+                },
+            },
+            Constructor::Literal(value) => Pattern::Literal {
+                value: value.clone(),
+            },
+            Constructor::Range(start, end, inclusive) => Pattern::Range {
+                start: start.clone(),
+                end: end.clone(),
+                inclusive: *inclusive,
+            },
+        };
+        [vec![pattern], witness].concat()
+    }
+}
+
+fn head(pattern: &Pattern) -> Option<Constructor> {
+    match pattern {
+        Pattern::Wildcard | Pattern::Binding { .. } => None,
+        Pattern::Literal {
+            value: Atom::Bool { value },
+        } => Some(Constructor::Bool(*value)),
+        Pattern::Literal { value } => Some(Constructor::Literal(value.clone())),
+        Pattern::Range {
+            start,
+            end,
+            inclusive,
+        } => Some(Constructor::Range(start.clone(), end.clone(), *inclusive)),
+        Pattern::None => Some(Constructor::None),
+        Pattern::Some { .. } => Some(Constructor::Some),
+        Pattern::Variant { tag, .. } => Some(Constructor::Variant(*tag)),
+        Pattern::Tuple { .. } | Pattern::Struct { .. } => Some(Constructor::Single),
+    }
+}
+
+fn subpatterns(pattern: &Pattern) -> Vec<Pattern> {
+    match pattern {
+        Pattern::Some { pattern } => vec![*pattern.clone()],
+        Pattern::Variant {
+            pattern: Some(pattern),
+            ..
+        } => vec![*pattern.clone()],
+        Pattern::Tuple { patterns }
+        | Pattern::Struct {
+            fields: patterns, ..
+        } => patterns.clone(),
+        _ => vec![],
+    }
+}
+
+// the rows that can match a value built by the constructor, with the
+// values inside it in place of their first pattern. a literal is only
+// known to be covered by the same literal or a range around it
+fn specialize(rows: &[Vec<Pattern>], constructor: &Constructor, count: usize) -> Vec<Vec<Pattern>> {
+    rows.iter()
+        .filter_map(|row| {
+            let covers = match (head(&row[0]), constructor) {
+                (None, _) => {
+                    return Some([vec![Pattern::Wildcard; count], row[1..].to_vec()].concat())
+                }
+                (Some(Constructor::Range(start, end, inclusive)), Constructor::Literal(value)) => {
+                    within(value, &start, &end, inclusive)
+                }
+                (Some(head), constructor) => &head == constructor,
+            };
+            covers.then(|| [subpatterns(&row[0]), row[1..].to_vec()].concat())
+        })
+        .collect()
+}
+
+fn within(value: &Atom, start: &Atom, end: &Atom, inclusive: bool) -> bool {
+    let number = |atom: &Atom| match atom {
+        Atom::Integer { value } => Some(*value),
+        Atom::Char { value } => Some(*value as i64),
+        _ => None,
+    };
+    match (number(value), number(start), number(end)) {
+        (Some(value), Some(start), Some(end)) => {
+            value >= start && (value < end || (inclusive && value == end))
+        }
+        _ => false,
+    }
+}
+
+// how a pattern is written, for the arms a match is missing
+pub(crate) fn describe(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Binding { name } => name.clone(),
+        Pattern::Literal { value } => literal(value),
+        Pattern::Range {
+            start,
+            end,
+            inclusive,
+        } => format!(
+            "{}{}{}",
+            literal(start),
+            if *inclusive { "..=" } else { ".." },
+            literal(end)
+        ),
+        Pattern::None => "None".to_string(),
+        Pattern::Some { pattern } => format!("Some({})", describe(pattern)),
+        Pattern::Variant {
+            name,
+            pattern: Some(pattern),
+            ..
+        } => format!("{}({})", name, describe(pattern)),
+        Pattern::Variant { name, .. } => name.clone(),
+        Pattern::Tuple { patterns } => format!("#({})", describe_all(patterns)),
+        Pattern::Struct { name, fields } => format!("{}({})", name, describe_all(fields)),
+    }
+}
+
+fn describe_all(patterns: &[Pattern]) -> String {
+    patterns
+        .iter()
+        .map(describe)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn literal(atom: &Atom) -> String {
+    match atom {
+        Atom::Integer { value } => value.to_string(),
+        Atom::Char { value } => format!("{:?}", value),
+        Atom::String { value } => format!("{:?}", value),
+        Atom::Bool { value } => value.to_string(),
+        _ => "_".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::error::NovaError;
+    use lexer::Lexer;
+
+    // parses the body after the types every test shares
+    fn parse(body: &str) -> crate::Parser {
+        let source = format!(
+            "module matched
+
+struct Person {{ name: String, age: Int }}
+enum Shape {{ Circle: Float, Square: Int, Dot }}
+
+{body}
+"
+        );
+        let mut parser = crate::new("matched.nv");
+        parser.input = Lexer::from_source("matched.nv", &source)
+            .tokenize()
+            .unwrap();
+        let _ = parser.parse();
+        parser
+    }
+
+    // the missing arm a match reports, or None when it is exhaustive
+    fn missing(ttype: &str, arms: &str) -> Option<String> {
+        let parser = parse(&format!(
+            "fn f(x: {ttype}) {{\n    match x {{\n{arms}\n    }}\n}}"
+        ));
+        match parser.errors.as_slice() {
+            [] => None,
+            [NovaError::Parsing { msg, .. }] => Some(msg.clone()),
+            errors => panic!("expected one error, got {errors:?}"),
+        }
+    }
+
+    fn unreachable(ttype: &str, arms: &str) -> Vec<usize> {
+        let parser = parse(&format!(
+            "fn f(x: {ttype}) {{\n    match x {{\n{arms}\n    }}\n}}"
+        ));
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        parser
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                NovaError::Warning { msg, position, .. } => {
+                    assert_eq!(msg, "unreachable match arm");
+                    position.line
+                }
+                other => panic!("expected a warning, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exhaustive() {
+        assert_eq!(missing("Bool", "true -> {}\nfalse -> {}"), None);
+        assert_eq!(missing("Int", "0 -> {}\nn -> {}"), None);
+        assert_eq!(missing("?Int", "Some(_) -> {}\nNone -> {}"), None);
+        assert_eq!(
+            missing("Shape", "Circle(_) -> {}\nSquare(_) -> {}\nDot -> {}"),
+            None
+        );
+        assert_eq!(
+            missing(
+                "#(Bool, Bool)",
+                "#(true, _) -> {}\n#(_, true) -> {}\n#(false, false) -> {}"
+            ),
+            None
+        );
+        assert_eq!(
+            missing("Person", "Person { age: 1..5 } -> {}\n_ -> {}"),
+            None
+        );
+    }
+
+    #[test]
+    fn not_exhaustive() {
+        assert_eq!(
+            missing("Bool", "true -> {}").unwrap(),
+            "false is not covered"
+        );
+        assert_eq!(missing("Int", "0..=9 -> {}").unwrap(), "_ is not covered");
+        assert_eq!(
+            missing("?Int", "Some(1) -> {}\nNone -> {}").unwrap(),
+            "Some(_) is not covered"
+        );
+        assert_eq!(
+            missing("Shape", "Circle(_) -> {}\nDot -> {}").unwrap(),
+            "Square(_) is not covered"
+        );
+        assert_eq!(
+            missing(
+                "?Shape",
+                "Some(Circle(_)) -> {}\nSome(Square(_)) -> {}\nNone -> {}"
+            )
+            .unwrap(),
+            "Some(Dot) is not covered"
+        );
+        assert_eq!(
+            missing("#(Bool, ?Int)", "#(true, _) -> {}\n#(false, None) -> {}").unwrap(),
+            "#(false, Some(_)) is not covered"
+        );
+        assert_eq!(
+            missing("Person", "Person { name: \"bob\" } -> {}").unwrap(),
+            "Person(_, _) is not covered"
+        );
+        // a guard may fail, its arm covers nothing
+        assert_eq!(
+            missing("Bool", "true -> {}\nfalse if 1 == 1 -> {}").unwrap(),
+            "false is not covered"
+        );
+    }
+
+    #[test]
+    fn unreachable_arms() {
+        // the match starts on line 7 of the program, its arms follow
+        assert_eq!(unreachable("Int", "_ -> {}\n0 -> {}"), [9]);
+        assert_eq!(
+            unreachable("Int", "1..=9 -> {}\n5 -> {}\n10 -> {}\n_ -> {}"),
+            [9]
+        );
+        assert_eq!(
+            unreachable("?Int", "Some(_) -> {}\nNone -> {}\nSome(3) -> {}"),
+            [10]
+        );
+        assert_eq!(
+            unreachable(
+                "#(Bool, Bool)",
+                "#(true, _) -> {}\n#(_, false) -> {}\n#(true, true) -> {}\n_ -> {}"
+            ),
+            [10]
+        );
+        // an arm behind a guard can still run
+        assert!(unreachable("Int", "n if n > 0 -> {}\n1 -> {}\n_ -> {}").is_empty());
+    }
+
+    #[test]
+    fn pattern_errors() {
+        let error = |arms: &str| {
+            let parser = parse(&format!(
+                "fn f(x: ?Int) {{\n    match x {{\n{arms}\n    }}\n}}"
+            ));
+            parser.errors[0].message()
+        };
+        assert_eq!(
+            error("Some(\"a\") -> {}\n_ -> {}"),
+            "expected type: Int, found type: String"
+        );
+        assert_eq!(
+            error("Some(5..1) -> {}\n_ -> {}"),
+            "range pattern matches nothing"
+        );
+        assert_eq!(
+            error("Some(n) if n -> {}\n_ -> {}"),
+            "match guard must return a bool"
+        );
+        assert_eq!(
+            error("#(a, b) -> {}"),
+            "a tuple pattern cannot match a value of type ?Int"
+        );
+    }
+}
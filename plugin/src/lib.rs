@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use common::{error::NovaError, ttype::TType};
+use vm::{
+    bind::{self, Module},
+    state::VmData,
+    Vm,
+};
+
+vm::native! {
+    // the average of a list, 0 when it is empty
+    fn mean(values: Vec<f64>) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+vm::native! {
+    fn clamp(value: i64, low: i64, high: i64) -> i64 {
+        value.max(low).min(high)
+    }
+}
+
+vm::native! {
+    fn count(values: Vec<VmData>) -> i64 {
+        values.len() as i64
+    }
+}
+
+// calls f with every number from 0 up to n
+fn times(vm: &mut Vm) -> Result<(), NovaError> {
+    let (function, n) = match (vm.state.stack.pop(), vm.state.stack.pop()) {
+        (Some(function), Some(VmData::Int(n))) => (function, n),
+        _ => {
+            return Err(NovaError::Runtime {
+                msg: "Expected a number and a function on the stack".to_string(),
+            })
+        }
+    };
+    // keep the function on the stack while nova runs
+    vm.state.stack.push(function);
+    for i in 0..n {
+        vm.call(function, &[VmData::Int(i)])?;
+    }
+    vm.state.stack.pop();
+    Ok(())
+}
+
+fn register() -> Module {
+    let mut module = bind::module("stats");
+    module.function("mean", mean());
+    module.function("clamp", clamp());
+    module.generic("count", count());
+    module.function(
+        "times",
+        bind::native(
+            vec![
+                TType::Int,
+                TType::Function {
+                    parameters: vec![TType::Int],
+                    return_type: Box::new(TType::Void),
+                },
+            ],
+            TType::Void,
+            Arc::new(times),
+        ),
+    );
+    module
+}
+
+vm::plugin!(register);
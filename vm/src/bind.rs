@@ -0,0 +1,770 @@
+pub use common::error::NovaError;
+use common::{nodes::SymbolKind, ttype::TType};
+
+use std::sync::Arc;
+
+use crate::{
+    state::{Heap, State, VmData},
+    CallBack, Vm,
+};
+
+// a rust type that has a nova type and can be moved in and out of the vm
+pub trait Marshal: Sized {
+    fn ttype() -> TType;
+    fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError>;
+    fn into_vm(self, state: &mut State) -> VmData;
+}
+
+// the arguments of a native, popped off the stack in the order they were pushed
+pub trait Arguments: Sized {
+    fn ttypes() -> Vec<TType>;
+    fn pop(state: &mut State) -> Result<Self, NovaError>;
+    fn into_vm(self, state: &mut State) -> Vec<VmData>;
+}
+
+// what a native returns, void natives leave nothing on the stack
+pub trait Returns {
+    fn ttype() -> TType;
+    fn push_result(self, state: &mut State) -> Result<(), NovaError>;
+}
+
+// a rust function or closure that can be turned into a native,
+// A are its argument types as a tuple
+pub trait Function<A> {
+    fn into_native(self) -> Native;
+}
+
+// like Function, for natives that also take the state of the vm as their
+// first parameter, to write output, allocate or change lists in place
+pub trait Stateful<A> {
+    fn into_native(self) -> Native;
+}
+
+// a native function along with the signature derived from its rust types
+pub struct Native {
+    pub ttype: TType,
+    pub function: CallBack,
+}
+
+pub fn native(parameters: Vec<TType>, return_type: TType, function: CallBack) -> Native {
+    Native {
+        ttype: TType::Function {
+            parameters,
+            return_type: Box::new(return_type),
+        },
+        function,
+    }
+}
+
+// the natives a plugin library hands to the host, they are called as
+// name::function from nova
+pub struct Module {
+    pub name: String,
+    pub functions: Vec<(String, SymbolKind, Native)>,
+}
+
+pub fn module(name: &str) -> Module {
+    Module {
+        name: name.to_string(),
+        functions: vec![],
+    }
+}
+
+impl Module {
+    // overloadable by its parameter types, like functions declared in nova
+    pub fn function(&mut self, name: &str, native: Native) {
+        self.functions
+            .push((name.to_string(), SymbolKind::Function, native));
+    }
+
+    // the signature holds generics, only one version can exist
+    pub fn generic(&mut self, name: &str, native: Native) {
+        self.functions
+            .push((name.to_string(), SymbolKind::GenericFunction, native));
+    }
+}
+
+// plugins share rust types with the host, Module, TType and the Vm itself,
+// and rust gives those no stable abi. a plugin is only loaded when it was
+// built from the same nova version by the same rustc and agrees on the size
+// of every type that crosses over, anything else is refused rather than run
+//
+// bumped whenever Module or the vm types natives touch change shape
+pub const PLUGIN_VERSION: u32 = 1;
+
+// the nova version and the rustc that built it, nul terminated so the host
+// can read it through a c function before touching any rust type
+pub const PLUGIN_BUILD: &str = concat!(
+    "nova ",
+    env!("CARGO_PKG_VERSION"),
+    ", ",
+    env!("NOVA_RUSTC_VERSION"),
+    "\0"
+);
+
+// a hash of the size and alignment of the types a plugin hands over
+pub const PLUGIN_LAYOUT: u64 = layout();
+
+const fn layout() -> u64 {
+    use std::mem::{align_of, size_of};
+    let sizes = [
+        size_of::<Vm>(),
+        align_of::<Vm>(),
+        size_of::<State>(),
+        size_of::<VmData>(),
+        size_of::<Heap>(),
+        size_of::<TType>(),
+        size_of::<SymbolKind>(),
+        size_of::<NovaError>(),
+        size_of::<CallBack>(),
+        size_of::<Native>(),
+        size_of::<Module>(),
+    ];
+    // fnv-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut index = 0;
+    while index < sizes.len() {
+        hash = (hash ^ sizes[index] as u64).wrapping_mul(0x100000001b3);
+        index += 1;
+    }
+    hash
+}
+
+// wraps a plain rust function or a closure, closures may capture whatever
+// state the native needs
+//
+// let prefix = "hello".to_string();
+// core.add_native("greet", SymbolKind::Function, bind::function(move |name: String| {
+//     format!("{prefix} {name}")
+// }));
+pub fn function<A, F: Function<A>>(function: F) -> Native {
+    function.into_native()
+}
+
+impl<F, R> Function<()> for F
+where
+    F: Fn() -> R + Send + Sync + 'static,
+    R: Returns,
+{
+    fn into_native(self) -> Native {
+        native(
+            <() as Arguments>::ttypes(),
+            R::ttype(),
+            Arc::new(move |vm: &mut Vm| self().push_result(&mut vm.state)),
+        )
+    }
+}
+
+// wraps a rust function or closure whose first parameter is &mut State
+pub fn stateful<A, F: Stateful<A>>(function: F) -> Native {
+    function.into_native()
+}
+
+impl<F, R> Stateful<()> for F
+where
+    F: Fn(&mut State) -> R + Send + Sync + 'static,
+    R: Returns,
+{
+    fn into_native(self) -> Native {
+        native(
+            <() as Arguments>::ttypes(),
+            R::ttype(),
+            Arc::new(move |vm: &mut Vm| self(&mut vm.state).push_result(&mut vm.state)),
+        )
+    }
+}
+
+fn mismatch(expected: &str, found: VmData) -> NovaError {
+    NovaError::Runtime {
+        msg: format!("Expected {expected} but found {found:?}"),
+    }
+}
+
+// follows address cells until the heap cell that holds the value
+fn resolve(state: &State, index: usize) -> Option<&Heap> {
+    match state.heap.get(index) {
+        Some(Heap::StringAddress(v) | Heap::ListAddress(v) | Heap::StructAddress(v)) => {
+            resolve(state, *v)
+        }
+        heap => heap,
+    }
+}
+
+impl Marshal for i64 {
+    fn ttype() -> TType {
+        TType::Int
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::Int(v) => Ok(v),
+            item => Err(mismatch("an Int", item)),
+        }
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::Int(self)
+    }
+}
+
+impl Marshal for f64 {
+    fn ttype() -> TType {
+        TType::Float
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::Float(v) => Ok(v),
+            item => Err(mismatch("a Float", item)),
+        }
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::Float(self)
+    }
+}
+
+impl Marshal for bool {
+    fn ttype() -> TType {
+        TType::Bool
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::Bool(v) => Ok(v),
+            item => Err(mismatch("a Bool", item)),
+        }
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::Bool(self)
+    }
+}
+
+impl Marshal for char {
+    fn ttype() -> TType {
+        TType::Char
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::Char(v) => Ok(v),
+            item => Err(mismatch("a Char", item)),
+        }
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::Char(self)
+    }
+}
+
+impl Marshal for String {
+    fn ttype() -> TType {
+        TType::String
+    }
+
+    fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::String(index) => match resolve(state, index) {
+                Some(Heap::String(v)) => Ok(v.clone()),
+                _ => Err(mismatch("a String", item)),
+            },
+            item => Err(mismatch("a String", item)),
+        }
+    }
+
+    fn into_vm(self, state: &mut State) -> VmData {
+        VmData::String(state.allocate_string(self))
+    }
+}
+
+impl<T: Marshal> Marshal for Vec<T> {
+    fn ttype() -> TType {
+        TType::List {
+            inner: Box::new(T::ttype()),
+        }
+    }
+
+    fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::List(index) => match resolve(state, index) {
+                Some(Heap::List(cells)) => cells
+                    .iter()
+                    .map(|cell| T::from_vm(state, state.to_vmdata(*cell)))
+                    .collect(),
+                _ => Err(mismatch("a List", item)),
+            },
+            item => Err(mismatch("a List", item)),
+        }
+    }
+
+    fn into_vm(self, state: &mut State) -> VmData {
+        let mut cells = vec![];
+        for item in self {
+            let item = item.into_vm(state);
+            cells.push(state.allocate_vmdata_to_heap(item));
+        }
+        VmData::List(state.allocate_array(cells))
+    }
+}
+
+// an option is its inner value, or None
+impl<T: Marshal> Marshal for Option<T> {
+    fn ttype() -> TType {
+        TType::Option {
+            inner: Box::new(T::ttype()),
+        }
+    }
+
+    fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::None => Ok(None),
+            item => Ok(Some(T::from_vm(state, item)?)),
+        }
+    }
+
+    fn into_vm(self, state: &mut State) -> VmData {
+        match self {
+            Some(item) => item.into_vm(state),
+            None => VmData::None,
+        }
+    }
+}
+
+// values of any type are passed through untouched, their nova type is the generic `a`
+impl Marshal for VmData {
+    fn ttype() -> TType {
+        TType::Generic {
+            name: "a".to_string(),
+        }
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        Ok(item)
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        self
+    }
+}
+
+// a list of any element type, left in the heap so natives can change it in
+// place, the index is the heap cell that holds its elements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct List(pub usize);
+
+impl Marshal for List {
+    fn ttype() -> TType {
+        TType::List {
+            inner: Box::new(TType::Generic {
+                name: "a".to_string(),
+            }),
+        }
+    }
+
+    fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError> {
+        match item {
+            VmData::List(index) => match state.heap.get(index) {
+                Some(Heap::ListAddress(v)) => List::from_vm(state, VmData::List(*v)),
+                Some(Heap::List(_)) => Ok(List(index)),
+                _ => Err(mismatch("a List", item)),
+            },
+            item => Err(mismatch("a List", item)),
+        }
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::List(self.0)
+    }
+}
+
+// a value the type checker lets through whatever its type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Any(pub VmData);
+
+impl Marshal for Any {
+    fn ttype() -> TType {
+        TType::Any
+    }
+
+    fn from_vm(_: &State, item: VmData) -> Result<Self, NovaError> {
+        Ok(Any(item))
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        self.0
+    }
+}
+
+impl Marshal for () {
+    fn ttype() -> TType {
+        TType::Void
+    }
+
+    fn from_vm(_: &State, _: VmData) -> Result<Self, NovaError> {
+        Ok(())
+    }
+
+    fn into_vm(self, _: &mut State) -> VmData {
+        VmData::None
+    }
+}
+
+impl<T: Marshal> Returns for T {
+    fn ttype() -> TType {
+        T::ttype()
+    }
+
+    fn push_result(self, state: &mut State) -> Result<(), NovaError> {
+        if T::ttype() != TType::Void {
+            let item = self.into_vm(state);
+            state.stack.push(item);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Marshal> Returns for Result<T, NovaError> {
+    fn ttype() -> TType {
+        T::ttype()
+    }
+
+    fn push_result(self, state: &mut State) -> Result<(), NovaError> {
+        self?.push_result(state)
+    }
+}
+
+// natives without parameters take None, like functions declared in nova do
+impl Arguments for () {
+    fn ttypes() -> Vec<TType> {
+        vec![TType::None]
+    }
+
+    fn pop(_: &mut State) -> Result<Self, NovaError> {
+        Ok(())
+    }
+
+    fn into_vm(self, _: &mut State) -> Vec<VmData> {
+        vec![]
+    }
+}
+
+// tuples are lists in the vm, so they convert the same way argument lists do
+macro_rules! tuple {
+    ($count:literal, $($name:ident),+) => {
+        impl<$($name: Marshal),+> Marshal for ($($name,)+) {
+            fn ttype() -> TType {
+                TType::Tuple {
+                    elements: vec![$($name::ttype()),+],
+                }
+            }
+
+            #[allow(non_snake_case)]
+            fn from_vm(state: &State, item: VmData) -> Result<Self, NovaError> {
+                match item {
+                    VmData::List(index) => match resolve(state, index) {
+                        Some(Heap::List(cells)) if cells.len() == $count => {
+                            let mut cells = cells.iter();
+                            $(let $name = $name::from_vm(state, state.to_vmdata(*cells.next().unwrap()))?;)+
+                            Ok(($($name,)+))
+                        }
+                        _ => Err(mismatch(concat!("a tuple of ", $count), item)),
+                    },
+                    item => Err(mismatch(concat!("a tuple of ", $count), item)),
+                }
+            }
+
+            #[allow(non_snake_case)]
+            fn into_vm(self, state: &mut State) -> VmData {
+                let ($($name,)+) = self;
+                let mut cells = vec![];
+                $(
+                    let item = $name.into_vm(state);
+                    cells.push(state.allocate_vmdata_to_heap(item));
+                )+
+                VmData::List(state.allocate_array(cells))
+            }
+        }
+
+        impl<$($name: Marshal),+> Arguments for ($($name,)+) {
+            fn ttypes() -> Vec<TType> {
+                vec![$($name::ttype()),+]
+            }
+
+            #[allow(non_snake_case)]
+            fn pop(state: &mut State) -> Result<Self, NovaError> {
+                if state.stack.len() < $count {
+                    return Err(NovaError::Runtime {
+                        msg: "Stack is empty".to_string(),
+                    });
+                }
+                let items = state.stack.split_off(state.stack.len() - $count);
+                let mut items = items.into_iter();
+                $(let $name = $name::from_vm(state, items.next().unwrap())?;)+
+                Ok(($($name,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn into_vm(self, state: &mut State) -> Vec<VmData> {
+                let ($($name,)+) = self;
+                vec![$($name.into_vm(state)),+]
+            }
+        }
+
+        impl<Func, R, $($name: Marshal),+> Function<($($name,)+)> for Func
+        where
+            Func: Fn($($name),+) -> R + Send + Sync + 'static,
+            R: Returns,
+        {
+            fn into_native(self) -> Native {
+                native(
+                    <($($name,)+) as Arguments>::ttypes(),
+                    R::ttype(),
+                    Arc::new(move |vm: &mut Vm| {
+                        #[allow(non_snake_case)]
+                        let ($($name,)+) = <($($name,)+) as Arguments>::pop(&mut vm.state)?;
+                        self($($name),+).push_result(&mut vm.state)
+                    }),
+                )
+            }
+        }
+
+        impl<Func, R, $($name: Marshal),+> Stateful<($($name,)+)> for Func
+        where
+            Func: Fn(&mut State, $($name),+) -> R + Send + Sync + 'static,
+            R: Returns,
+        {
+            fn into_native(self) -> Native {
+                native(
+                    <($($name,)+) as Arguments>::ttypes(),
+                    R::ttype(),
+                    Arc::new(move |vm: &mut Vm| {
+                        #[allow(non_snake_case)]
+                        let ($($name,)+) = <($($name,)+) as Arguments>::pop(&mut vm.state)?;
+                        self(&mut vm.state, $($name),+).push_result(&mut vm.state)
+                    }),
+                )
+            }
+        }
+    };
+}
+
+tuple!(1, A);
+tuple!(2, A, B);
+tuple!(3, A, B, C);
+tuple!(4, A, B, C, D);
+tuple!(5, A, B, C, D, E);
+tuple!(6, A, B, C, D, E, F);
+
+// turns a plain rust function into a native, its nova signature and the code
+// that moves the arguments off the stack are derived from the rust types
+//
+// vm::native! {
+//     pub fn strlen(text: String) -> i64 {
+//         text.len() as i64
+//     }
+// }
+//
+// strlen() then returns the Native to register with the host. a first
+// parameter of &mut State is handed the state of the vm and is not part of
+// the nova signature
+//
+// vm::native! {
+//     pub fn count(state: &mut State) -> i64 {
+//         state.gc_count as i64
+//     }
+// }
+#[macro_export]
+macro_rules! native {
+    ($(#[$meta:meta])* $vis:vis fn $name:ident($state:ident: &mut $statetype:ty $(, $arg:ident: $argtype:ty)* $(,)?) -> $ret:ty $body:block) => {
+        $(#[$meta])*
+        $vis fn $name() -> $crate::bind::Native {
+            fn body($state: &mut $statetype $(, $arg: $argtype)*) -> $ret $body
+            $crate::bind::stateful(body)
+        }
+    };
+    ($(#[$meta:meta])* $vis:vis fn $name:ident($state:ident: &mut $statetype:ty $(, $arg:ident: $argtype:ty)* $(,)?) $body:block) => {
+        $crate::native! {
+            $(#[$meta])*
+            $vis fn $name($state: &mut $statetype $(, $arg: $argtype)*) -> () $body
+        }
+    };
+    ($(#[$meta:meta])* $vis:vis fn $name:ident($($arg:ident: $argtype:ty),* $(,)?) -> $ret:ty $body:block) => {
+        $(#[$meta])*
+        $vis fn $name() -> $crate::bind::Native {
+            fn body($($arg: $argtype),*) -> $ret $body
+            $crate::bind::function(body)
+        }
+    };
+    ($(#[$meta:meta])* $vis:vis fn $name:ident($($arg:ident: $argtype:ty),* $(,)?) $body:block) => {
+        $crate::native! {
+            $(#[$meta])*
+            $vis fn $name($($arg: $argtype),*) -> () $body
+        }
+    };
+}
+
+// exports the entry point the host looks for in a plugin library. the
+// library must be a cdylib built with the same compiler and nova version,
+// the host checks the exported fingerprint before it calls nova_plugin
+//
+// fn register() -> vm::bind::Module {
+//     let mut module = vm::bind::module("stats");
+//     module.function("mean", mean());
+//     module
+// }
+//
+// vm::plugin!(register);
+#[macro_export]
+macro_rules! plugin {
+    ($register:path) => {
+        #[no_mangle]
+        pub static NOVA_PLUGIN_VERSION: u32 = $crate::bind::PLUGIN_VERSION;
+
+        #[no_mangle]
+        pub static NOVA_PLUGIN_LAYOUT: u64 = $crate::bind::PLUGIN_LAYOUT;
+
+        #[no_mangle]
+        pub extern "C" fn nova_plugin_build() -> *const ::std::os::raw::c_char {
+            $crate::bind::PLUGIN_BUILD.as_ptr() as *const ::std::os::raw::c_char
+        }
+
+        #[no_mangle]
+        pub fn nova_plugin() -> $crate::bind::Module {
+            $register()
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // moves a value into the vm and back out again
+    fn round_trip<T: Marshal>(value: T) -> T {
+        let mut state = crate::state::new();
+        let item = value.into_vm(&mut state);
+        T::from_vm(&state, item).unwrap()
+    }
+
+    crate::native! {
+        fn add(a: i64, b: i64) -> i64 {
+            a + b
+        }
+    }
+
+    crate::native! {
+        fn append(state: &mut State, list: List, item: VmData) {
+            let item = state.allocate_vmdata_to_heap(item);
+            if let Heap::List(array) = &mut state.heap[list.0] {
+                array.push(item);
+            }
+        }
+    }
+
+    // calls a native with the arguments pushed the way compiled code does
+    fn call(native: Native, vm: &mut Vm, arguments: Vec<VmData>) -> Result<(), NovaError> {
+        vm.state.stack.extend(arguments);
+        (native.function)(vm)
+    }
+
+    #[test]
+    fn lists_options_and_tuples_round_trip() {
+        assert_eq!(round_trip(vec![1i64, 2, 3]), vec![1, 2, 3]);
+        assert_eq!(round_trip(Vec::<String>::new()), Vec::<String>::new());
+        assert_eq!(
+            round_trip(vec![vec!['a'], vec![], vec!['b', 'c']]),
+            vec![vec!['a'], vec![], vec!['b', 'c']]
+        );
+        assert_eq!(round_trip(Some(4i64)), Some(4));
+        assert_eq!(round_trip(None::<String>), None);
+        assert_eq!(
+            round_trip(Some(vec!["x".to_string()])),
+            Some(vec!["x".to_string()])
+        );
+        assert_eq!(
+            round_trip((1i64, "two".to_string(), true)),
+            (1, "two".to_string(), true)
+        );
+        assert_eq!(
+            round_trip(vec![(1.5f64, 'a'), (2.5, 'b')]),
+            vec![(1.5, 'a'), (2.5, 'b')]
+        );
+    }
+
+    #[test]
+    fn signatures_follow_the_rust_types() {
+        let a = TType::Generic {
+            name: "a".to_string(),
+        };
+        assert_eq!(
+            add().ttype,
+            TType::Function {
+                parameters: vec![TType::Int, TType::Int],
+                return_type: Box::new(TType::Int),
+            }
+        );
+        // the state is not a parameter of the nova signature
+        assert_eq!(
+            append().ttype,
+            TType::Function {
+                parameters: vec![
+                    TType::List {
+                        inner: Box::new(a.clone())
+                    },
+                    a
+                ],
+                return_type: Box::new(TType::Void),
+            }
+        );
+        assert_eq!(
+            <(i64, Option<String>) as Marshal>::ttype(),
+            TType::Tuple {
+                elements: vec![
+                    TType::Int,
+                    TType::Option {
+                        inner: Box::new(TType::String)
+                    }
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn natives_pop_their_arguments_and_push_the_result() {
+        let mut vm = crate::new();
+        call(add(), &mut vm, vec![VmData::Int(2), VmData::Int(3)]).unwrap();
+        assert_eq!(vm.state.stack, vec![VmData::Int(5)]);
+
+        let list = vec![1i64].into_vm(&mut vm.state);
+        call(append(), &mut vm, vec![list, VmData::Int(2)]).unwrap();
+        assert_eq!(vm.state.stack, vec![VmData::Int(5)]);
+        assert_eq!(Vec::<i64>::from_vm(&vm.state, list).unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn missing_arguments_are_an_error() {
+        let mut vm = crate::new();
+        let error = call(add(), &mut vm, vec![VmData::Int(2)]).unwrap_err();
+        assert_eq!(error.message(), "Stack is empty");
+    }
+
+    #[test]
+    fn arguments_of_the_wrong_type_are_an_error() {
+        let mut vm = crate::new();
+        let error = call(add(), &mut vm, vec![VmData::Int(2), VmData::Bool(true)]).unwrap_err();
+        assert_eq!(error.message(), "Expected an Int but found Bool(true)");
+
+        let error = call(append(), &mut vm, vec![VmData::Int(1), VmData::Int(2)]).unwrap_err();
+        assert_eq!(error.message(), "Expected a List but found Int(1)");
+
+        let mut state = crate::state::new();
+        let pair = Marshal::into_vm((1i64, 2i64), &mut state);
+        let error = <(i64, i64, i64)>::from_vm(&state, pair).unwrap_err();
+        assert_eq!(
+            error.message(),
+            format!("Expected a tuple of 3 but found {pair:?}")
+        );
+        assert!(Vec::<String>::from_vm(&state, pair).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use common::{error::NovaError, resolver::normalize};
+
+use crate::{bind::Marshal, CallBack, Vm};
+
+// what a native may reach outside of the vm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    FsRead,
+    FsWrite,
+    Terminal,
+    Time,
+    Random,
+    Process,
+    Plugin,
+}
+
+pub const CAPABILITIES: [Capability; 7] = [
+    Capability::FsRead,
+    Capability::FsWrite,
+    Capability::Terminal,
+    Capability::Time,
+    Capability::Random,
+    Capability::Process,
+    Capability::Plugin,
+];
+
+impl Capability {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::FsRead => "fs-read",
+            Capability::FsWrite => "fs-write",
+            Capability::Terminal => "terminal",
+            Capability::Time => "time",
+            Capability::Random => "random",
+            Capability::Process => "process",
+            Capability::Plugin => "plugin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Capability> {
+        CAPABILITIES
+            .into_iter()
+            .find(|capability| capability.name() == name)
+    }
+
+    // natives of these take the path they touch as their first argument
+    pub fn takes_path(&self) -> bool {
+        matches!(self, Capability::FsRead | Capability::FsWrite)
+    }
+}
+
+// the capability each builtin native needs, natives not listed here only
+// touch the vm and are always allowed
+pub fn builtins() -> HashMap<String, Capability> {
+    [
+        ("readFile", Capability::FsRead),
+        ("writeFile", Capability::FsWrite),
+        ("readln", Capability::Terminal),
+        ("terminal::hideCursor", Capability::Terminal),
+        ("terminal::showCursor", Capability::Terminal),
+        ("terminal::rawmode", Capability::Terminal),
+        ("terminal::getch", Capability::Terminal),
+        ("terminal::rawread", Capability::Terminal),
+        ("terminal::clearScreen", Capability::Terminal),
+        ("sleep", Capability::Time),
+        ("random", Capability::Random),
+        ("terminal::args", Capability::Process),
+    ]
+    .into_iter()
+    .map(|(function_id, capability)| (function_id.to_string(), capability))
+    .collect()
+}
+
+// the capabilities a program was granted. fs-read, fs-write and plugin
+// can be limited to some directories, None means anywhere
+#[derive(Debug, Clone)]
+pub struct Permissions {
+    pub granted: HashMap<Capability, Option<Vec<PathBuf>>>,
+}
+
+// everything is granted unless the host says otherwise
+pub fn all() -> Permissions {
+    Permissions {
+        granted: CAPABILITIES
+            .into_iter()
+            .map(|capability| (capability, None))
+            .collect(),
+    }
+}
+
+pub fn none() -> Permissions {
+    Permissions {
+        granted: HashMap::default(),
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        all()
+    }
+}
+
+impl Permissions {
+    pub fn allow(&mut self, capability: Capability) {
+        self.granted.insert(capability, None);
+    }
+
+    // grants the capability below directory, a capability that is already
+    // granted everywhere stays that way
+    pub fn allow_path(&mut self, capability: Capability, directory: &str) {
+        let directory = absolute(Path::new(directory));
+        match self.granted.get_mut(&capability) {
+            Some(None) => {}
+            Some(Some(directories)) => directories.push(directory),
+            None => {
+                self.granted.insert(capability, Some(vec![directory]));
+            }
+        }
+    }
+
+    pub fn deny(&mut self, capability: Capability) {
+        self.granted.remove(&capability);
+    }
+
+    pub fn check(
+        &self,
+        capability: Capability,
+        function_id: &str,
+        path: Option<&str>,
+    ) -> Result<(), NovaError> {
+        let directories = match self.granted.get(&capability) {
+            Some(Some(directories)) => directories,
+            Some(None) => return Ok(()),
+            None => return Err(NovaError::Runtime {
+                msg: format!(
+                    "'{function_id}' needs the {} capability, which this program was not granted",
+                    capability.name()
+                ),
+            }),
+        };
+        // a native that is limited to some directories must say which path it touches
+        let Some(path) = path else {
+            return Err(NovaError::Runtime {
+                msg: format!(
+                    "'{function_id}' was not given a path, {} is only granted for some directories",
+                    capability.name()
+                ),
+            });
+        };
+        let resolved = absolute(Path::new(path));
+        if directories
+            .iter()
+            .any(|directory| resolved.starts_with(directory))
+        {
+            return Ok(());
+        }
+        Err(NovaError::Runtime {
+            msg: format!(
+                "'{function_id}' may not access '{path}', {} is only granted for {}",
+                capability.name(),
+                directories
+                    .iter()
+                    .map(|directory| format!("'{}'", directory.display()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        })
+    }
+}
+
+// the real location of a path, files that do not exist yet are placed in
+// their parent directory. symlinks are followed so they cannot leave a directory
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(path) = std::fs::canonicalize(path) {
+        return path;
+    }
+    if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if let Ok(parent) = std::fs::canonicalize(parent) {
+            return parent.join(name);
+        }
+    }
+    let path = std::env::current_dir().unwrap_or_default().join(path);
+    PathBuf::from(normalize(&path.to_string_lossy()))
+}
+
+// checks the permissions of the running program before the native runs.
+// arity is how many arguments the native takes off the stack
+pub fn guard(
+    capability: Capability,
+    function_id: &str,
+    arity: usize,
+    function: CallBack,
+) -> CallBack {
+    let function_id = function_id.to_string();
+    Arc::new(move |vm: &mut Vm| {
+        let path = match capability.takes_path() {
+            true => vm
+                .state
+                .stack
+                .len()
+                .checked_sub(arity)
+                .and_then(|first| vm.state.stack.get(first))
+                .and_then(|argument| String::from_vm(&vm.state, *argument).ok()),
+            false => None,
+        };
+        vm.state
+            .permissions
+            .check(capability, &function_id, path.as_deref())?;
+        function(vm)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh directory with an allowed and a secret subdirectory
+    fn directories(name: &str) -> (PathBuf, String) {
+        let root =
+            std::env::temp_dir().join(format!("nova-capability-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("allowed/inner")).unwrap();
+        std::fs::create_dir_all(root.join("secret")).unwrap();
+        std::fs::write(root.join("allowed/inner/file.txt"), "").unwrap();
+        std::fs::write(root.join("secret/file.txt"), "").unwrap();
+        let allowed = root.join("allowed").to_string_lossy().to_string();
+        (root, allowed)
+    }
+
+    fn path(root: &Path, relative: &str) -> String {
+        root.join(relative).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn allow_and_deny() {
+        let mut permissions = none();
+        let denied = permissions
+            .check(Capability::Time, "sleep", None)
+            .unwrap_err();
+        assert_eq!(
+            denied.message(),
+            "'sleep' needs the time capability, which this program was not granted"
+        );
+        permissions.allow(Capability::Time);
+        assert!(permissions.check(Capability::Time, "sleep", None).is_ok());
+        permissions.deny(Capability::Time);
+        assert!(permissions.check(Capability::Time, "sleep", None).is_err());
+        assert!(all()
+            .check(Capability::FsWrite, "writeFile", Some("/anywhere"))
+            .is_ok());
+    }
+
+    #[test]
+    fn paths_inside_the_directory() {
+        let (root, allowed) = directories("inside");
+        let mut permissions = none();
+        permissions.allow_path(Capability::FsRead, &allowed);
+        let check = |relative: &str| {
+            permissions.check(Capability::FsRead, "readFile", Some(&path(&root, relative)))
+        };
+        assert!(check("allowed/inner/file.txt").is_ok());
+        // files that do not exist yet are checked by their directory
+        assert!(check("allowed/new.txt").is_ok());
+        assert!(check("secret/file.txt")
+            .unwrap_err()
+            .message()
+            .contains("may not access"));
+        // only the granted capability is limited
+        assert!(permissions
+            .check(
+                Capability::FsWrite,
+                "writeFile",
+                Some(&path(&root, "allowed/new.txt"))
+            )
+            .is_err());
+        // a limited capability needs to know the path
+        assert!(permissions
+            .check(Capability::FsRead, "readFile", None)
+            .unwrap_err()
+            .message()
+            .contains("was not given a path"));
+    }
+
+    #[test]
+    fn dots_cannot_escape() {
+        let (root, allowed) = directories("dots");
+        let mut permissions = none();
+        permissions.allow_path(Capability::FsRead, &allowed);
+        for escape in [
+            "allowed/../secret/file.txt",
+            "allowed/inner/../../secret/file.txt",
+            "allowed/missing/../../secret/file.txt",
+            "allowed/..",
+        ] {
+            let checked =
+                permissions.check(Capability::FsRead, "readFile", Some(&path(&root, escape)));
+            assert!(checked.is_err(), "{escape} was allowed");
+        }
+        assert!(permissions
+            .check(
+                Capability::FsRead,
+                "readFile",
+                Some(&path(&root, "allowed/inner/../inner/file.txt"))
+            )
+            .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_escape() {
+        let (root, allowed) = directories("symlinks");
+        let link = root.join("allowed/link");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(root.join("secret"), &link).unwrap();
+        let mut permissions = none();
+        permissions.allow_path(Capability::FsRead, &allowed);
+        assert!(permissions
+            .check(
+                Capability::FsRead,
+                "readFile",
+                Some(&path(&root, "allowed/link/file.txt"))
+            )
+            .is_err());
+        assert!(permissions
+            .check(
+                Capability::FsRead,
+                "readFile",
+                Some(&path(&root, "allowed/link/new.txt"))
+            )
+            .is_err());
+        // a link to the allowed directory from outside of it is fine
+        let inward = root.join("secret/inward");
+        let _ = std::fs::remove_file(&inward);
+        std::os::unix::fs::symlink(root.join("allowed/inner"), &inward).unwrap();
+        assert!(permissions
+            .check(
+                Capability::FsRead,
+                "readFile",
+                Some(&path(&root, "secret/inward/file.txt"))
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn granted_everywhere_stays_everywhere() {
+        let (root, allowed) = directories("everywhere");
+        let mut permissions = none();
+        permissions.allow(Capability::FsRead);
+        permissions.allow_path(Capability::FsRead, &allowed);
+        assert!(permissions
+            .check(
+                Capability::FsRead,
+                "readFile",
+                Some(&path(&root, "secret/file.txt"))
+            )
+            .is_ok());
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use common::{error::NovaError, fileposition::FilePosition};
+
+use crate::{state::VmData, Vm};
+
+// decides where execution pauses next, the depth is the size of the
+// callstack when the command was given
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Step,
+    Next(usize),
+    Finish(usize),
+    Continue,
+}
+
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub filepath: String,
+    pub line: usize,
+    pub addresses: Vec<usize>,
+}
+
+// one entry of the backtrace, the innermost frame comes first
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: String,
+    pub address: usize,
+    pub position: Option<FilePosition>,
+    pub base: Option<usize>,
+    pub locals: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub mode: Mode,
+    pub globals: Vec<String>,
+    sources: HashMap<String, Vec<String>>,
+}
+
+pub fn new(globals: Vec<String>) -> Debugger {
+    Debugger {
+        breakpoints: vec![],
+        mode: Mode::Step,
+        globals,
+        sources: HashMap::default(),
+    }
+}
+
+impl Debugger {
+    pub fn run(&mut self, vm: &mut Vm) -> Result<(), NovaError> {
+        println!("Nova debugger, type help for a list of commands");
+        loop {
+            vm.checkpoint()?;
+            if self.should_stop(vm) {
+                self.show_location(vm);
+                if !self.prompt(vm)? {
+                    return Ok(());
+                }
+            }
+            if !vm.step()? {
+                break;
+            }
+        }
+        println!("program finished");
+        Ok(())
+    }
+
+    // execution only ever pauses at the start of a statement
+    pub fn should_stop(&self, vm: &Vm) -> bool {
+        let address = vm.state.current_instruction;
+        if !vm.debug_info.is_statement(address) {
+            return false;
+        }
+        if self.at_breakpoint(vm) {
+            return true;
+        }
+        let depth = vm.state.callstack.len();
+        match self.mode {
+            Mode::Step => true,
+            Mode::Next(start) => depth <= start,
+            Mode::Finish(start) => depth < start,
+            Mode::Continue => false,
+        }
+    }
+
+    pub fn at_breakpoint(&self, vm: &Vm) -> bool {
+        let address = vm.state.current_instruction;
+        self.breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.addresses.contains(&address))
+    }
+
+    // returns the number of the new breakpoint
+    pub fn add_breakpoint(
+        &mut self,
+        vm: &Vm,
+        filepath: &str,
+        line: usize,
+    ) -> Result<usize, NovaError> {
+        let addresses = vm.debug_info.addresses(filepath, line);
+        if addresses.is_empty() {
+            return Err(NovaError::Runtime {
+                msg: format!("no statement starts at {filepath}:{line}"),
+            });
+        }
+        self.breakpoints.push(Breakpoint {
+            filepath: filepath.to_string(),
+            line,
+            addresses,
+        });
+        Ok(self.breakpoints.len())
+    }
+
+    // walks the callstack, every return address points back into the caller
+    pub fn frames(&self, vm: &Vm) -> Vec<Frame> {
+        let depth = vm.state.callstack.len();
+        let mut frames = vec![];
+        for level in (0..=depth).rev() {
+            let (address, lookup) = if level == depth {
+                let address = vm.state.current_instruction;
+                (address, address)
+            } else {
+                let address = vm.state.callstack[level];
+                (address, address.saturating_sub(1))
+            };
+            let scope = vm.debug_info.scope(lookup);
+            frames.push(Frame {
+                name: scope.map_or("?".to_string(), |scope| scope.name.clone()),
+                address,
+                position: vm.debug_info.position_before(lookup + 1).cloned(),
+                // the first window holds the globals
+                base: vm.state.window.get(level + 1).copied(),
+                locals: scope.map_or(vec![], |scope| scope.locals.clone()),
+            });
+        }
+        frames
+    }
+
+    pub fn locals(&self, vm: &Vm, frame: &Frame) -> Vec<(String, VmData)> {
+        let Some(base) = frame.base else {
+            return vec![];
+        };
+        frame
+            .locals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                vm.state
+                    .stack
+                    .get(base + index)
+                    .map(|value| (name.clone(), *value))
+            })
+            .collect()
+    }
+
+    pub fn globals(&self, vm: &Vm) -> Vec<(String, VmData)> {
+        self.globals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                vm.state
+                    .stack
+                    .get(index)
+                    .map(|value| (name.clone(), *value))
+            })
+            .collect()
+    }
+
+    // locals of the current frame shadow the top level ones, which shadow the globals
+    pub fn lookup(&self, vm: &Vm, name: &str) -> Option<VmData> {
+        let frames = self.frames(vm);
+        let (current, main) = (frames.first()?, frames.last()?);
+        self.locals(vm, current)
+            .into_iter()
+            .chain(self.locals(vm, main))
+            .chain(self.globals(vm))
+            .find(|(local, _)| local == name)
+            .map(|(_, value)| value)
+    }
+
+    fn show_location(&mut self, vm: &Vm) {
+        let address = vm.state.current_instruction;
+        if let Some(position) = vm.debug_info.position(address).cloned() {
+            println!("{}:{}", position.filepath, position.line);
+            if let Some(source) = self.source_line(&position.filepath, position.line) {
+                println!("{:>5} | {}", position.line, source);
+            }
+        }
+    }
+
+    fn source_line(&mut self, filepath: &str, line: usize) -> Option<String> {
+        let lines = self.sources.entry(filepath.to_string()).or_insert_with(|| {
+            common::fileposition::load_file_content(filepath)
+                .map(|content| content.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        lines.get(line.checked_sub(1)?).cloned()
+    }
+
+    // reads commands until one of them resumes execution,
+    // returns false when the session should end
+    fn prompt(&mut self, vm: &mut Vm) -> Result<bool, NovaError> {
+        let stdin = io::stdin();
+        loop {
+            print!("(dbg) ");
+            io::stdout().flush().expect("");
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) => return Ok(false),
+                Ok(_) => {}
+                Err(_) => {
+                    return Err(NovaError::File {
+                        msg: "Could not read from stdin".to_string(),
+                    })
+                }
+            }
+            let mut words = line.split_whitespace();
+            let depth = vm.state.callstack.len();
+            match (words.next(), words.next()) {
+                (None, _) => continue,
+                (Some("s" | "step"), None) => {
+                    self.mode = Mode::Step;
+                    return Ok(true);
+                }
+                (Some("n" | "next"), None) => {
+                    self.mode = Mode::Next(depth);
+                    return Ok(true);
+                }
+                (Some("f" | "finish"), None) => {
+                    self.mode = Mode::Finish(depth);
+                    return Ok(true);
+                }
+                (Some("c" | "continue"), None) => {
+                    self.mode = Mode::Continue;
+                    return Ok(true);
+                }
+                (Some("q" | "quit"), None) => return Ok(false),
+                (Some("b" | "break"), Some(location)) => {
+                    let current = vm
+                        .debug_info
+                        .position(vm.state.current_instruction)
+                        .map(|position| position.filepath.clone())
+                        .unwrap_or_default();
+                    let (filepath, line) = match location.rsplit_once(':') {
+                        Some((filepath, line)) => (filepath.to_string(), line),
+                        None => (current, location),
+                    };
+                    let Ok(line) = line.parse::<usize>() else {
+                        println!("expected a location like file.nv:12");
+                        continue;
+                    };
+                    match self.add_breakpoint(vm, &filepath, line) {
+                        Ok(number) => println!("breakpoint {number} at {filepath}:{line}"),
+                        Err(error) => error.show(),
+                    }
+                }
+                (Some("d" | "delete"), Some(number)) => match number.parse::<usize>() {
+                    Ok(number) if number >= 1 && number <= self.breakpoints.len() => {
+                        self.breakpoints.remove(number - 1);
+                    }
+                    _ => println!("no breakpoint {number}"),
+                },
+                (Some("breakpoints"), None) => {
+                    for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+                        println!("{}: {}:{}", index + 1, breakpoint.filepath, breakpoint.line);
+                    }
+                }
+                (Some("bt" | "backtrace"), None) => {
+                    for (index, frame) in self.frames(vm).iter().enumerate() {
+                        match &frame.position {
+                            Some(position) => println!(
+                                "#{index} {} at {}:{}",
+                                frame.name, position.filepath, position.line
+                            ),
+                            None => println!("#{index} {}", frame.name),
+                        }
+                    }
+                }
+                (Some("l" | "locals"), None) => {
+                    if let Some(frame) = self.frames(vm).first() {
+                        for (name, value) in self.locals(vm, frame) {
+                            // the debugger talks to the terminal, not to the program's streams
+                            println!("{name} = {}", vm.describe(value));
+                        }
+                    }
+                }
+                (Some("p" | "print"), Some(name)) => match self.lookup(vm, name) {
+                    Some(value) => println!("{}", vm.describe(value)),
+                    None => println!("no variable named {name}"),
+                },
+                (Some("h" | "help"), None) => print_help(),
+                _ => println!("unknown command, type help for a list of commands"),
+            }
+        }
+    }
+}
+
+fn print_help() {
+    println!("\tbreak [file:]line  // pause when the line is reached");
+    println!("\tdelete [n]         // remove breakpoint n");
+    println!("\tbreakpoints        // list the breakpoints");
+    println!("\tstep               // run to the next statement, entering calls");
+    println!("\tnext               // run to the next statement, stepping over calls");
+    println!("\tfinish             // run until the current function returns");
+    println!("\tcontinue           // run until a breakpoint is hit");
+    println!("\tbacktrace          // show the callstack");
+    println!("\tlocals             // show the locals of the current function");
+    println!("\tprint [name]       // show a local or global");
+    println!("\tquit               // stop the program");
+}
@@ -0,0 +1,267 @@
+use common::{
+    error::NovaError,
+    shape::{Layout, Shape},
+};
+
+use crate::{
+    state::{Heap, VmData},
+    Vm,
+};
+
+impl Vm {
+    // the length prefixed operand of LAYOUT, SHOWWITH and SHOW
+    pub(crate) fn text_operand(&mut self) -> String {
+        let mut size = [0; 8];
+        for byte in size.iter_mut() {
+            *byte = self.state.next();
+        }
+        let bytes = (0..u64::from_le_bytes(size))
+            .map(|_| self.state.next())
+            .collect::<Vec<u8>>();
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+
+    // the text of a value like Person { name: "bob", age: 42 }, Maybe::Just(3)
+    // or #(1, "a"). structs and enums carry their name in their hidden type
+    // field, so they show the same way when their static type is generic,
+    // tuples are only known by their static type. structs and enums with a
+    // show or toString extends function are shown by calling it
+    pub fn show(&mut self, value: VmData, shape: &Shape) -> Result<String, NovaError> {
+        self.show_in(value, shape, false, &mut vec![])
+    }
+
+    // a value the way print would show it, for the debuggers. the static
+    // type is not known there, structs and enums are found by their tag
+    pub fn describe(&mut self, value: VmData) -> String {
+        match self.show(value, &Shape::Value) {
+            Ok(text) => text,
+            Err(_) => self.state.format_vmdata(value),
+        }
+    }
+
+    // strings and chars are quoted inside any list, struct, enum or tuple,
+    // only a value shown on its own is not. lists already being shown further
+    // up are cut short, so values that contain themselves end
+    fn show_in(
+        &mut self,
+        value: VmData,
+        shape: &Shape,
+        quoted: bool,
+        seen: &mut Vec<usize>,
+    ) -> Result<String, NovaError> {
+        // an option is the value itself or None at runtime
+        if let Shape::Option(inner) = shape {
+            if value == VmData::None {
+                return Ok("None".to_string());
+            }
+            return self.show_in(value, inner, quoted, seen);
+        }
+        let index = match value {
+            VmData::List(index) | VmData::Struct(index) => index,
+            VmData::String(index) if quoted => {
+                return Ok(format!("{:?}", self.state.heap[index].get_string()))
+            }
+            VmData::Char(char) if quoted => return Ok(format!("{:?}", char)),
+            _ => return Ok(self.state.format_vmdata(value)),
+        };
+        let Heap::List(items) = &self.state.heap[index] else {
+            return Ok(self.state.format_vmdata(value));
+        };
+        let items = items.clone();
+        let shape = match (shape, self.state.tag_of(&items)) {
+            (Shape::Value | Shape::Generic(_), Some(name)) => Shape::Named(name, vec![]),
+            _ => shape.clone(),
+        };
+        if let Shape::Named(name, _) = &shape {
+            if let Some(function) = self.state.shows.get(name).copied() {
+                return match self.call(function, &[value])? {
+                    Some(VmData::String(index)) => {
+                        Ok(self.state.heap[index].get_string().to_string())
+                    }
+                    _ => Err(NovaError::Runtime {
+                        msg: format!("show of {name} must return a String"),
+                    }),
+                };
+            }
+        }
+        if seen.contains(&index) {
+            return Ok("...".to_string());
+        }
+        seen.push(index);
+        let text = match &shape {
+            Shape::Tuple(elements) => format!(
+                "#({})",
+                self.show_items(&items, elements, true, seen)?.join(", ")
+            ),
+            Shape::Named(name, arguments) => match self.state.layouts.get(name).cloned() {
+                Some(Layout::Struct {
+                    generics, fields, ..
+                }) => {
+                    let shapes = fields
+                        .iter()
+                        .map(|(_, shape)| shape.substitute(&generics, arguments))
+                        .collect::<Vec<Shape>>();
+                    let values = self.show_items(
+                        &items[..shapes.len().min(items.len())],
+                        &shapes,
+                        true,
+                        seen,
+                    )?;
+                    let fields = fields
+                        .iter()
+                        .zip(values)
+                        .map(|((field, _), value)| format!("{field}: {value}"))
+                        .collect::<Vec<String>>();
+                    if fields.is_empty() {
+                        format!("{name} {{}}")
+                    } else {
+                        format!("{name} {{ {} }}", fields.join(", "))
+                    }
+                }
+                Some(Layout::Enum {
+                    generics, variants, ..
+                }) => {
+                    let tag = match items.get(1).map(|item| self.state.to_vmdata(*item)) {
+                        Some(VmData::Int(tag)) => tag as usize,
+                        _ => 0,
+                    };
+                    match variants.get(tag) {
+                        Some((variant, Some(shape))) => {
+                            let shape = shape.substitute(&generics, arguments);
+                            let value = self.state.to_vmdata(items[0]);
+                            let value = self.show_in(value, &shape, true, seen)?;
+                            format!("{name}::{variant}({value})")
+                        }
+                        Some((variant, None)) => format!("{name}::{variant}"),
+                        None => format!("{name}::?"),
+                    }
+                }
+                None => format!("[{}]", self.show_items(&items, &[], true, seen)?.join(",")),
+            },
+            // lists are written the way print always wrote them
+            Shape::List(inner) => {
+                let shapes = vec![*inner.clone(); items.len()];
+                format!(
+                    "[{}]",
+                    self.show_items(&items, &shapes, true, seen)?.join(",")
+                )
+            }
+            _ => format!("[{}]", self.show_items(&items, &[], true, seen)?.join(",")),
+        };
+        seen.pop();
+        Ok(text)
+    }
+
+    // items past the end of the shapes are shown by their value alone
+    fn show_items(
+        &mut self,
+        items: &[usize],
+        shapes: &[Shape],
+        quoted: bool,
+        seen: &mut Vec<usize>,
+    ) -> Result<Vec<String>, NovaError> {
+        let mut shown = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let value = self.state.to_vmdata(*item);
+            let shape = shapes.get(index).unwrap_or(&Shape::Value);
+            shown.push(self.show_in(value, shape, quoted, seen)?);
+        }
+        Ok(shown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::shape::{Layout, Shape};
+
+    use crate::state::{Heap, VmData};
+
+    // a struct P { x: Int, items: [$A] } whose items may hold P itself
+    fn vm_with_struct() -> (crate::Vm, usize) {
+        let mut vm = crate::new();
+        vm.state.layouts.insert(
+            "P".to_string(),
+            Layout::Struct {
+                name: "P".to_string(),
+                generics: vec!["A".to_string()],
+                fields: vec![
+                    ("x".to_string(), Shape::Value),
+                    (
+                        "items".to_string(),
+                        Shape::List(Box::new(Shape::Generic("A".to_string()))),
+                    ),
+                ],
+            },
+        );
+        let tag = vm.state.allocate_string("P".to_string());
+        vm.state.tags.insert("P".to_string(), tag);
+        (vm, tag)
+    }
+
+    fn list(vm: &mut crate::Vm, items: &[VmData]) -> usize {
+        let cells = items
+            .iter()
+            .map(|item| vm.state.allocate_vmdata_to_heap(*item))
+            .collect();
+        vm.state.allocate_array(cells)
+    }
+
+    #[test]
+    fn generic_values_show_by_their_tag() {
+        let (mut vm, tag) = vm_with_struct();
+        let name = vm.state.allocate_string("bob".to_string());
+        let items = list(&mut vm, &[VmData::String(name)]);
+        let p = list(
+            &mut vm,
+            &[VmData::Int(1), VmData::List(items), VmData::String(tag)],
+        );
+        let shown = vm.show(VmData::List(p), &Shape::Generic("A".to_string()));
+        assert_eq!(shown.unwrap(), "P { x: 1, items: [\"bob\"] }");
+    }
+
+    #[test]
+    fn plain_lists_show_like_print() {
+        let (mut vm, _) = vm_with_struct();
+        // a string that only reads like the tag is not one
+        let a = vm.state.allocate_string("a".to_string());
+        let p = vm.state.allocate_string("P".to_string());
+        let inner = list(&mut vm, &[VmData::Int(1), VmData::Int(2)]);
+        let outer = list(&mut vm, &[VmData::List(inner), VmData::Int(3)]);
+        let strings = list(&mut vm, &[VmData::String(a), VmData::String(p)]);
+        let shape = Shape::List(Box::new(Shape::Value));
+        assert_eq!(
+            vm.show(VmData::List(outer), &Shape::Value).unwrap(),
+            vm.state.format_vmdata(VmData::List(outer))
+        );
+        assert_eq!(
+            vm.show(VmData::List(strings), &shape).unwrap(),
+            "[\"a\",\"P\"]"
+        );
+        assert_eq!(
+            vm.show(VmData::List(strings), &Shape::Value).unwrap(),
+            vm.state.format_vmdata(VmData::List(strings))
+        );
+    }
+
+    #[test]
+    fn cycles_are_cut_short() {
+        let (mut vm, tag) = vm_with_struct();
+        let items = list(&mut vm, &[]);
+        let p = list(
+            &mut vm,
+            &[VmData::Int(1), VmData::List(items), VmData::String(tag)],
+        );
+        let cell = vm.state.allocate_vmdata_to_heap(VmData::List(p));
+        vm.state.heap[items] = Heap::List(vec![cell]);
+        let shown = vm.show(VmData::List(p), &Shape::Value);
+        assert_eq!(shown.unwrap(), "P { x: 1, items: [...] }");
+    }
+
+    #[test]
+    fn options_show_their_value() {
+        let (mut vm, _) = vm_with_struct();
+        let shape = Shape::Option(Box::new(Shape::Value));
+        assert_eq!(vm.show(VmData::Int(3), &shape).unwrap(), "3");
+        assert_eq!(vm.show(VmData::None, &shape).unwrap(), "None");
+    }
+}
@@ -1,7 +1,13 @@
-use std::io::{self, Write};
+use std::{any::Any, cmp::Ordering, collections::HashMap, sync::Arc};
 
+use common::{code::Code, error::NovaError, shape::Layout};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    capability::{self, Permissions},
+    stream::Streams,
+};
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Heap {
     // pointer and instance
@@ -65,6 +71,11 @@ pub enum VmData {
     None,
 }
 
+// the collector never lets its threshold drop below this many live heap cells
+pub const GC_MIN_THRESHOLD: usize = 1 << 16;
+// after a collection the next one runs once the live heap has grown this much
+pub const GC_GROWTH_FACTOR: usize = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     pub program: Vec<u8>,
@@ -75,11 +86,27 @@ pub struct State {
     pub current_instruction: usize,
     pub offset: usize,
     pub window: Vec<usize>,
-    pub used_data: common::table::Table<usize>,
+    pub marks: Vec<u64>,
     pub threshold: usize,
     pub gc_count: usize,
     pub garbage_collected: usize,
     pub gclock: bool,
+    // the structs and enums of the program by name, and the show or toString
+    // functions that replace how they are shown
+    pub layouts: HashMap<String, Layout>,
+    pub shows: HashMap<String, VmData>,
+    // the heap string every struct or enum of a type keeps in its hidden
+    // type field, by name
+    pub tags: HashMap<String, usize>,
+    // data the host hands to its natives, shared with every copy of the state
+    #[serde(skip)]
+    pub context: Option<Arc<dyn Any + Send + Sync>>,
+    // what the natives of this program may do
+    #[serde(skip)]
+    pub permissions: Permissions,
+    // where print, readln and friends go, shared with every copy of the state
+    #[serde(skip)]
+    pub streams: Streams,
 }
 
 pub fn new() -> State {
@@ -92,85 +119,245 @@ pub fn new() -> State {
         window: vec![],
         heap: vec![],
         free_space: vec![],
-        used_data: common::table::new(),
-        threshold: 999999999,
+        marks: vec![],
+        threshold: GC_MIN_THRESHOLD,
         gc_count: 0,
         garbage_collected: 0,
         gclock: false,
+        layouts: HashMap::default(),
+        shows: HashMap::default(),
+        tags: HashMap::default(),
+        context: None,
+        permissions: capability::all(),
+        streams: Streams::default(),
     }
 }
 
 impl State {
-    // recursiely print out data for the Heap type, and ouly print out the value
-
-    pub fn print_heap(&self, index: usize) {
+    // recursiely format data for the Heap type, and ouly format the value
+    pub fn format_heap(&self, index: usize) -> String {
         // check if the index is out of bounds
         if index >= self.heap.len() {
-            io::stdout().flush().expect("");
-            return;
+            return String::new();
         }
         match &self.heap[index] {
-            Heap::ClosureAddress(v) => {
-                self.print_heap(*v);
-            }
-            Heap::Function(v) => {
-                print!("Function Pointer ({})", v);
-                io::stdout().flush().expect("");
-            }
-            Heap::Int(v) => {
-                print!("{}", v);
-                io::stdout().flush().expect("");
-            }
-            Heap::Float(v) => {
-                print!("{}", v);
-                io::stdout().flush().expect("");
-            }
-            Heap::Bool(v) => {
-                print!("{}", v);
-                io::stdout().flush().expect("");
-            }
-            Heap::ListAddress(v) => {
-                self.print_heap(*v);
-            }
-            Heap::StringAddress(v) => {
-                self.print_heap(*v);
-            }
-            Heap::None => {
-                print!("None");
-                io::stdout().flush().expect("");
-            }
-            Heap::Closure(function_poiner, capture_index) => {
-                print!("Closure (");
-                print!("Function Pointer: {}", function_poiner);
-                print!(", ");
-                print!("Captures: ");
-                self.print_heap(*capture_index);
-                print!(")");
-            }
+            Heap::ClosureAddress(v) => self.format_heap(*v),
+            Heap::Function(v) => format!("Function Pointer ({})", v),
+            Heap::Int(v) => v.to_string(),
+            Heap::Float(v) => v.to_string(),
+            Heap::Bool(v) => v.to_string(),
+            Heap::ListAddress(v) => self.format_heap(*v),
+            Heap::StringAddress(v) => self.format_heap(*v),
+            Heap::None => "None".to_string(),
+            Heap::Closure(function_poiner, capture_index) => format!(
+                "Closure (Function Pointer: {}, Captures: {})",
+                function_poiner,
+                self.format_heap(*capture_index)
+            ),
             Heap::List(v) => {
-                print!("[");
-                for (i, item) in v.iter().enumerate() {
-                    self.print_heap(*item);
-                    if i < v.len() - 1 {
-                        print!(",");
-                    }
-                }
-                print!("]");
-            }
-            Heap::String(v) => {
-                print!("{}", v);
-                io::stdout().flush().expect("");
+                let items = v
+                    .iter()
+                    .map(|item| self.format_item(*item))
+                    .collect::<Vec<String>>();
+                format!("[{}]", items.join(","))
             }
+            Heap::String(v) => v.clone(),
             Heap::Struct(_, _) => {
                 todo!()
             }
-            Heap::StructAddress(v) => {
-                self.print_heap(*v);
+            Heap::StructAddress(v) => self.format_heap(*v),
+            Heap::Char(v) => v.to_string(),
+        }
+    }
+
+    // strings and chars inside a list are quoted, the same as inside a struct
+    fn format_item(&self, index: usize) -> String {
+        match self.heap.get(index) {
+            Some(Heap::String(v)) => format!("{:?}", v),
+            Some(Heap::Char(v)) => format!("{:?}", v),
+            Some(Heap::StringAddress(v)) => self.format_item(*v),
+            _ => self.format_heap(index),
+        }
+    }
+
+    pub fn format_vmdata(&self, item: VmData) -> String {
+        match item {
+            VmData::Function(v) => format!("Function Pointer ({})", v),
+            VmData::Int(v) => v.to_string(),
+            VmData::Float(v) => v.to_string(),
+            VmData::Bool(v) => v.to_string(),
+            VmData::None => "None".to_string(),
+            VmData::List(index) => self.format_heap(index),
+            VmData::String(index) => self.format_heap(index),
+            VmData::Closure(v) => self.format_heap(v),
+            VmData::StackAddress(v) => self.format_vmdata(self.stack[self.offset + v]),
+            VmData::Struct(v) => self.format_heap(v),
+            VmData::Char(char) => char.to_string(),
+        }
+    }
+
+    pub fn print_heap(&self, index: usize) -> Result<(), NovaError> {
+        self.write(&self.format_heap(index))
+    }
+
+    pub fn print_vmdata(&self, item: VmData) -> Result<(), NovaError> {
+        self.write(&self.format_vmdata(item))
+    }
+
+    // the shared heap string structs and enums of a type carry as their
+    // last field, made the first time the type is used
+    pub fn tag(&mut self, name: String) -> usize {
+        match self.tags.get(&name) {
+            Some(index) => *index,
+            None => {
+                let index = self.allocate_string(name.clone());
+                self.tags.insert(name, index);
+                index
             }
-            Heap::Char(v) => {
-                print!("{}", v);
-                io::stdout().flush().expect("");
+        }
+    }
+
+    // the name in the hidden type field of a struct or enum, it is always
+    // the shared string of a type tag. plain lists and tuples have none
+    pub fn tag_of(&self, items: &[usize]) -> Option<String> {
+        let Heap::StringAddress(string) = self.heap[*items.last()?] else {
+            return None;
+        };
+        self.tags
+            .iter()
+            .find(|(_, index)| **index == string)
+            .map(|(name, _)| name.clone())
+    }
+
+    // the host context, if one of type T was set
+    pub fn context<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.context.as_ref()?.downcast_ref::<T>()
+    }
+
+    // program output goes to stdout unless the host plugged in its own stream
+    pub fn write(&self, text: &str) -> Result<(), NovaError> {
+        self.streams
+            .stdout
+            .write(text)
+            .map_err(|e| NovaError::Runtime {
+                msg: format!("Error writing output: {}", e),
+            })
+    }
+
+    pub fn write_error(&self, text: &str) -> Result<(), NovaError> {
+        self.streams
+            .stderr
+            .write(text)
+            .map_err(|e| NovaError::Runtime {
+                msg: format!("Error writing error output: {}", e),
+            })
+    }
+
+    // the next line of input without its line ending, empty at the end of the input
+    pub fn read_line(&self) -> Result<String, NovaError> {
+        let mut line = String::new();
+        self.streams
+            .stdin
+            .read_line(&mut line)
+            .map_err(|e| NovaError::Runtime {
+                msg: format!("Error reading line: {}", e),
+            })?;
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(line)
+    }
+
+    // structural equality, lists, tuples, structs and enums are equal when
+    // all of their items are
+    pub fn equal(&self, a: VmData, b: VmData) -> bool {
+        self.equal_in(a, b, &mut vec![])
+    }
+
+    // pairs of lists already being compared count as equal, so values that
+    // contain themselves do not recurse forever
+    fn equal_in(&self, a: VmData, b: VmData, seen: &mut Vec<(usize, usize)>) -> bool {
+        match (a, b) {
+            (VmData::String(a), VmData::String(b)) => {
+                a == b || self.heap[a].get_string() == self.heap[b].get_string()
             }
+            (VmData::List(a), VmData::List(b)) | (VmData::Struct(a), VmData::Struct(b)) => {
+                if a == b || seen.contains(&(a, b)) {
+                    return true;
+                }
+                let (items, other) = match (&self.heap[a], &self.heap[b]) {
+                    (Heap::List(items), Heap::List(other)) => (items, other),
+                    (Heap::Struct(name, items), Heap::Struct(other_name, other))
+                        if name == other_name =>
+                    {
+                        (items, other)
+                    }
+                    _ => return false,
+                };
+                if items.len() != other.len() {
+                    return false;
+                }
+                seen.push((a, b));
+                let result = items
+                    .iter()
+                    .zip(other.iter())
+                    .all(|(a, b)| self.equal_in(self.to_vmdata(*a), self.to_vmdata(*b), seen));
+                seen.pop();
+                result
+            }
+            _ => a == b,
+        }
+    }
+
+    // lexicographic ordering of strings, lists and tuples, None orders
+    // before any value
+    pub fn compare(&self, a: VmData, b: VmData) -> Ordering {
+        self.compare_in(a, b, &mut vec![])
+    }
+
+    fn compare_in(&self, a: VmData, b: VmData, seen: &mut Vec<(usize, usize)>) -> Ordering {
+        match (a, b) {
+            (VmData::Int(a), VmData::Int(b)) => a.cmp(&b),
+            (VmData::Float(a), VmData::Float(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (VmData::Bool(a), VmData::Bool(b)) => a.cmp(&b),
+            (VmData::Char(a), VmData::Char(b)) => a.cmp(&b),
+            (VmData::String(a), VmData::String(b)) => {
+                self.heap[a].get_string().cmp(self.heap[b].get_string())
+            }
+            (VmData::List(a), VmData::List(b)) => {
+                if a == b || seen.contains(&(a, b)) {
+                    return Ordering::Equal;
+                }
+                let (Heap::List(items), Heap::List(other)) = (&self.heap[a], &self.heap[b]) else {
+                    return Ordering::Equal;
+                };
+                seen.push((a, b));
+                let (items, other) = (self.in_order(items), self.in_order(other));
+                let result = items
+                    .iter()
+                    .zip(other.iter())
+                    .map(|(a, b)| self.compare_in(self.to_vmdata(*a), self.to_vmdata(*b), seen))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(items.len().cmp(&other.len()));
+                seen.pop();
+                result
+            }
+            (VmData::None, VmData::None) => Ordering::Equal,
+            (VmData::None, _) => Ordering::Less,
+            (_, VmData::None) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+    }
+
+    // enums hold their payload before the variant, they order by the
+    // variant first
+    fn in_order(&self, items: &[usize]) -> Vec<usize> {
+        match self.tag_of(items).and_then(|name| self.layouts.get(&name)) {
+            Some(Layout::Enum { .. }) if items.len() == 3 => vec![items[1], items[0], items[2]],
+            _ => items.to_vec(),
         }
     }
 
@@ -212,66 +399,116 @@ impl State {
     }
 
     #[inline(always)]
-    pub fn check_useage(&mut self, index: usize) {
-        if !self.used_data.has(&index) {
-            self.used_data.insert(index);
-            let href = self.heap[index].clone();
-            match href {
-                Heap::List(list) => {
-                    for i in list.iter() {
-                        self.check_useage(*i)
-                    }
-                }
-                Heap::ListAddress(index) => self.check_useage(index),
-                Heap::StringAddress(index) => self.check_useage(index),
-                Heap::ClosureAddress(index) => self.check_useage(index),
-                Heap::Closure(_, indextwo) => self.check_useage(indextwo),
-                _ => {}
+    fn is_marked(&self, index: usize) -> bool {
+        self.marks[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    // marks a heap cell, returns false if it was already marked
+    #[inline(always)]
+    fn mark(&mut self, index: usize) -> bool {
+        if index >= self.heap.len() || self.is_marked(index) {
+            return false;
+        }
+        self.marks[index / 64] |= 1 << (index % 64);
+        true
+    }
+
+    // the heap cell a value on the stack keeps alive, if any
+    #[inline(always)]
+    fn root(item: &VmData) -> Option<usize> {
+        match item {
+            VmData::List(index)
+            | VmData::String(index)
+            | VmData::Closure(index)
+            | VmData::Struct(index) => Some(*index),
+            VmData::StackAddress(_)
+            | VmData::Function(_)
+            | VmData::Int(_)
+            | VmData::Float(_)
+            | VmData::Bool(_)
+            | VmData::Char(_)
+            | VmData::None => None,
+        }
+    }
+
+    // marks everything reachable from the stack, which holds the globals,
+    // every call frame and all temporaries, from the show functions and
+    // from the type tags
+    pub fn mark_reachable(&mut self) {
+        self.marks.clear();
+        self.marks.resize(self.heap.len() / 64 + 1, 0);
+
+        let mut worklist: Vec<usize> = self
+            .stack
+            .iter()
+            .chain(self.shows.values())
+            .filter_map(State::root)
+            .chain(self.tags.values().copied())
+            .collect();
+        while let Some(index) = worklist.pop() {
+            if !self.mark(index) {
+                continue;
+            }
+            match &self.heap[index] {
+                Heap::List(list) => worklist.extend(list.iter()),
+                Heap::Struct(_, fields) => worklist.extend(fields.iter()),
+                Heap::Closure(_, captures) => worklist.push(*captures),
+                Heap::ListAddress(index)
+                | Heap::StringAddress(index)
+                | Heap::ClosureAddress(index)
+                | Heap::StructAddress(index) => worklist.push(*index),
+                Heap::Function(_)
+                | Heap::Int(_)
+                | Heap::Float(_)
+                | Heap::Bool(_)
+                | Heap::Char(_)
+                | Heap::String(_)
+                | Heap::None => {}
             }
         }
     }
 
+    // number of heap cells currently in use
+    #[inline(always)]
+    pub fn live_heap(&self) -> usize {
+        self.heap.len() - self.free_space.len()
+    }
+
+    // only call between instructions, when every live value is on the stack
     #[inline(always)]
     pub fn collect_garbage(&mut self) {
-        if self.gclock {
+        if self.gclock || self.live_heap() < self.threshold {
             return;
         }
+        self.force_collect_garbage();
+    }
 
-        // only run when out of free space and over threshold
-        if self.threshold <= self.heap.len() {
-            self.threshold = self.heap.len() * 11 / 10;
-            //dbg!(&self.threshold);
-        } else {
+    pub fn force_collect_garbage(&mut self) {
+        if self.gclock {
             return;
         }
-
         self.gc_count += 1;
-        self.used_data.clear();
-        for item in self.stack.clone().iter() {
-            match item {
-                VmData::List(index) => {
-                    self.check_useage(*index);
-                }
-                VmData::String(index) => {
-                    self.check_useage(*index);
-                }
-                VmData::Closure(index) => {
-                    self.check_useage(*index);
-                }
-                _ => {}
+        let live = self.live_heap();
+        self.mark_reachable();
+
+        // drop unused cells from the end so the heap can shrink
+        while let Some(last) = self.heap.len().checked_sub(1) {
+            if self.is_marked(last) {
+                break;
             }
+            self.heap.pop();
         }
 
-        for i in 0..self.heap.len() {
-            if !self.used_data.has(&i) {
-                self.free_heap(i);
-                self.garbage_collected += 1;
-                if i == self.heap.len() {
-                    self.heap.pop();
-                }
+        self.free_space.clear();
+        for index in 0..self.heap.len() {
+            if !self.is_marked(index) {
+                self.heap[index] = Heap::None;
+                self.free_space.push(index);
             }
         }
-        //dbg!(&self.garbage_collected);
+
+        self.garbage_collected += live.saturating_sub(self.live_heap());
+        self.threshold = GC_MIN_THRESHOLD.max(self.live_heap() * GC_GROWTH_FACTOR);
     }
 
     #[inline(always)]
@@ -396,14 +633,8 @@ impl State {
         if let Some(space) = self.free_space.pop() {
             space
         } else {
-            self.collect_garbage();
-            if let Some(space) = self.free_space.pop() {
-                self.heap[space] = Heap::None;
-                space
-            } else {
-                self.heap.push(Heap::None);
-                self.heap.len() - 1
-            }
+            self.heap.push(Heap::None);
+            self.heap.len() - 1
         }
     }
 
@@ -424,14 +655,8 @@ impl State {
             self.heap[space] = Heap::String(str);
             space
         } else {
-            self.collect_garbage();
-            if let Some(space) = self.free_space.pop() {
-                self.heap[space] = Heap::String(str);
-                space
-            } else {
-                self.heap.push(Heap::String(str));
-                self.heap.len() - 1
-            }
+            self.heap.push(Heap::String(str));
+            self.heap.len() - 1
         }
     }
 
@@ -453,6 +678,29 @@ impl State {
         }
     }
 
+    // replaces the current frame with the arguments on top of the stack
+    // and jumps to the function, the return address is left untouched
+    #[inline(always)]
+    pub fn tail_call(&mut self, target: usize) {
+        let arguments = if self.program[target] == Code::OFFSET {
+            u32::from_le_bytes([
+                self.program[target + 1],
+                self.program[target + 2],
+                self.program[target + 3],
+                self.program[target + 4],
+            ]) as usize
+        } else {
+            0
+        };
+        if let Some(window) = self.window.pop() {
+            let arguments = self.stack.split_off(self.stack.len() - arguments);
+            self.stack.truncate(window);
+            self.stack.extend(arguments);
+        }
+        self.offset = *self.window.last().unwrap();
+        self.goto(target);
+    }
+
     #[inline(always)]
     pub fn deallocate_registers(&mut self) {
         if let Some(window) = self.window.pop() {
@@ -477,3 +725,147 @@ impl State {
         self.stack.push(returnvalue);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use common::shape::{Layout, Shape};
+
+    use super::{Heap, State, VmData};
+
+    fn list(state: &mut State, items: &[VmData]) -> VmData {
+        let cells = items
+            .iter()
+            .map(|item| state.allocate_vmdata_to_heap(*item))
+            .collect();
+        VmData::List(state.allocate_array(cells))
+    }
+
+    fn string(state: &mut State, text: &str) -> VmData {
+        VmData::String(state.allocate_string(text.to_string()))
+    }
+
+    // a struct or enum value, the items followed by the tag of its type
+    fn tagged(state: &mut State, name: &str, items: &[VmData]) -> VmData {
+        let tag = VmData::String(state.tag(name.to_string()));
+        list(state, &[items, &[tag]].concat())
+    }
+
+    // Maybe::Just(payload) or Maybe::Nothing
+    fn maybe(state: &mut State, variant: i64, payload: VmData) -> VmData {
+        state.layouts.insert(
+            "Maybe".to_string(),
+            Layout::Enum {
+                name: "Maybe".to_string(),
+                generics: vec![],
+                variants: vec![
+                    ("Just".to_string(), Some(Shape::Value)),
+                    ("Nothing".to_string(), None),
+                ],
+            },
+        );
+        tagged(state, "Maybe", &[payload, VmData::Int(variant)])
+    }
+
+    #[test]
+    fn nested_lists_compare_item_by_item() {
+        let mut state = super::new();
+        let a = list(&mut state, &[VmData::Int(1), VmData::Int(2)]);
+        let b = list(&mut state, &[VmData::Int(3)]);
+        let left = list(&mut state, &[a, b]);
+        let c = list(&mut state, &[VmData::Int(1), VmData::Int(2)]);
+        let d = list(&mut state, &[VmData::Int(3)]);
+        let right = list(&mut state, &[c, d]);
+        assert!(state.equal(left, right));
+        assert_eq!(state.compare(left, right), Ordering::Equal);
+
+        let e = list(&mut state, &[VmData::Int(4)]);
+        let bigger = list(&mut state, &[c, e]);
+        assert!(!state.equal(left, bigger));
+        assert_eq!(state.compare(left, bigger), Ordering::Less);
+
+        // a list that runs out first orders first
+        let shorter = list(&mut state, &[a]);
+        assert!(!state.equal(shorter, left));
+        assert_eq!(state.compare(shorter, left), Ordering::Less);
+    }
+
+    #[test]
+    fn struct_fields_compare_in_order() {
+        let mut state = super::new();
+        let p = tagged(&mut state, "P", &[VmData::Int(1), VmData::Int(2)]);
+        let same = tagged(&mut state, "P", &[VmData::Int(1), VmData::Int(2)]);
+        let swapped = tagged(&mut state, "P", &[VmData::Int(2), VmData::Int(1)]);
+        let other = tagged(&mut state, "Q", &[VmData::Int(1), VmData::Int(2)]);
+        assert!(state.equal(p, same));
+        assert!(!state.equal(p, swapped));
+        assert!(!state.equal(p, other));
+        assert_eq!(state.compare(p, swapped), Ordering::Less);
+        assert_eq!(state.compare(swapped, p), Ordering::Greater);
+    }
+
+    #[test]
+    fn enums_compare_variant_then_payload() {
+        let mut state = super::new();
+        let one = maybe(&mut state, 0, VmData::Int(1));
+        let also_one = maybe(&mut state, 0, VmData::Int(1));
+        let two = maybe(&mut state, 0, VmData::Int(2));
+        let nothing = maybe(&mut state, 1, VmData::None);
+        let nothing_with_one = maybe(&mut state, 1, VmData::Int(1));
+        assert!(state.equal(one, also_one));
+        assert!(!state.equal(one, two));
+        assert!(!state.equal(one, nothing));
+        assert!(!state.equal(one, nothing_with_one));
+
+        assert_eq!(state.compare(one, two), Ordering::Less);
+        // the variant decides before the payload is looked at
+        let big = maybe(&mut state, 0, VmData::Int(9));
+        assert_eq!(state.compare(big, nothing_with_one), Ordering::Less);
+        assert_eq!(state.compare(nothing, one), Ordering::Greater);
+    }
+
+    #[test]
+    fn strings_compare_by_content() {
+        let mut state = super::new();
+        // b lives at the lower heap index
+        let b = string(&mut state, "b");
+        let a = string(&mut state, "a");
+        let also_a = string(&mut state, "a");
+        assert_ne!(a, also_a);
+        assert!(state.equal(a, also_a));
+        assert!(!state.equal(a, b));
+        assert_eq!(state.compare(a, also_a), Ordering::Equal);
+        assert_eq!(state.compare(a, b), Ordering::Less);
+        assert_eq!(state.compare(b, a), Ordering::Greater);
+
+        let left = list(&mut state, &[a]);
+        let right = list(&mut state, &[also_a]);
+        assert!(state.equal(left, right));
+    }
+
+    // [head, the list itself]
+    fn cycle(state: &mut State, head: i64) -> VmData {
+        let VmData::List(index) = list(state, &[VmData::Int(head)]) else {
+            unreachable!()
+        };
+        let itself = state.allocate_vmdata_to_heap(VmData::List(index));
+        if let Heap::List(items) = &mut state.heap[index] {
+            items.push(itself);
+        }
+        VmData::List(index)
+    }
+
+    #[test]
+    fn lists_that_contain_themselves_terminate() {
+        let mut state = super::new();
+        let a = cycle(&mut state, 1);
+        let b = cycle(&mut state, 1);
+        let c = cycle(&mut state, 2);
+        assert!(state.equal(a, a));
+        assert!(state.equal(a, b));
+        assert!(!state.equal(a, c));
+        assert_eq!(state.compare(a, b), Ordering::Equal);
+        assert_eq!(state.compare(a, c), Ordering::Less);
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+// where the output of a program goes, the process streams are used
+// unless the host plugs in its own writer
+#[derive(Clone, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    Stderr,
+    Writer(Arc<Mutex<dyn Write + Send>>),
+}
+
+pub fn writer(writer: impl Write + Send + 'static) -> Output {
+    Output::Writer(Arc::new(Mutex::new(writer)))
+}
+
+impl Output {
+    // every write is flushed so prompts show up before the program reads
+    pub fn write(&self, text: &str) -> io::Result<()> {
+        match self {
+            Output::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()
+            }
+            Output::Stderr => io::stderr().lock().write_all(text.as_bytes()),
+            Output::Writer(writer) => {
+                let mut writer = writer.lock().unwrap();
+                writer.write_all(text.as_bytes())?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Output::Stdout => write!(f, "Stdout"),
+            Output::Stderr => write!(f, "Stderr"),
+            Output::Writer(_) => write!(f, "Writer"),
+        }
+    }
+}
+
+// where a program reads its input from. stdin is only locked while a line
+// is read, so a debugger can share it with the program
+#[derive(Clone, Default)]
+pub enum Input {
+    #[default]
+    Stdin,
+    Reader(Arc<Mutex<dyn BufRead + Send>>),
+}
+
+pub fn reader(reader: impl BufRead + Send + 'static) -> Input {
+    Input::Reader(Arc::new(Mutex::new(reader)))
+}
+
+impl Input {
+    // reads up to and including the next newline, nothing is read at the end of the input
+    pub fn read_line(&self, line: &mut String) -> io::Result<usize> {
+        match self {
+            Input::Stdin => io::stdin().lock().read_line(line),
+            Input::Reader(reader) => reader.lock().unwrap().read_line(line),
+        }
+    }
+}
+
+impl std::fmt::Debug for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Stdin => write!(f, "Stdin"),
+            Input::Reader(_) => write!(f, "Reader"),
+        }
+    }
+}
+
+// collects output in memory for the host to look at, clones share the same buffer
+#[derive(Debug, Clone, Default)]
+pub struct Buffer {
+    bytes: Arc<Mutex<Vec<u8>>>,
+}
+
+pub fn buffer() -> Buffer {
+    Buffer::default()
+}
+
+impl Buffer {
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.bytes.lock().unwrap()).to_string()
+    }
+
+    // the contents so far, the buffer starts over empty
+    pub fn take(&self) -> String {
+        let bytes = std::mem::take(&mut *self.bytes.lock().unwrap());
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.bytes.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// the three standard streams of a program
+#[derive(Debug, Clone)]
+pub struct Streams {
+    pub stdout: Output,
+    pub stderr: Output,
+    pub stdin: Input,
+}
+
+impl Default for Streams {
+    fn default() -> Self {
+        Streams {
+            stdout: Output::Stdout,
+            stderr: Output::Stderr,
+            stdin: Input::Stdin,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use common::{error::NovaError, shape::Layout, ttype::TType};
+
+use crate::state::{Heap, State, VmData};
+
+// a value owned by the host, it lives outside of the vm heap so it can be
+// kept around while the garbage collector runs. options are their inner
+// value or None, tuples are lists of their elements
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    String(String),
+    List(Vec<Value>),
+    Struct {
+        name: String,
+        fields: Vec<Value>,
+    },
+    // variant is the position of the variant in the enum, the value of a
+    // variant without one is None
+    Enum {
+        name: String,
+        variant: usize,
+        value: Box<Value>,
+    },
+    None,
+}
+
+impl Value {
+    // a shallow check that the value can be passed where ttype is expected
+    pub fn fits(&self, ttype: &TType) -> bool {
+        match (self, ttype) {
+            (_, TType::Any | TType::Generic { .. }) => true,
+            (Value::None, TType::Option { .. } | TType::Void | TType::None) => true,
+            (value, TType::Option { inner }) => value.fits(inner),
+            (Value::Int(_), TType::Int)
+            | (Value::Float(_), TType::Float)
+            | (Value::Bool(_), TType::Bool)
+            | (Value::Char(_), TType::Char)
+            | (Value::String(_), TType::String) => true,
+            (Value::List(items), TType::List { inner }) => {
+                items.iter().all(|item| item.fits(inner))
+            }
+            (Value::List(items), TType::Tuple { elements }) => {
+                items.len() == elements.len()
+                    && items
+                        .iter()
+                        .zip(elements.iter())
+                        .all(|(item, element)| item.fits(element))
+            }
+            (
+                Value::Struct { name, .. } | Value::Enum { name, .. },
+                TType::Custom { name: other, .. },
+            ) => name == other,
+            _ => false,
+        }
+    }
+}
+
+impl State {
+    // copies a value into the vm, strings and lists are allocated on the heap.
+    // the result is only safe from the collector once it is on the stack
+    pub fn from_value(&mut self, value: &Value) -> VmData {
+        match value {
+            Value::Int(v) => VmData::Int(*v),
+            Value::Float(v) => VmData::Float(*v),
+            Value::Bool(v) => VmData::Bool(*v),
+            Value::Char(v) => VmData::Char(*v),
+            Value::None => VmData::None,
+            Value::String(v) => VmData::String(self.allocate_string(v.clone())),
+            Value::List(items) => {
+                let mut cells = vec![];
+                for item in items.iter() {
+                    let item = self.from_value(item);
+                    cells.push(self.allocate_vmdata_to_heap(item));
+                }
+                VmData::List(self.allocate_array(cells))
+            }
+            Value::Struct { name, fields } => {
+                let mut cells = vec![];
+                for field in fields.iter() {
+                    let field = self.from_value(field);
+                    cells.push(self.allocate_vmdata_to_heap(field));
+                }
+                let tag = self.tag(name.clone());
+                cells.push(self.allocate_vmdata_to_heap(VmData::String(tag)));
+                VmData::List(self.allocate_array(cells))
+            }
+            Value::Enum {
+                name,
+                variant,
+                value,
+            } => {
+                let value = self.from_value(value);
+                let tag = self.tag(name.clone());
+                let cells = vec![
+                    self.allocate_vmdata_to_heap(value),
+                    self.allocate_vmdata_to_heap(VmData::Int(*variant as i64)),
+                    self.allocate_vmdata_to_heap(VmData::String(tag)),
+                ];
+                VmData::List(self.allocate_array(cells))
+            }
+        }
+    }
+
+    // copies a value out of the vm, data that contains itself has no host
+    // value and is an error
+    pub fn to_value(&self, item: VmData) -> Result<Value, NovaError> {
+        self.to_value_in(item, &mut vec![])
+    }
+
+    fn to_value_in(&self, item: VmData, seen: &mut Vec<usize>) -> Result<Value, NovaError> {
+        match item {
+            VmData::Int(v) => Ok(Value::Int(v)),
+            VmData::Float(v) => Ok(Value::Float(v)),
+            VmData::Bool(v) => Ok(Value::Bool(v)),
+            VmData::Char(v) => Ok(Value::Char(v)),
+            VmData::None => Ok(Value::None),
+            VmData::StackAddress(v) => self.to_value_in(self.stack[self.offset + v], seen),
+            VmData::String(index) | VmData::List(index) | VmData::Struct(index) => {
+                self.heap_to_value(index, seen)
+            }
+            VmData::Function(_) | VmData::Closure(_) => Err(NovaError::Runtime {
+                msg: "functions cannot be passed back to the host".to_string(),
+            }),
+        }
+    }
+
+    // seen holds the lists being copied, one that shows up inside itself
+    // would never finish
+    fn heap_to_value(&self, index: usize, seen: &mut Vec<usize>) -> Result<Value, NovaError> {
+        match self.heap.get(index) {
+            Some(Heap::String(v)) => Ok(Value::String(v.clone())),
+            Some(Heap::List(cells)) => {
+                if seen.contains(&index) {
+                    return Err(NovaError::Runtime {
+                        msg: "data that contains itself cannot be passed back to the host"
+                            .to_string(),
+                    });
+                }
+                seen.push(index);
+                let value = self.list_to_value(cells, seen);
+                seen.pop();
+                value
+            }
+            Some(Heap::StringAddress(v) | Heap::ListAddress(v) | Heap::StructAddress(v)) => {
+                self.heap_to_value(*v, seen)
+            }
+            _ => Err(NovaError::Runtime {
+                msg: format!("heap cell {index} does not hold a value the host can read"),
+            }),
+        }
+    }
+
+    // structs and enums are lists that end in the tag of their type
+    fn list_to_value(&self, cells: &[usize], seen: &mut Vec<usize>) -> Result<Value, NovaError> {
+        let mut items = vec![];
+        let name = self.tag_of(cells);
+        let fields = if name.is_some() {
+            &cells[..cells.len() - 1]
+        } else {
+            cells
+        };
+        for cell in fields.iter() {
+            items.push(self.to_value_in(self.to_vmdata(*cell), seen)?);
+        }
+        let Some(name) = name else {
+            return Ok(Value::List(items));
+        };
+        match (self.layouts.get(&name), items.as_slice()) {
+            (Some(Layout::Enum { .. }), [value, Value::Int(variant)]) => Ok(Value::Enum {
+                variant: *variant as usize,
+                value: Box::new(value.clone()),
+                name,
+            }),
+            _ => Ok(Value::Struct {
+                name,
+                fields: items,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use crate::state::{self, Heap, VmData};
+    use common::shape::{Layout, Shape};
+
+    fn state_with_types() -> state::State {
+        let mut state = state::new();
+        state.layouts.insert(
+            "P".to_string(),
+            Layout::Struct {
+                name: "P".to_string(),
+                generics: vec![],
+                fields: vec![
+                    ("x".to_string(), Shape::Value),
+                    ("items".to_string(), Shape::List(Box::new(Shape::Value))),
+                ],
+            },
+        );
+        state.layouts.insert(
+            "Shape".to_string(),
+            Layout::Enum {
+                name: "Shape".to_string(),
+                generics: vec![],
+                variants: vec![
+                    ("Circle".to_string(), Some(Shape::Value)),
+                    ("Dot".to_string(), None),
+                ],
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let mut state = state_with_types();
+        let values = [
+            Value::Int(-3),
+            Value::String("a".to_string()),
+            Value::List(vec![Value::List(vec![Value::Float(1.5)]), Value::None]),
+            Value::Struct {
+                name: "P".to_string(),
+                fields: vec![
+                    Value::Int(1),
+                    Value::List(vec![Value::Char('c'), Value::Bool(true)]),
+                ],
+            },
+            Value::Enum {
+                name: "Shape".to_string(),
+                variant: 0,
+                value: Box::new(Value::Float(2.0)),
+            },
+            Value::Enum {
+                name: "Shape".to_string(),
+                variant: 1,
+                value: Box::new(Value::None),
+            },
+        ];
+        for value in values {
+            let item = state.from_value(&value);
+            assert_eq!(state.to_value(item).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn structs_do_not_leak_their_tag() {
+        let mut state = state_with_types();
+        let tag = state.tag("P".to_string());
+        let cells = vec![
+            state.allocate_vmdata_to_heap(VmData::Int(1)),
+            state.allocate_vmdata_to_heap(VmData::String(tag)),
+        ];
+        let p = state.allocate_array(cells);
+        assert_eq!(
+            state.to_value(VmData::List(p)).unwrap(),
+            Value::Struct {
+                name: "P".to_string(),
+                fields: vec![Value::Int(1)],
+            }
+        );
+        // a string that only reads like the tag keeps the list a list
+        let text = state.allocate_string("P".to_string());
+        let cells = vec![state.allocate_vmdata_to_heap(VmData::String(text))];
+        let list = state.allocate_array(cells);
+        assert_eq!(
+            state.to_value(VmData::List(list)).unwrap(),
+            Value::List(vec![Value::String("P".to_string())])
+        );
+    }
+
+    #[test]
+    fn cyclic_data_is_an_error() {
+        let mut state = state_with_types();
+        let inner = state.allocate_array(vec![]);
+        let cell = state.allocate_vmdata_to_heap(VmData::List(inner));
+        let outer = state.allocate_array(vec![cell]);
+        let back = state.allocate_vmdata_to_heap(VmData::List(outer));
+        state.heap[inner] = Heap::List(vec![back]);
+        let error = state.to_value(VmData::List(outer)).unwrap_err();
+        assert!(error.message().contains("contains itself"));
+
+        // the same list twice is shared, not a cycle
+        let shared = state.allocate_array(vec![]);
+        let cells = vec![
+            state.allocate_vmdata_to_heap(VmData::List(shared)),
+            state.allocate_vmdata_to_heap(VmData::List(shared)),
+        ];
+        let twice = state.allocate_array(cells);
+        assert_eq!(
+            state.to_value(VmData::List(twice)).unwrap(),
+            Value::List(vec![Value::List(vec![]), Value::List(vec![])])
+        );
+    }
+
+    #[test]
+    fn values_fit_their_types() {
+        use common::ttype::TType;
+        let p = TType::Custom {
+            name: "P".to_string(),
+            type_params: vec![],
+        };
+        let struct_p = Value::Struct {
+            name: "P".to_string(),
+            fields: vec![],
+        };
+        assert!(struct_p.fits(&p));
+        assert!(!Value::List(vec![]).fits(&p));
+        assert!(!Value::Struct {
+            name: "Q".to_string(),
+            fields: vec![],
+        }
+        .fits(&p));
+    }
+}